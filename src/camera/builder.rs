@@ -9,7 +9,9 @@ const DEFAULT_VFOV: f32 = 90.0;
 const DEFAULT_VIEWPORT_HEIGHT: f32 = 2.0;
 const DEFAULT_IMAGE_WIDTH: u32 = 100;
 const DEFAULT_SAMPLES_PER_PIXEL: u16 = 10;
+const DEFAULT_ADAPTIVE_SAMPLES_FACTOR: u16 = 8;
 const DEFAULT_MAX_DEPTH: u16 = 10;
+const DEFAULT_TILE_SIZE: u32 = 16;
 const DEFAULT_LOOK_FROM: Point3 = Point3::from_origin();
 const DEFAULT_LOOK_AT: Point3 = Point3::new(0.0, 0.0, -1.0);
 const DEFAULT_VUP: Vec3 = Vec3::new(0.0, 1.0, 0.0);
@@ -17,7 +19,7 @@ const DEFAULT_BACKGROUND: Color = BLACK;
 
 // ─────────────────────────────
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct ViewportBuilderParams {
     look_at: Option<Point3>,
     look_from: Option<Point3>,
@@ -40,8 +42,7 @@ impl ViewportBuilderParams {
 
 // ─────────────────────────────
 
-#[derive(Default)]
-
+#[derive(Default, Clone)]
 struct LensBuilderParams {
     defocus_angle: Option<f32>,
     focus_dist: Option<f32>,
@@ -58,34 +59,53 @@ impl LensBuilderParams {
 
 // ─────────────────────────────
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct RenderBuilderParams {
     background: Option<Color>,
     aspect_ratio: Option<f32>,
     image_width: Option<u32>,
     samples_per_pixel: Option<u16>,
     max_depth: Option<u16>,
+    adaptive_threshold: Option<f32>,
+    max_samples_per_pixel: Option<u16>,
 }
 
 impl RenderBuilderParams {
-    fn build(self) -> (Color, f32, u32, u16, u16) {
+    fn build(self) -> (Color, f32, u32, u16, u16, Option<(f32, u16)>) {
+        let samples_per_pixel = self.samples_per_pixel.unwrap_or(DEFAULT_SAMPLES_PER_PIXEL);
+        let adaptive = self.adaptive_threshold.map(|threshold| {
+            let max = self
+                .max_samples_per_pixel
+                .unwrap_or(samples_per_pixel.saturating_mul(DEFAULT_ADAPTIVE_SAMPLES_FACTOR));
+            (threshold, max)
+        });
         (
             self.background.unwrap_or(DEFAULT_BACKGROUND),
             self.aspect_ratio.unwrap_or(DEFAULT_ASPECT_RATIO),
             self.image_width.unwrap_or(DEFAULT_IMAGE_WIDTH),
-            self.samples_per_pixel.unwrap_or(DEFAULT_SAMPLES_PER_PIXEL),
+            samples_per_pixel,
             self.max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+            adaptive,
         )
     }
 }
 
 // ─────────────────────────────
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct CameraBuilder {
     viewport: ViewportBuilderParams,
     lens: LensBuilderParams,
     render: RenderBuilderParams,
+    integrator: Option<Arc<dyn Integrator>>,
+    sampler: SamplerKind,
+    filter: Filter,
+    tile_size: Option<u32>,
+    tile_order: TileOrder,
+    crop: Option<Region>,
+    aovs: Vec<Aov>,
+    denoiser: Option<Denoiser>,
+    seed: u64,
 }
 
 impl CameraBuilder {
@@ -153,11 +173,85 @@ impl CameraBuilder {
         self
     }
 
+    /// Enables adaptive sampling: after `samples_per_pixel` samples, pixels keep taking as many
+    /// again until the standard error of their luminance is below `threshold` times the
+    /// luminance, or they reach [`max_samples_per_pixel`](Self::max_samples_per_pixel).
+    pub fn adaptive_threshold(mut self, threshold: f32) -> Self {
+        self.render.adaptive_threshold = Some(threshold);
+        self
+    }
+
+    /// Most samples a pixel takes with adaptive sampling; 8 times `samples_per_pixel` by
+    /// default.
+    pub fn max_samples_per_pixel(mut self, samples: u16) -> Self {
+        self.render.max_samples_per_pixel = Some(samples);
+        self
+    }
+
+    /// Light transport algorithm used per sample; [`PathTracer`] by default.
+    pub fn integrator(mut self, integrator: Arc<dyn Integrator>) -> Self {
+        self.integrator = Some(integrator);
+        self
+    }
+
+    /// How the random numbers of the samples of each pixel are spread;
+    /// [`SamplerKind::Sobol`] by default.
+    pub fn sampler(mut self, sampler: SamplerKind) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    /// The filter that spreads each sample over the pixels around it;
+    /// a box filter of half a pixel, which keeps samples in their own pixel, by default.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Side of the square tiles the image is cut into for the threads; 16 pixels by default.
+    pub fn tile_size(mut self, size: u32) -> Self {
+        self.tile_size = Some(size);
+        self
+    }
+
+    /// The order tiles are handed out in; [`TileOrder::Hilbert`] by default.
+    pub fn tile_order(mut self, order: TileOrder) -> Self {
+        self.tile_order = order;
+        self
+    }
+
+    /// Renders only `region` of the image, cut to the image, with the same samples a render
+    /// of the whole image takes there. With adaptive sampling, pixels near the edges of the
+    /// region may stop after fewer or more of them, as the noise beyond it is unknown.
+    pub fn crop(mut self, region: Region) -> Self {
+        self.crop = Some(region);
+        self
+    }
+
+    /// Extra channels to record alongside the image; none by default.
+    pub fn aovs(mut self, aovs: Vec<Aov>) -> Self {
+        self.aovs = aovs;
+        self
+    }
+
+    /// Filters the noise out of the image once rendered; off by default.
+    pub fn denoiser(mut self, denoiser: Denoiser) -> Self {
+        self.denoiser = Some(denoiser);
+        self
+    }
+
+    /// Seed of the random numbers drawn while rendering; 0 by default. The same seed gives the
+    /// same image, however many threads render it.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
     #[inline(always)]
     pub fn build(self) -> Camera {
         let (look_from, look_at, vup, vfov, viewport_height_factor) = self.viewport.build();
         let (focus_dist, defocus_angle) = self.lens.build();
-        let (background, aspect_ratio, image_width, samples_per_pixel, max_depth) =
+        let (background, aspect_ratio, image_width, samples_per_pixel, max_depth, adaptive) =
             self.render.build();
 
         let image_width_f = image_width as f32;
@@ -187,16 +281,36 @@ impl CameraBuilder {
         let defocus_disk_u = defocus_radius * u;
         let defocus_disk_v = defocus_radius * v;
 
+        let render = RenderParams::new(
+            background,
+            max_depth,
+            image_width,
+            image_height,
+            samples_per_pixel,
+            adaptive,
+        );
+
+        let full_image = Region::new(0, 0, image_width, image_height);
+
         Camera {
             viewport: ViewportParams::new(look_from, pixel00_loc, pixel_delta_u, pixel_delta_v),
             lens: LensParams::new(defocus_angle, defocus_disk_u, defocus_disk_v),
-            render: RenderParams::new(
-                background,
-                max_depth,
-                image_width,
-                image_height,
-                samples_per_pixel,
+            integrator: self
+                .integrator
+                .unwrap_or_else(|| Arc::new(PathTracer::new())),
+            sampler: self.sampler.build(render.max_samples_per_pixel, self.seed),
+            sampler_kind: self.sampler,
+            filter: self.filter,
+            tiles: TileParams::new(
+                self.crop
+                    .map_or(full_image, |crop| crop.clip(image_width, image_height)),
+                self.tile_size.unwrap_or(DEFAULT_TILE_SIZE),
+                self.tile_order,
             ),
+            render,
+            aovs: self.aovs,
+            denoiser: self.denoiser,
+            seed: self.seed,
         }
     }
 }
@@ -1,4 +1,11 @@
-use super::*;
+use std::hash::{Hash, Hasher};
+
+use super::{
+    checkpoint::{Fnv1a, RenderState},
+    filter::FilmSample,
+    framebuffer::*,
+    *,
+};
 
 use rayon::prelude::*;
 
@@ -25,10 +32,13 @@ pub struct RenderParams {
     pub background: Color,
     pub image_width: u32,
     pub image_height: u32,
-    pub pixel_samples_scale: f32,
-    pub recip_sqrt_spp: f32,
     pub max_depth: u16,
-    pub sqrt_spp: u16,
+    /// Samples taken in every pixel, the least with adaptive sampling.
+    pub samples_per_pixel: u32,
+    /// Samples a pixel may take with adaptive sampling, `samples_per_pixel` otherwise.
+    pub max_samples_per_pixel: u32,
+    /// Relative error at which adaptive sampling leaves a pixel, if enabled.
+    pub adaptive_threshold: Option<f32>,
 }
 
 impl RenderParams {
@@ -38,16 +48,21 @@ impl RenderParams {
         image_width: u32,
         image_height: u32,
         samples_per_pixel: u16,
+        adaptive: Option<(f32, u16)>,
     ) -> Self {
-        let sqrt_spp = samples_per_pixel.isqrt();
+        let samples_per_pixel = u32::from(samples_per_pixel.max(1));
+        let (adaptive_threshold, max_samples_per_pixel) = match adaptive {
+            Some((threshold, max)) => (Some(threshold), u32::from(max).max(samples_per_pixel)),
+            None => (None, samples_per_pixel),
+        };
         Self {
             background,
             max_depth,
             image_width,
             image_height,
-            sqrt_spp,
-            pixel_samples_scale: 1.0 / (sqrt_spp * sqrt_spp) as f32,
-            recip_sqrt_spp: 1.0 / sqrt_spp as f32,
+            samples_per_pixel,
+            max_samples_per_pixel,
+            adaptive_threshold,
         }
     }
 }
@@ -76,151 +91,565 @@ impl ViewportParams {
     }
 }
 // ─────────────────────────────
+#[derive(Clone)]
+pub struct TileParams {
+    /// The pixels rendered: the whole image unless cropped.
+    pub region: Region,
+    pub size: u32,
+    pub order: TileOrder,
+}
+
+impl TileParams {
+    pub fn new(region: Region, size: u32, order: TileOrder) -> Self {
+        Self {
+            region,
+            size,
+            order,
+        }
+    }
+}
+// ─────────────────────────────
 pub struct Camera {
     pub(crate) viewport: ViewportParams,
     pub(crate) lens: LensParams,
     pub(crate) render: RenderParams,
+    pub(crate) tiles: TileParams,
+    pub(crate) integrator: Arc<dyn Integrator>,
+    pub(crate) sampler: Arc<dyn Sampler>,
+    pub(crate) sampler_kind: SamplerKind,
+    pub(crate) filter: Filter,
+    pub(crate) aovs: Vec<Aov>,
+    pub(crate) denoiser: Option<Denoiser>,
+    pub(crate) seed: u64,
 }
 
 impl Camera {
-    pub fn render(&self, world: &dyn Hittable, lights: &dyn Primitive) -> Vec<f32> {
-        let capacity = (self.render.image_width * self.render.image_height * 3) as usize;
-        let mut pixels = Vec::with_capacity(capacity);
-
-        let progress = Progress::new(self.render.image_height as usize);
-
-        let rows: Vec<Vec<f32>> = (0..self.render.image_height)
-            .into_par_iter()
-            .map(|j| {
-                progress.inc();
-
-                let mut row_pixels = Vec::with_capacity(self.render.image_width as usize * 3);
-                for i in 0..self.render.image_width {
-                    let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-
-                    for s_j in 0..self.render.sqrt_spp {
-                        for s_i in 0..self.render.sqrt_spp {
-                            let ray = self.get_ray(i, j, s_i, s_j);
-                            pixel_color +=
-                                self.ray_color(&ray, world, lights, self.render.max_depth);
+    /// Renders `world` into a [`FrameBuffer`] holding the image and the [`Aov`] layers this
+    /// camera was built with, denoised if it has a [`Denoiser`].
+    pub fn render(&self, world: &dyn Hittable, lights: &dyn Primitive) -> FrameBuffer {
+        self.frame(&self.render_progressive(world, lights, None, &mut |_| {}))
+    }
+
+    /// Renders `world` pass by pass, calling `on_pass` with the samples so far after each pass,
+    /// and returns them all.
+    ///
+    /// A pass takes one more sample in every pixel, or with adaptive sampling a batch of
+    /// `samples_per_pixel` in every pixel still too noisy. Starting from the state of an earlier
+    /// render of the same scene with this camera, given as `resume`, only the samples it lacks
+    /// are taken, and the result is the same as that of a render never stopped.
+    ///
+    /// # Panics
+    ///
+    /// If `resume` holds samples for another image size, crop or filter. States read by
+    /// [`RenderState::load`] under this camera's [`checkpoint_key`](Self::checkpoint_key)
+    /// always fit, since the key covers all three.
+    pub fn render_progressive(
+        &self,
+        world: &dyn Hittable,
+        lights: &dyn Primitive,
+        resume: Option<RenderState>,
+        on_pass: &mut dyn FnMut(&RenderState),
+    ) -> RenderState {
+        let film = self.film();
+        let (width, height) = (film.width, film.height);
+        let max = self.render.max_samples_per_pixel;
+        let batch = match self.render.adaptive_threshold {
+            Some(_) => self.render.samples_per_pixel,
+            None => 1,
+        };
+        let mut state = resume.unwrap_or_else(|| RenderState::new(width, height));
+        assert!(
+            state.width == width && state.height == height,
+            "resuming a {}x{} render with a {}x{} camera",
+            state.width,
+            state.height,
+            width,
+            height
+        );
+
+        let tiles = self.tiles.order.tiles(width, height, self.tiles.size);
+        let mut tile_of = vec![0; state.pixels.len()];
+        for (t, tile) in tiles.iter().enumerate() {
+            for y in tile.y..tile.y + tile.height {
+                let row = (y * width) as usize;
+                tile_of[row + tile.x as usize..row + (tile.x + tile.width) as usize].fill(t);
+            }
+        }
+
+        let fewest = state.pixels.iter().map(PixelSamples::samples).min();
+        let rounds = max.saturating_sub(fewest.unwrap_or(0));
+        let progress = Progress::new(tiles.len() * rounds.max(1) as usize);
+
+        let ctx = RenderContext {
+            world,
+            lights,
+            background: &self.render.background,
+            max_depth: self.render.max_depth,
+        };
+        rng::seed_thread(self.seed, rng::PREPROCESS_STREAM);
+        self.integrator.preprocess(&ctx);
+
+        let aovs = self.recorded_aovs();
+        let split_light = aovs.iter().any(|aov| aov.is_radiance());
+        let find_surface = aovs.iter().any(|aov| aov.is_surface());
+
+        let take_sample = |i: u32, j: u32, s: u32, pixel: &mut PixelSamples| {
+            rng::seed_thread(self.seed, self.sample_index(i, j, s));
+            sampler::with_sample(&self.sampler, [i, j], s, || {
+                let [x, y] = math::random_float_2d();
+                let ray = self.get_ray(i, j, [x, y]);
+                let light = if split_light {
+                    self.integrator.light_paths(&ray, &ctx)
+                } else {
+                    LightPaths::emitted(self.integrator.radiance(&ray, &ctx))
+                };
+                pixel.add_light(&light);
+
+                if find_surface {
+                    if let Some(rec) = first_surface(&ray, world) {
+                        pixel.add_surface(&ray, &rec);
+                    }
+                }
+                FilmSample {
+                    offset: [x - 0.5, y - 0.5],
+                    light,
+                }
+            })
+        };
+
+        // Filters that reach no pixel but a sample's own add it there straight away.
+        let splat = self.filter.margin() > 0;
+        let mut samples: Vec<Option<FilmSample>> = state.pixels.iter().map(|_| None).collect();
+
+        // With adaptive sampling, passes go on over the pixels still too noisy until none is
+        // left.
+        loop {
+            let mut active: Vec<bool> = state
+                .pixels
+                .iter()
+                .map(|pixel| pixel.samples() < max)
+                .collect();
+            if let Some(threshold) = self.render.adaptive_threshold {
+                let noisy = noisy_pixels(&state.pixels, width as usize, threshold);
+                active
+                    .iter_mut()
+                    .zip(noisy)
+                    .for_each(|(a, noisy)| *a &= noisy);
+            }
+            if !active.contains(&true) {
+                break;
+            }
+
+            // A pass takes its samples in rounds of one per pixel, each splatted through the
+            // filter before the next.
+            for _ in 0..batch {
+                // Threads take the tiles in order as they become free.
+                let mut work: Vec<Vec<_>> = tiles.iter().map(|_| Vec::new()).collect();
+                let pixels = state.pixels.iter_mut().zip(samples.iter_mut());
+                for (p, (pixel, sample)) in pixels.enumerate() {
+                    if active[p] && pixel.samples() < max {
+                        work[tile_of[p]].push((p, pixel, sample));
+                    }
+                }
+                work.into_iter().par_bridge().for_each(|pixels| {
+                    progress.inc();
+                    for (p, pixel, slot) in pixels {
+                        let i = film.x + p as u32 % width;
+                        let j = film.y + p as u32 / width;
+                        let sample = take_sample(i, j, pixel.samples(), pixel);
+                        if splat {
+                            *slot = Some(sample);
+                        } else {
+                            let [x, y] = sample.offset;
+                            pixel.add_weighted(self.filter.weight(x, y), &sample.light);
                         }
                     }
+                });
 
-                    let scaled_color = self.render.pixel_samples_scale * pixel_color;
-                    row_pixels.extend_from_slice(&[
-                        scaled_color.x(),
-                        scaled_color.y(),
-                        scaled_color.z(),
-                    ]);
+                if splat {
+                    self.filter
+                        .splat(&mut state.pixels, width as usize, &samples);
+                    samples.fill_with(|| None);
                 }
-                row_pixels
+            }
+            state.passes += 1;
+            on_pass(&state);
+        }
+
+        progress.finish();
+        state
+    }
+
+    /// The image and layers of the samples in `state`, denoised if this camera has a
+    /// [`Denoiser`].
+    pub fn frame(&self, state: &RenderState) -> FrameBuffer {
+        let (region, film) = (self.tiles.region, self.film());
+        let aovs = self.recorded_aovs();
+        let rows = (0..region.height)
+            .map(|y| {
+                let start = ((region.y - film.y + y) * film.width + region.x - film.x) as usize;
+                let mut row = Row::new(region.width, &aovs);
+                for pixel in &state.pixels[start..start + region.width as usize] {
+                    row.push(&aovs, pixel);
+                }
+                row
             })
             .collect();
+        let mut frame = FrameBuffer::from_rows(region.width, region.height, &aovs, rows);
+        if let Some(denoiser) = &self.denoiser {
+            denoiser.apply(&mut frame);
+            frame.retain_layers(&self.aovs);
+        }
+        frame
+    }
+
+    /// The pixels sampled: the region rendered and, around a crop, the pixels whose samples
+    /// the filter spreads into it.
+    fn film(&self) -> Region {
+        let region = self.tiles.region;
+        let margin = self.filter.margin();
+        let (x, y) = (
+            region.x.saturating_sub(margin),
+            region.y.saturating_sub(margin),
+        );
+        let width = region.x.saturating_add(region.width).saturating_add(margin) - x;
+        let height = region
+            .y
+            .saturating_add(region.height)
+            .saturating_add(margin)
+            - y;
+        Region::new(x, y, width, height).clip(self.render.image_width, self.render.image_height)
+    }
 
-        for row in rows {
-            pixels.extend(row);
+    /// The layers asked for, and the feature layers the denoiser needs whether or not they
+    /// were.
+    fn recorded_aovs(&self) -> Vec<Aov> {
+        let mut aovs = self.aovs.clone();
+        if self.denoiser.is_some() {
+            for feature in DENOISE_FEATURES {
+                if !aovs.contains(&feature) {
+                    aovs.push(feature);
+                }
+            }
         }
+        aovs
+    }
 
-        progress.finish();
+    /// A hash of everything that decides what the samples of this camera add up to, combined
+    /// with `scene`, which should tell the scene and the integrator apart. Checkpoints only
+    /// resume under the key they were saved with; the sample counts are left out, so a resumed
+    /// render may take more samples than the first. The image size, crop and filter are in, so
+    /// [`RenderState::load`] rejects the samples of a differently sized film.
+    pub fn checkpoint_key(&self, scene: impl Hash) -> u64 {
+        let mut hasher = Fnv1a::new();
+        scene.hash(&mut hasher);
+
+        let viewport = &self.viewport;
+        let lens = &self.lens;
+        let vectors = [
+            viewport.center.to_vec3(),
+            viewport.pixel00_loc.to_vec3(),
+            viewport.pixel_delta_u.clone(),
+            viewport.pixel_delta_v.clone(),
+            lens.defocus_disk_u.clone(),
+            lens.defocus_disk_v.clone(),
+            self.render.background.clone(),
+        ];
+        for c in vectors.iter().flat_map(|v| [v.x(), v.y(), v.z()]) {
+            hasher.write_u32(c.to_bits());
+        }
+        hasher.write_u32(lens.defocus_angle.to_bits());
+        hasher.write_u32(self.render.image_width);
+        hasher.write_u32(self.render.image_height);
+        self.tiles.region.hash(&mut hasher);
+        self.filter.to_string().hash(&mut hasher);
+        hasher.write_u16(self.render.max_depth);
+        hasher.write_u64(self.seed);
+        self.sampler_kind.to_string().hash(&mut hasher);
+
+        // Which sums the samples keep, as layers only record when asked for.
+        let aovs = self.recorded_aovs();
+        hasher.write_u8(aovs.iter().any(|aov| aov.is_radiance()) as u8);
+        hasher.write_u8(aovs.iter().any(|aov| aov.is_surface()) as u8);
+        hasher.finish()
+    }
 
-        pixels
+    /// Numbers the samples of the image, pixel by pixel, to key their random number streams.
+    /// The numbers do not depend on the sample count, so that resumed renders can add samples.
+    fn sample_index(&self, i: u32, j: u32, s: u32) -> u64 {
+        let pixel = u64::from(j) * u64::from(self.render.image_width) + u64::from(i);
+        pixel << 32 | u64::from(s)
     }
 
-    //Antialiasing
-    fn get_ray(&self, i: u32, j: u32, s_i: u16, s_j: u16) -> Ray {
-        let offset = sample_square_stratified(s_i, s_j, self.render.recip_sqrt_spp);
+    /// The camera ray of the current sample through the point `[x, y]` of pixel `(i, j)`,
+    /// drawing the next three dimensions of the sample: the position on the lens, and the
+    /// time.
+    fn get_ray(&self, i: u32, j: u32, [x, y]: [f32; 2]) -> Ray {
         let pixel_sample = &self.viewport.pixel00_loc
-            + ((i as f32 + offset.x()) * &self.viewport.pixel_delta_u)
-            + ((j as f32 + offset.y()) * &self.viewport.pixel_delta_v);
+            + ((i as f32 + x - 0.5) * &self.viewport.pixel_delta_u)
+            + ((j as f32 + y - 0.5) * &self.viewport.pixel_delta_v);
 
+        // Drawn even without depth of field, so the dimensions after it stay the same.
+        let lens = Point3::random_in_unit_disk();
         let ray_origin = if self.lens.defocus_angle <= 0.0 {
             self.viewport.center.clone()
         } else {
-            self.defocus_disk_sample()
+            self.defocus_disk_sample(&lens)
         };
         let ray_direction = pixel_sample - &ray_origin;
 
         Ray::new(ray_origin, ray_direction).set_time(math::random_float())
     }
 
-    fn defocus_disk_sample(&self) -> Point3 {
-        let point = Point3::random_in_unit_disk();
+    fn defocus_disk_sample(&self, point: &Point3) -> Point3 {
         &self.viewport.center
             + (point.x() * &self.lens.defocus_disk_u)
             + (point.y() * &self.lens.defocus_disk_v)
     }
 
-    fn ray_color(
-        &self,
-        ray: &Ray,
-        world: &dyn Hittable,
-        lights: &dyn Primitive,
-        depth: u16,
-    ) -> Color {
-        if depth == 0 {
-            return BLACK;
-        }
-
-        let rec = match world.hit(ray, Interval::CAMERA_VIEW) {
-            Some(rec) => rec,
-            None => return self.render.background.clone(),
-        };
+    pub fn width(&self) -> u32 {
+        self.render.image_width
+    }
 
-        let emitted = rec.material().emitted(&rec);
+    pub fn height(&self) -> u32 {
+        self.render.image_height
+    }
 
-        let srec = match rec.material().scatter(ray, &rec) {
-            Some(srec) => srec,
-            None => return emitted,
-        };
+    /// The pixels rendered, of the whole image of [`width`](Self::width) ×
+    /// [`height`](Self::height).
+    pub fn region(&self) -> Region {
+        self.tiles.region
+    }
 
-        let attenuation = srec.attenuation();
+    pub fn aovs(&self) -> &[Aov] {
+        &self.aovs
+    }
 
-        if let Some(spec) = srec.specular() {
-            return attenuation.mul(&self.ray_color(spec, world, lights, depth - 1));
-        }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
 
-        let pdf = srec.pdf().unwrap();
+    pub fn samples_per_pixel(&self) -> u32 {
+        self.render.samples_per_pixel
+    }
+}
 
-        let light_pdf = pdf::Primitive::new(lights, rec.point());
-        let mixture_pdf = pdf::Mixture::new(&light_pdf, pdf);
+/// The pixels to sample again with adaptive sampling: those with a relative error above
+/// `threshold` in their 3×3 neighbourhood. Looking at the neighbours keeps pixels whose samples
+/// have missed rare bright paths so far, and so seem converged, from stopping too dark.
+///
+/// Pixels on the edges of `pixels` only look at the neighbours within it. Around a crop, the
+/// film stops short of the pixels a full render would look at, so the edges of the crop can be
+/// sampled more or less than in the full render.
+fn noisy_pixels(pixels: &[PixelSamples], width: usize, threshold: f32) -> Vec<bool> {
+    let height = pixels.len() / width;
+    let noisy: Vec<bool> = pixels
+        .iter()
+        .map(|pixel| pixel.relative_error() >= threshold)
+        .collect();
+
+    (0..pixels.len())
+        .map(|p| {
+            let (x, y) = (p % width, p / width);
+            (y.saturating_sub(1)..(y + 2).min(height)).any(|ny| {
+                (x.saturating_sub(1)..(x + 2).min(width)).any(|nx| noisy[ny * width + nx])
+            })
+        })
+        .collect()
+}
 
-        let scattered_direction = mixture_pdf.generate();
-        let scattered = Ray::new(rec.point().clone(), scattered_direction).set_time(ray.time());
-        let pdf_val = mixture_pdf.value(scattered.direction().clone());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        camera::CheckpointError,
+        geometry::{HittableList, Quad, Sphere},
+        materials::{Dielectric, Lambertian},
+    };
+
+    /// A glass ball on a grey floor.
+    fn world() -> HittableList {
+        let mut world = HittableList::new();
+        world.push(Arc::new(Quad::new(
+            Point3::new(-50.0, 0.0, -50.0),
+            Vec3::new(0.0, 0.0, 100.0),
+            Vec3::new(100.0, 0.0, 0.0),
+            Arc::new(Lambertian::from_color(Color::new(0.5, 0.5, 0.5), 1.0)),
+        )));
+        world.push(Arc::new(Sphere::new(
+            Point3::new(0.0, 1.0, 0.0),
+            0.5,
+            Arc::new(Dielectric::new(1.5)),
+        )));
+        world
+    }
 
-        let scattering_pdf = rec.material().scattering_pdf(ray, &rec, &scattered);
+    /// A 12×12 view of [`world`] under a grey sky, level with the ball, with 4 samples per
+    /// pixel.
+    fn camera() -> CameraBuilder {
+        CameraBuilder::uninitialized()
+            .look_from(Point3::new(0.0, 1.0, 5.0))
+            .look_at(Point3::new(0.0, 1.0, 0.0))
+            .vfov(40.0)
+            .aspect_ratio(1.0)
+            .image_width(12)
+            .samples_per_pixel(4)
+            .background(Color::new(0.5, 0.5, 0.5))
+    }
 
-        let sample_color = self.ray_color(&scattered, world, lights, depth - 1);
+    fn render(seed: u64, threads: usize) -> Vec<f32> {
+        let camera = camera().seed(seed).build();
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(|| camera.render(&world(), &HittableList::new()))
+            .into_beauty()
+    }
 
-        if pdf_val.abs() < EPSILON || !(scattering_pdf / pdf_val).is_finite() {
-            return emitted;
-        }
+    #[test]
+    fn seeds_reproduce_the_image_on_any_thread_count() {
+        let image = render(7, 1);
+        assert_eq!(image, render(7, 4));
+        assert_ne!(image, render(8, 1));
+    }
 
-        let color_from_scatter = (scattering_pdf / pdf_val) * sample_color.mul(srec.attenuation());
+    #[test]
+    fn adaptive_sampling_spends_samples_on_noise() {
+        let frame = camera()
+            .adaptive_threshold(0.02)
+            .max_samples_per_pixel(64)
+            .aovs(vec![Aov::SampleCount])
+            .build()
+            .render(&world(), &HittableList::new());
+        let counts: Vec<f32> = frame
+            .layer(Aov::SampleCount)
+            .unwrap()
+            .iter()
+            .step_by(3)
+            .copied()
+            .collect();
 
-        emitted + color_from_scatter
+        // The sky is the same in every sample, the rim of the ball is not.
+        let (sky, rim) = (counts[6], counts[5 * 12 + 4]);
+        assert_eq!(sky, 4.0);
+        assert!(rim > 4.0, "{}", rim);
+        assert!(counts
+            .iter()
+            .all(|&n| n % 4.0 == 0.0 && (4.0..=64.0).contains(&n)));
     }
 
-    pub fn width(&self) -> u32 {
-        self.render.image_width
+    #[test]
+    fn resumed_renders_match_uninterrupted_ones() {
+        let (world, lights) = (world(), HittableList::new());
+        let layers = |frame: FrameBuffer| (frame.layer(Aov::Depth).unwrap().to_vec(), frame.beauty);
+        let direct = camera().aovs(vec![Aov::Depth]).build();
+
+        let first = camera().samples_per_pixel(2).aovs(vec![Aov::Depth]).build();
+        let mut passes = 0;
+        let state = first.render_progressive(&world, &lights, None, &mut |_| passes += 1);
+        assert_eq!((passes, state.passes()), (2, 2));
+
+        let path =
+            std::env::temp_dir().join(format!("camera_test_resume_{}.ckpt", std::process::id()));
+        let key = direct.checkpoint_key("balls");
+        assert_eq!(key, first.checkpoint_key("balls"));
+        state.save(&path, key).unwrap();
+        let cropped = camera().crop(Region::new(0, 0, 4, 4)).build();
+        assert!(matches!(
+            RenderState::load(&path, cropped.checkpoint_key("balls")),
+            Err(CheckpointError::Mismatch(_))
+        ));
+        let state = RenderState::load(&path, key).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let resumed = direct.render_progressive(&world, &lights, Some(state), &mut |_| {});
+        assert_eq!(resumed.passes(), 4);
+        assert_eq!(
+            layers(direct.frame(&resumed)),
+            layers(direct.render(&world, &lights))
+        );
     }
 
-    pub fn height(&self) -> u32 {
-        self.render.image_height
+    #[test]
+    fn crops_render_the_same_pixels_in_any_tile_order() {
+        let (world, lights) = (world(), HittableList::new());
+        let full = camera().build().render(&world, &lights);
+        let crop = camera()
+            .crop(Region::new(3, 5, 4, 6))
+            .tile_size(3)
+            .tile_order(TileOrder::Spiral)
+            .build()
+            .render(&world, &lights);
+
+        assert_eq!((crop.width(), crop.height()), (4, 6));
+        for y in 0..6 {
+            let row = 3 * ((5 + y) * 12 + 3);
+            assert_eq!(
+                crop.beauty()[3 * 4 * y..3 * 4 * (y + 1)],
+                full.beauty()[row..row + 3 * 4]
+            );
+        }
     }
-}
-
-fn sample_square_stratified(s_i: u16, s_j: u16, recip_sqrt_spp: f32) -> Vec3 {
-    let r1 = math::random_float();
-    let r2 = math::random_float();
 
-    let px = ((s_i as f32 + r1) * recip_sqrt_spp) - 0.5;
-    let py = ((s_j as f32 + r2) * recip_sqrt_spp) - 0.5;
+    #[test]
+    fn adaptive_crops_take_the_samples_of_full_renders() {
+        let (world, lights) = (world(), HittableList::new());
+        let adaptive = || {
+            camera()
+                .adaptive_threshold(0.2)
+                .max_samples_per_pixel(32)
+                .aovs(vec![Aov::SampleCount])
+        };
+        let full = adaptive().build().render(&world, &lights);
+        let crop = adaptive()
+            .crop(Region::new(2, 3, 6, 6))
+            .build()
+            .render(&world, &lights);
+
+        // Pixels near the edges may stop at other counts; those stopping at the same count match.
+        let mut matching = 0;
+        for y in 0..6 {
+            for x in 0..6 {
+                let (c, f) = (3 * (y * 6 + x), 3 * ((3 + y) * 12 + 2 + x));
+                let count = crop.layer(Aov::SampleCount).unwrap()[c];
+                assert!((4.0..=32.0).contains(&count));
+                if count == full.layer(Aov::SampleCount).unwrap()[f] {
+                    assert_eq!(crop.beauty()[c..c + 3], full.beauty()[f..f + 3]);
+                    matching += 1;
+                }
+            }
+        }
+        assert!(matching >= 24, "{}", matching);
+    }
 
-    Vec3::new(px, py, 0.0)
-}
+    #[test]
+    fn filters_spread_samples_into_crops_as_into_full_images() {
+        let (world, lights) = (world(), HittableList::new());
+        let filter = Filter::new(FilterKind::Mitchell, 1.5);
+        let full = camera().filter(filter).build().render(&world, &lights);
+        let crop = camera()
+            .filter(filter)
+            .crop(Region::new(0, 4, 5, 3))
+            .build()
+            .render(&world, &lights);
+        for y in 0..3 {
+            let row = 3 * (4 + y) * 12;
+            assert_eq!(
+                crop.beauty()[3 * 5 * y..3 * 5 * (y + 1)],
+                full.beauty()[row..row + 3 * 5]
+            );
+        }
 
-#[allow(dead_code)]
-fn sample_square() -> Vec3 {
-    Vec3::new(math::random_float() - 0.5, math::random_float() - 0.5, 0.0)
+        // Wider filters blur the image: neighbouring pixels differ less.
+        let variation = |filter: Filter| {
+            let frame = camera().filter(filter).build().render(&world, &lights);
+            let beauty = frame.beauty();
+            (3..beauty.len())
+                .map(|i| (beauty[i] - beauty[i - 3]).abs())
+                .sum::<f32>()
+        };
+        let gaussian = Filter::new(FilterKind::Gaussian, 2.0);
+        assert!(variation(gaussian) < 0.8 * variation(Filter::default()));
+    }
 }
@@ -0,0 +1,218 @@
+use std::{
+    error::Error,
+    fmt, fs,
+    hash::Hasher,
+    io,
+    path::{Path, PathBuf},
+};
+
+use super::framebuffer::{PixelSamples, PIXEL_WORDS};
+
+const MAGIC: &[u8; 8] = b"RTCKPT\0\0";
+const VERSION: u32 = 2;
+/// Magic, version, key, width, height and passes.
+const HEADER_BYTES: usize = 8 + 4 + 8 + 4 + 4 + 4;
+
+/// The samples a progressive render has taken so far, summed per pixel, which
+/// [`Camera::frame`](super::Camera::frame) turns into an image at any point.
+///
+/// Samples draw their random numbers from streams keyed by the seed, the pixel and the index
+/// of the sample, so the sample counts are all the random state there is: a render picked up
+/// from a checkpoint takes the same samples it would have taken without stopping.
+#[derive(Clone)]
+pub struct RenderState {
+    pub(super) width: u32,
+    pub(super) height: u32,
+    pub(super) passes: u32,
+    pub(super) pixels: Vec<PixelSamples>,
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    /// The checkpoint belongs to another scene or camera.
+    Mismatch(PathBuf),
+    Corrupt(PathBuf),
+}
+
+impl RenderState {
+    pub(super) fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            passes: 0,
+            pixels: (0..width * height).map(|_| PixelSamples::new()).collect(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Passes over the image so far, counting those before a resume.
+    pub fn passes(&self) -> u32 {
+        self.passes
+    }
+
+    /// Writes the state to `path`, tagged with `key`, which should identify the scene and
+    /// camera (see [`Camera::checkpoint_key`](super::Camera::checkpoint_key)).
+    ///
+    /// The file is written next to `path` and then renamed over it, so a render killed while
+    /// saving leaves the previous checkpoint whole.
+    pub fn save(&self, path: &Path, key: u64) -> Result<(), CheckpointError> {
+        let mut bytes = Vec::with_capacity(HEADER_BYTES + self.pixels.len() * PIXEL_WORDS * 4);
+        bytes.extend(MAGIC);
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend(key.to_le_bytes());
+        for value in [self.width, self.height, self.passes] {
+            bytes.extend(value.to_le_bytes());
+        }
+        for pixel in &self.pixels {
+            for word in pixel.to_words() {
+                bytes.extend(word.to_le_bytes());
+            }
+        }
+
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".part");
+        fs::write(&partial, bytes)?;
+        fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// Reads a state written by [`save`](Self::save) with the same `key`.
+    pub fn load(path: &Path, key: u64) -> Result<Self, CheckpointError> {
+        let bytes = fs::read(path)?;
+        let corrupt = || CheckpointError::Corrupt(path.to_path_buf());
+        if bytes.len() < HEADER_BYTES || &bytes[..8] != MAGIC {
+            return Err(corrupt());
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        if u32_at(8) != VERSION {
+            return Err(corrupt());
+        }
+        if u64::from_le_bytes(bytes[12..20].try_into().unwrap()) != key {
+            return Err(CheckpointError::Mismatch(path.to_path_buf()));
+        }
+        let (width, height, passes) = (u32_at(20), u32_at(24), u32_at(28));
+
+        let body = &bytes[HEADER_BYTES..];
+        let pixel_bytes = PIXEL_WORDS * 4;
+        if body.len() != (width as usize * height as usize) * pixel_bytes {
+            return Err(corrupt());
+        }
+        let pixels = body
+            .chunks_exact(pixel_bytes)
+            .map(|chunk| {
+                let mut words = [0; PIXEL_WORDS];
+                for (word, bytes) in words.iter_mut().zip(chunk.chunks_exact(4)) {
+                    *word = u32::from_le_bytes(bytes.try_into().unwrap());
+                }
+                PixelSamples::from_words(&words)
+            })
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            passes,
+            pixels,
+        })
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> Self {
+        CheckpointError::Io(error)
+    }
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(err) => write!(f, "IO error: {}", err),
+            CheckpointError::Mismatch(path) => write!(
+                f,
+                "{} was saved from another scene or camera",
+                path.display()
+            ),
+            CheckpointError::Corrupt(path) => {
+                write!(f, "{} is not a valid checkpoint", path.display())
+            }
+        }
+    }
+}
+
+impl Error for CheckpointError {}
+
+/// The 64-bit FNV-1a hash, which unlike the standard hasher stays the same from one build to
+/// the next, as keys written to checkpoints must.
+pub(super) struct Fnv1a(u64);
+
+impl Fnv1a {
+    pub(super) fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        camera::CameraBuilder,
+        geometry::{HittableList, Sphere},
+        materials::Lambertian,
+        math::{Color, Point3},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn reads_back_what_it_saves() {
+        let mut world = HittableList::new();
+        world.push(Arc::new(Sphere::new(
+            Point3::new(0.0, 0.0, -2.0),
+            1.0,
+            Arc::new(Lambertian::from_color(Color::new(0.5, 0.5, 0.5), 1.0)),
+        )));
+        let camera = CameraBuilder::uninitialized()
+            .image_width(6)
+            .aspect_ratio(1.5)
+            .samples_per_pixel(3)
+            .background(Color::new(0.4, 0.6, 0.9))
+            .build();
+        let state = camera.render_progressive(&world, &HittableList::new(), None, &mut |_| {});
+
+        let path =
+            std::env::temp_dir().join(format!("checkpoint_test_{}.ckpt", std::process::id()));
+        state.save(&path, 42).unwrap();
+        let loaded = RenderState::load(&path, 42).unwrap();
+        let other_key = RenderState::load(&path, 43);
+        fs::write(&path, &fs::read(&path).unwrap()[..100]).unwrap();
+        let truncated = RenderState::load(&path, 42);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!((loaded.width(), loaded.height()), (6, 4));
+        assert_eq!(loaded.passes(), 3);
+        for (a, b) in state.pixels.iter().zip(&loaded.pixels) {
+            assert_eq!(a.to_words(), b.to_words());
+        }
+        assert!(matches!(other_key, Err(CheckpointError::Mismatch(_))));
+        assert!(matches!(truncated, Err(CheckpointError::Corrupt(_))));
+    }
+}
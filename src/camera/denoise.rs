@@ -0,0 +1,403 @@
+use rayon::prelude::*;
+
+use super::*;
+
+/// Passes of the filter unless configured otherwise; the last one reaches 64 pixels away.
+pub const DEFAULT_DENOISE_ITERATIONS: u32 = 5;
+
+/// Weights of the B3 spline, the smoothing kernel of the wavelet transform.
+const KERNEL: [f32; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// How many standard deviations of noise apart two luminances may be and still be averaged.
+const SIGMA_LUMINANCE: f32 = 4.0;
+
+/// Exponent on the cosine between normals; higher keeps creases sharper.
+const SIGMA_NORMAL: f32 = 128.0;
+
+/// Tolerance on depth differences, relative to the depth gradient at the pixel.
+const SIGMA_DEPTH: f32 = 1.0;
+
+/// Albedo below which a channel is filtered as is rather than divided by the albedo, as for
+/// emitters and the background.
+const MIN_ALBEDO: f32 = 0.01;
+
+/// Keeps the edge-stopping functions finite where a term vanishes.
+const TOLERANCE: f32 = 1e-6;
+
+/// Feature layers the denoiser is guided by.
+pub const DENOISE_FEATURES: [Aov; 3] = [Aov::Albedo, Aov::Normal, Aov::Depth];
+
+/// Edge-avoiding à-trous wavelet filter, after Dammertz et al. with the variance-guided
+/// luminance weights of SVGF (Schied et al.).
+///
+/// Each pass blurs with a 5×5 B3 spline kernel whose taps spread twice as far as in the pass
+/// before, and weighs every tap down where the normal or depth of the first surface differ,
+/// or where the luminance differs by more than the noise of the pixel explains. The variance
+/// is filtered along, so later passes only smooth what noise is left. Radiance is divided by
+/// the albedo first and multiplied back at the end, which keeps textures sharp.
+///
+/// Runs on the [`FrameBuffer`] of a render, before tone mapping, and needs its
+/// [`DENOISE_FEATURES`]. The lighting layers are filtered with the weights of the image, so
+/// they keep adding up to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Denoiser {
+    iterations: u32,
+}
+
+impl Denoiser {
+    pub fn new() -> Self {
+        Self::with_iterations(DEFAULT_DENOISE_ITERATIONS)
+    }
+
+    pub fn with_iterations(iterations: u32) -> Self {
+        Self { iterations }
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Filters the image and lighting layers of `frame` in place. Does nothing when `frame`
+    /// lacks a feature layer.
+    pub fn apply(&self, frame: &mut FrameBuffer) {
+        let (Some(albedo), Some(normal), Some(depth)) = (
+            frame.layer(Aov::Albedo),
+            frame.layer(Aov::Normal),
+            frame.layer(Aov::Depth),
+        ) else {
+            return;
+        };
+        let guide = Guide::new(frame.width as usize, frame.height as usize, normal, depth);
+        let albedo: Vec<f32> = albedo
+            .iter()
+            .map(|a| if *a < MIN_ALBEDO { 1.0 } else { *a })
+            .collect();
+        let albedo_luminance: Vec<f32> = albedo
+            .chunks_exact(3)
+            .map(|a| luminance(&Color::new(a[0], a[1], a[2])))
+            .collect();
+
+        let radiance_layers: Vec<usize> = (0..frame.layers.len())
+            .filter(|i| frame.layers[*i].0.is_radiance())
+            .collect();
+        let mut signals: Vec<Vec<f32>> = std::iter::once(&frame.beauty)
+            .chain(radiance_layers.iter().map(|i| &frame.layers[*i].1))
+            .map(|signal| signal.iter().zip(&albedo).map(|(c, a)| c / a).collect())
+            .collect();
+        let mut variance: Vec<f32> = frame
+            .variance
+            .iter()
+            .zip(&albedo_luminance)
+            .map(|(v, a)| v / (a * a))
+            .collect();
+
+        for i in 0..self.iterations {
+            (signals, variance) = guide.pass(1 << i, &signals, &variance);
+        }
+
+        let mut signals = signals.into_iter();
+        let remodulate = |target: &mut Vec<f32>, signal: Vec<f32>| {
+            for ((value, filtered), a) in target.iter_mut().zip(signal).zip(&albedo) {
+                *value = filtered * a;
+            }
+        };
+        remodulate(&mut frame.beauty, signals.next().unwrap());
+        for (i, signal) in radiance_layers.into_iter().zip(signals) {
+            remodulate(&mut frame.layers[i].1, signal);
+        }
+        frame.variance = variance
+            .into_iter()
+            .zip(albedo_luminance)
+            .map(|(v, a)| v * a * a)
+            .collect();
+    }
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The geometry seen through each pixel, to tell edges from noise.
+struct Guide {
+    width: usize,
+    height: usize,
+    /// Unit normals, zero where no surface was seen.
+    normals: Vec<[f32; 3]>,
+    depths: Vec<f32>,
+    /// Largest change of depth to a neighbouring pixel on the same side of a silhouette.
+    gradients: Vec<f32>,
+}
+
+impl Guide {
+    fn new(width: usize, height: usize, normal: &[f32], depth: &[f32]) -> Self {
+        let normals = normal
+            .chunks_exact(3)
+            .map(|n| {
+                let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+                if length > TOLERANCE {
+                    [n[0] / length, n[1] / length, n[2] / length]
+                } else {
+                    [0.0; 3]
+                }
+            })
+            .collect();
+        let depths: Vec<f32> = depth.iter().step_by(3).copied().collect();
+
+        let gradients = (0..width * height)
+            .map(|p| {
+                let (x, y) = (p % width, p / width);
+                let neighbours = [
+                    (x > 0).then(|| p - 1),
+                    (x + 1 < width).then(|| p + 1),
+                    (y > 0).then(|| p - width),
+                    (y + 1 < height).then(|| p + width),
+                ];
+                neighbours
+                    .into_iter()
+                    .flatten()
+                    .filter(|q| depths[*q] > 0.0)
+                    .map(|q| (depths[q] - depths[p]).abs())
+                    .fold(0.0, f32::max)
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            normals,
+            depths,
+            gradients,
+        }
+    }
+
+    /// One pass of the filter with taps `step` pixels apart, over every signal at once with
+    /// the weights of the first.
+    fn pass(
+        &self,
+        step: usize,
+        signals: &[Vec<f32>],
+        variance: &[f32],
+    ) -> (Vec<Vec<f32>>, Vec<f32>) {
+        let blurred = self.blur_variance(variance);
+
+        let rows: Vec<(Vec<Vec<f32>>, Vec<f32>)> = (0..self.height)
+            .into_par_iter()
+            .map(|y| {
+                let mut row_signals = vec![Vec::with_capacity(3 * self.width); signals.len()];
+                let mut row_variance = Vec::with_capacity(self.width);
+
+                for x in 0..self.width {
+                    let p = y * self.width + x;
+                    if self.depths[p] <= 0.0 {
+                        for (row, signal) in row_signals.iter_mut().zip(signals) {
+                            row.extend_from_slice(&signal[3 * p..3 * p + 3]);
+                        }
+                        row_variance.push(variance[p]);
+                        continue;
+                    }
+
+                    let luminance_p = pixel_luminance(&signals[0], p);
+                    let luminance_scale = SIGMA_LUMINANCE * blurred[p].sqrt() + TOLERANCE;
+
+                    let mut sums = vec![[0.0; 3]; signals.len()];
+                    let mut weights = 0.0;
+                    let mut variance_sum = 0.0;
+                    for (j, ky) in KERNEL.iter().enumerate() {
+                        for (i, kx) in KERNEL.iter().enumerate() {
+                            let dx = (i as isize - 2) * step as isize;
+                            let dy = (j as isize - 2) * step as isize;
+                            let (qx, qy) = (x as isize + dx, y as isize + dy);
+                            if qx < 0
+                                || qy < 0
+                                || qx >= self.width as isize
+                                || qy >= self.height as isize
+                            {
+                                continue;
+                            }
+                            let q = qy as usize * self.width + qx as usize;
+
+                            let weight = if q == p {
+                                kx * ky
+                            } else {
+                                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                                let depth = (self.depths[p] - self.depths[q]).abs()
+                                    / (SIGMA_DEPTH * self.gradients[p] * distance + TOLERANCE);
+                                let luminance = (luminance_p - pixel_luminance(&signals[0], q))
+                                    .abs()
+                                    / luminance_scale;
+                                let cosine = dot(&self.normals[p], &self.normals[q]).max(0.0);
+                                kx * ky * cosine.powf(SIGMA_NORMAL) * (-depth - luminance).exp()
+                            };
+                            if weight <= 0.0 || !weight.is_finite() {
+                                continue;
+                            }
+
+                            for (sum, signal) in sums.iter_mut().zip(signals) {
+                                for c in 0..3 {
+                                    sum[c] += weight * signal[3 * q + c];
+                                }
+                            }
+                            weights += weight;
+                            variance_sum += weight * weight * variance[q];
+                        }
+                    }
+
+                    for (row, sum) in row_signals.iter_mut().zip(sums) {
+                        row.extend(sum.map(|s| s / weights));
+                    }
+                    row_variance.push(variance_sum / (weights * weights));
+                }
+                (row_signals, row_variance)
+            })
+            .collect();
+
+        let mut filtered = vec![Vec::with_capacity(3 * self.width * self.height); signals.len()];
+        let mut filtered_variance = Vec::with_capacity(self.width * self.height);
+        for (row_signals, row_variance) in rows {
+            for (signal, row) in filtered.iter_mut().zip(row_signals) {
+                signal.extend(row);
+            }
+            filtered_variance.extend(row_variance);
+        }
+        (filtered, filtered_variance)
+    }
+
+    /// Variance smoothed by a 3×3 Gaussian, a steadier guide than that of a single pixel.
+    fn blur_variance(&self, variance: &[f32]) -> Vec<f32> {
+        const WEIGHTS: [f32; 3] = [0.25, 0.5, 0.25];
+
+        (0..self.width * self.height)
+            .map(|p| {
+                let (x, y) = ((p % self.width) as isize, (p / self.width) as isize);
+                let mut sum = 0.0;
+                let mut weights = 0.0;
+                for (j, wy) in WEIGHTS.iter().enumerate() {
+                    for (i, wx) in WEIGHTS.iter().enumerate() {
+                        let (qx, qy) = (x + i as isize - 1, y + j as isize - 1);
+                        if qx < 0
+                            || qy < 0
+                            || qx >= self.width as isize
+                            || qy >= self.height as isize
+                        {
+                            continue;
+                        }
+                        sum += wx * wy * variance[qy as usize * self.width + qx as usize];
+                        weights += wx * wy;
+                    }
+                }
+                sum / weights
+            })
+            .collect()
+    }
+}
+
+fn pixel_luminance(signal: &[f32], p: usize) -> f32 {
+    luminance(&Color::new(
+        signal[3 * p],
+        signal[3 * p + 1],
+        signal[3 * p + 2],
+    ))
+}
+
+fn dot(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: usize = 32;
+    const HEIGHT: usize = 16;
+
+    /// Two walls meeting at a crease down the middle, grey on the left and bright on the right,
+    /// with uniform noise of half-width `noise` on the image and on the direct light layer.
+    fn noisy_crease(noise: f32) -> FrameBuffer {
+        let mut state = 12345u32;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            noise * (2.0 * state as f32 / u32::MAX as f32 - 1.0)
+        };
+
+        let mut beauty = Vec::new();
+        let mut direct = Vec::new();
+        let mut normal = Vec::new();
+        for _ in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let (value, n) = if x < WIDTH / 2 {
+                    (0.2, [0.0, 0.0, 1.0])
+                } else {
+                    (0.8, [1.0, 0.0, 0.0])
+                };
+                let v = value + random();
+                beauty.extend([v; 3]);
+                direct.extend([0.5 * v; 3]);
+                normal.extend(n);
+            }
+        }
+
+        let pixels = WIDTH * HEIGHT;
+        FrameBuffer {
+            width: WIDTH as u32,
+            height: HEIGHT as u32,
+            beauty,
+            variance: vec![noise * noise / 3.0; pixels],
+            layers: vec![
+                (Aov::Direct, direct),
+                (Aov::Albedo, vec![0.5; 3 * pixels]),
+                (Aov::Normal, normal),
+                (Aov::Depth, vec![5.0; 3 * pixels]),
+            ],
+        }
+    }
+
+    fn error(frame: &FrameBuffer) -> f32 {
+        let squares: f32 = frame
+            .beauty
+            .chunks_exact(3)
+            .enumerate()
+            .map(|(p, rgb)| {
+                let expected = if p % WIDTH < WIDTH / 2 { 0.2 } else { 0.8 };
+                (rgb[0] - expected).powi(2)
+            })
+            .sum();
+        (squares / (WIDTH * HEIGHT) as f32).sqrt()
+    }
+
+    #[test]
+    fn smooths_noise_and_keeps_edges() {
+        let mut frame = noisy_crease(0.1);
+        let before = error(&frame);
+        Denoiser::new().apply(&mut frame);
+        let after = error(&frame);
+        assert!(after < 0.25 * before, "{} -> {}", before, after);
+
+        // Nothing bleeds across the crease.
+        for y in 0..HEIGHT {
+            let left = frame.beauty[3 * (y * WIDTH + WIDTH / 2 - 1)];
+            let right = frame.beauty[3 * (y * WIDTH + WIDTH / 2)];
+            assert!((left - 0.2).abs() < 0.05, "{}", left);
+            assert!((right - 0.8).abs() < 0.05, "{}", right);
+        }
+
+        // Lighting layers get the weights of the image.
+        let direct = frame.layer(Aov::Direct).unwrap();
+        for (value, beauty) in direct.iter().zip(&frame.beauty) {
+            assert!((value - 0.5 * beauty).abs() < 1e-5);
+        }
+        assert!(frame.variance[0] < 0.01 * 0.01 / 3.0);
+    }
+
+    #[test]
+    fn needs_the_feature_layers() {
+        let mut frame = noisy_crease(0.1);
+        frame.layers.retain(|(aov, _)| *aov != Aov::Normal);
+        let beauty = frame.beauty.clone();
+        Denoiser::new().apply(&mut frame);
+        assert_eq!(frame.beauty, beauty);
+    }
+}
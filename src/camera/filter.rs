@@ -0,0 +1,322 @@
+//! Reconstruction filters, which weigh every sample by its distance to the centre of each
+//! pixel around it. Each pixel is then the weighted mean of the samples near it, its own and
+//! those of its neighbours, rather than the plain mean of its own.
+
+use std::{f32::consts::PI, fmt, str::FromStr};
+
+use rayon::prelude::*;
+
+use super::framebuffer::PixelSamples;
+use crate::integrators::LightPaths;
+
+/// The filters selectable by name, e.g. from the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterKind {
+    /// Equal weights within the radius; with the default radius of half a pixel, each pixel
+    /// is the mean of its own samples.
+    #[default]
+    Box,
+    /// Weights falling linearly to zero at the radius.
+    Tent,
+    /// A Gaussian with a standard deviation of a third of the radius, shifted to reach zero at
+    /// the radius.
+    Gaussian,
+    /// The Mitchell-Netravali cubic with `B = C = 1/3`: sharp, with slight negative lobes.
+    Mitchell,
+    /// The sinc function windowed by a wider sinc, with as many lobes as the radius has pixels:
+    /// the sharpest, with some ringing on hard edges.
+    Lanczos,
+    /// The Blackman-Harris window: close to a Gaussian, but smoother where it ends.
+    BlackmanHarris,
+}
+
+impl FilterKind {
+    pub fn default_radius(self) -> f32 {
+        match self {
+            FilterKind::Box => 0.5,
+            FilterKind::Tent => 1.0,
+            FilterKind::Gaussian | FilterKind::BlackmanHarris => 1.5,
+            FilterKind::Mitchell | FilterKind::Lanczos => 2.0,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            FilterKind::Box => "box",
+            FilterKind::Tent => "tent",
+            FilterKind::Gaussian => "gaussian",
+            FilterKind::Mitchell => "mitchell",
+            FilterKind::Lanczos => "lanczos",
+            FilterKind::BlackmanHarris => "blackman-harris",
+        }
+    }
+}
+
+/// A separable filter: the weight of a sample is the product of the 1D filter over its
+/// horizontal and vertical distances to the pixel centre, in pixels. Samples farther than
+/// `radius` along either axis do not count.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Filter {
+    kind: FilterKind,
+    radius: f32,
+}
+
+/// A camera sample waiting to be splatted: the light it found and where it lies relative to
+/// the centre of its pixel.
+pub(super) struct FilmSample {
+    pub(super) offset: [f32; 2],
+    pub(super) light: LightPaths,
+}
+
+impl Filter {
+    /// The widest radius, in pixels. Each sample is splatted over a square of pixels twice as
+    /// wide, so wider filters would only slow the render down to a crawl.
+    pub const MAX_RADIUS: f32 = 16.0;
+
+    /// The filter of `kind`, with a radius of `radius` pixels; a radius too small to reach
+    /// every sample of a pixel is raised to half a pixel, and one over
+    /// [`MAX_RADIUS`](Self::MAX_RADIUS) is lowered to it.
+    pub fn new(kind: FilterKind, radius: f32) -> Self {
+        let radius = if radius.is_nan() { 0.5 } else { radius };
+        Self {
+            kind,
+            radius: radius.clamp(0.5, Self::MAX_RADIUS),
+        }
+    }
+
+    pub fn kind(&self) -> FilterKind {
+        self.kind
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// How many pixels a sample reaches beyond its own, in each direction.
+    pub(super) fn margin(&self) -> u32 {
+        (self.radius - 0.5).ceil() as u32
+    }
+
+    /// The weight of a sample `(x, y)` pixels away from a pixel centre. The support is closed
+    /// on the negative side only, so that with a radius of half a pixel each sample belongs
+    /// to exactly one pixel.
+    pub(super) fn weight(&self, x: f32, y: f32) -> f32 {
+        let inside = |d: f32| -self.radius <= d && d < self.radius;
+        if !(inside(x) && inside(y)) {
+            return 0.0;
+        }
+        self.evaluate(x) * self.evaluate(y)
+    }
+
+    /// The 1D filter at `x` within the radius.
+    fn evaluate(&self, x: f32) -> f32 {
+        let r = self.radius;
+        match self.kind {
+            FilterKind::Box => 1.0,
+            FilterKind::Tent => (1.0 - x.abs() / r).max(0.0),
+            FilterKind::Gaussian => {
+                let sigma = r / 3.0;
+                let gaussian = |x: f32| (-x * x / (2.0 * sigma * sigma)).exp();
+                (gaussian(x) - gaussian(r)).max(0.0)
+            }
+            FilterKind::Mitchell => mitchell(2.0 * x / r),
+            FilterKind::Lanczos => sinc(x) * sinc(x / r),
+            FilterKind::BlackmanHarris => {
+                let t = 2.0 * PI * (x + r) / (2.0 * r);
+                0.35875 - 0.48829 * t.cos() + 0.14128 * (2.0 * t).cos() - 0.01168 * (3.0 * t).cos()
+            }
+        }
+    }
+
+    /// Adds the samples of one round, at most one per pixel of a `width` pixels wide film, to
+    /// the pixels they reach.
+    pub(super) fn splat(
+        &self,
+        pixels: &mut [PixelSamples],
+        width: usize,
+        samples: &[Option<FilmSample>],
+    ) {
+        let height = pixels.len() / width;
+        let margin = self.margin() as usize;
+        pixels
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    for sy in y.saturating_sub(margin)..(y + margin + 1).min(height) {
+                        for sx in x.saturating_sub(margin)..(x + margin + 1).min(width) {
+                            let Some(sample) = &samples[sy * width + sx] else {
+                                continue;
+                            };
+                            let weight = self.weight(
+                                sx as f32 - x as f32 + sample.offset[0],
+                                sy as f32 - y as f32 + sample.offset[1],
+                            );
+                            if weight != 0.0 {
+                                pixel.add_weighted(weight, &sample.light);
+                            }
+                        }
+                    }
+                }
+            });
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::new(FilterKind::Box, FilterKind::Box.default_radius())
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    /// Accepts `box`, `tent`, `gaussian`, `mitchell`, `lanczos` and `blackman-harris`, each
+    /// with an optional `:radius` in pixels.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, radius) = match s.trim().split_once(':') {
+            Some((name, radius)) => (name, Some(radius)),
+            None => (s.trim(), None),
+        };
+        let kind = [
+            FilterKind::Box,
+            FilterKind::Tent,
+            FilterKind::Gaussian,
+            FilterKind::Mitchell,
+            FilterKind::Lanczos,
+            FilterKind::BlackmanHarris,
+        ]
+        .into_iter()
+        .find(|kind| kind.name() == name)
+        .ok_or_else(|| {
+            format!(
+                "Unknown filter: '{}' (expected box, tent, gaussian, mitchell, lanczos or \
+                 blackman-harris)",
+                s
+            )
+        })?;
+        let radius = match radius {
+            Some(radius) => radius
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|radius| (0.0..=Self::MAX_RADIUS).contains(radius))
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid filter radius: '{}' (expected 0 to {} pixels)",
+                        radius,
+                        Self::MAX_RADIUS
+                    )
+                })?,
+            None => kind.default_radius(),
+        };
+        Ok(Filter::new(kind, radius))
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.kind.name(), self.radius)
+    }
+}
+
+/// The Mitchell-Netravali cubic with `B = C = 1/3`, over `[-2, 2]`.
+fn mitchell(x: f32) -> f32 {
+    const B: f32 = 1.0 / 3.0;
+    const C: f32 = 1.0 / 3.0;
+    let x = x.abs();
+    let value = if x < 1.0 {
+        (12.0 - 9.0 * B - 6.0 * C) * x.powi(3)
+            + (-18.0 + 12.0 * B + 6.0 * C) * x * x
+            + (6.0 - 2.0 * B)
+    } else if x < 2.0 {
+        (-B - 6.0 * C) * x.powi(3)
+            + (6.0 * B + 30.0 * C) * x * x
+            + (-12.0 * B - 48.0 * C) * x
+            + (8.0 * B + 24.0 * C)
+    } else {
+        0.0
+    };
+    value / 6.0
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-5 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [FilterKind; 6] = [
+        FilterKind::Box,
+        FilterKind::Tent,
+        FilterKind::Gaussian,
+        FilterKind::Mitchell,
+        FilterKind::Lanczos,
+        FilterKind::BlackmanHarris,
+    ];
+
+    #[test]
+    fn peaks_at_the_centre_and_ends_at_the_radius() {
+        for kind in KINDS {
+            let filter = Filter::new(kind, kind.default_radius());
+            let r = filter.radius();
+            let centre = filter.weight(0.0, 0.0);
+            assert!(centre > 0.0, "{:?}", kind);
+            for x in [-0.9, -0.3, 0.2, 0.7] {
+                assert!(filter.weight(x * r, 0.0) <= centre, "{:?} at {}", kind, x);
+                assert_eq!(filter.weight(x * r, 0.0), filter.weight(0.0, x * r));
+            }
+            assert_eq!(filter.weight(r, 0.0), 0.0);
+            assert_eq!(filter.weight(0.0, -1.01 * r), 0.0);
+            // Nearly nothing is left at the edge of the support.
+            if kind != FilterKind::Box {
+                assert!(
+                    filter.evaluate(0.999 * r).abs() < 0.01 * centre,
+                    "{:?}",
+                    kind
+                );
+            }
+        }
+        assert_eq!(Filter::default().margin(), 0);
+        assert_eq!("mitchell".parse::<Filter>().unwrap().margin(), 2);
+    }
+
+    #[test]
+    fn box_keeps_samples_in_their_pixel() {
+        let filter = Filter::default();
+        for offset in [-0.5, -0.2, 0.0, 0.49] {
+            assert_eq!(filter.weight(offset, offset), 1.0);
+            assert_eq!(filter.weight(offset + 1.0, 0.0), 0.0);
+            assert_eq!(filter.weight(offset - 1.0, 0.0), 0.0);
+        }
+    }
+
+    #[test]
+    fn parses_names_and_radii() {
+        for kind in KINDS {
+            let filter: Filter = kind.name().parse().unwrap();
+            assert_eq!(filter, Filter::new(kind, kind.default_radius()));
+            assert_eq!(filter.to_string().parse(), Ok(filter));
+        }
+        let gaussian: Filter = "gaussian:2.5".parse().unwrap();
+        assert_eq!(
+            (gaussian.kind(), gaussian.radius()),
+            (FilterKind::Gaussian, 2.5)
+        );
+        assert!("sinc".parse::<Filter>().is_err());
+        assert!("tent:wide".parse::<Filter>().is_err());
+        for radius in ["inf", "NaN", "-1", "5e9"] {
+            assert!(format!("gaussian:{}", radius).parse::<Filter>().is_err());
+        }
+        assert_eq!(
+            Filter::new(FilterKind::Gaussian, f32::INFINITY).radius(),
+            Filter::MAX_RADIUS
+        );
+    }
+}
@@ -0,0 +1,586 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use super::*;
+use crate::{geometry::HitRecord, integrators::LightPaths, math::tonemap::ToneMap};
+
+/// Luminance below which [`PixelSamples::relative_error`] measures errors in absolute terms,
+/// so that nearly black pixels do not take every sample allowed.
+const MIN_RELATIVE_LUMINANCE: f32 = 0.01;
+
+/// Number of 32-bit words [`PixelSamples::to_words`] stores a pixel in.
+pub(super) const PIXEL_WORDS: usize = 32;
+
+/// An arbitrary output variable: a per-pixel quantity recorded alongside the image, for
+/// compositing and denoising.
+///
+/// The geometric channels describe the first surface seen through each pixel, looking through
+/// participating media, and are averaged over the samples that find one. The lighting channels
+/// split the image itself by [`LightPaths`] and add up to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Aov {
+    /// Reflectance of the surface, from [`Material::albedo`](crate::materials::Material::albedo).
+    Albedo,
+    /// Shading normal of the surface in world space, on the side facing the camera.
+    Normal,
+    /// Distance from the camera to the surface.
+    Depth,
+    /// World space position of the surface.
+    Position,
+    /// Scene object seen, numbered from 1 in the order the objects first appear in the image
+    /// row by row, and 0 where there is none.
+    ObjectId,
+    /// Material of the surface, numbered like [`Aov::ObjectId`].
+    MaterialId,
+    /// Light seen without bouncing, background included.
+    Emission,
+    /// Light that bounced once on its way to the camera.
+    Direct,
+    /// Light that bounced more than once.
+    Indirect,
+    /// Number of samples taken in the pixel, which varies with adaptive sampling.
+    SampleCount,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 10] = [
+        Aov::Albedo,
+        Aov::Normal,
+        Aov::Depth,
+        Aov::Position,
+        Aov::ObjectId,
+        Aov::MaterialId,
+        Aov::Emission,
+        Aov::Direct,
+        Aov::Indirect,
+        Aov::SampleCount,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Aov::Albedo => "albedo",
+            Aov::Normal => "normal",
+            Aov::Depth => "depth",
+            Aov::Position => "position",
+            Aov::ObjectId => "object-id",
+            Aov::MaterialId => "material-id",
+            Aov::Emission => "emission",
+            Aov::Direct => "direct",
+            Aov::Indirect => "indirect",
+            Aov::SampleCount => "sample-count",
+        }
+    }
+
+    /// Whether the channel holds radiance, to be tone mapped like the image.
+    pub fn is_radiance(self) -> bool {
+        matches!(self, Aov::Emission | Aov::Direct | Aov::Indirect)
+    }
+
+    /// Whether the channel describes the first surface seen.
+    pub(super) fn is_surface(self) -> bool {
+        !self.is_radiance() && self != Aov::SampleCount
+    }
+
+    fn is_id(self) -> bool {
+        matches!(self, Aov::ObjectId | Aov::MaterialId)
+    }
+}
+
+impl FromStr for Aov {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        Aov::ALL
+            .into_iter()
+            .find(|aov| aov.name() == name)
+            .ok_or_else(|| format!("Unknown output variable: '{}'", s))
+    }
+}
+
+impl fmt::Display for Aov {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The result of [`Camera::render`]: the image and any [`Aov`] layers the camera was asked
+/// for, each stored row by row as three floats per pixel. Single valued channels repeat their
+/// value in all three.
+pub struct FrameBuffer {
+    pub(super) width: u32,
+    pub(super) height: u32,
+    pub(super) beauty: Vec<f32>,
+    pub(super) variance: Vec<f32>,
+    pub(super) layers: Vec<(Aov, Vec<f32>)>,
+}
+
+impl FrameBuffer {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The rendered image, in linear RGB.
+    pub fn beauty(&self) -> &[f32] {
+        &self.beauty
+    }
+
+    pub fn into_beauty(self) -> Vec<f32> {
+        self.beauty
+    }
+
+    /// Variance of the estimate of each pixel's luminance, from the spread of its samples;
+    /// infinite for pixels with a single sample.
+    pub fn variance(&self) -> &[f32] {
+        &self.variance
+    }
+
+    pub fn layer(&self, aov: Aov) -> Option<&[f32]> {
+        self.layers
+            .iter()
+            .find(|(layer, _)| *layer == aov)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// The recorded layers, in the order they were requested.
+    pub fn layers(&self) -> impl Iterator<Item = (Aov, &[f32])> {
+        self.layers
+            .iter()
+            .map(|(aov, values)| (*aov, values.as_slice()))
+    }
+
+    /// Turns every channel into colours for an 8-bit image: the image and the lighting layers
+    /// are tone mapped, normals are mapped from `[-1, 1]` to `[0, 1]`, depths and positions are
+    /// scaled to the range found in the image, ids get a colour each and sample counts become a
+    /// heatmap, from dark blue for the fewest to yellow for the most.
+    pub fn prepare_for_display(&mut self, tonemap: &ToneMap) {
+        tonemap.apply(&mut self.beauty);
+
+        for (aov, values) in self.layers.iter_mut() {
+            match aov {
+                Aov::Albedo => {}
+                Aov::Emission | Aov::Direct | Aov::Indirect => tonemap.apply(values),
+                Aov::Normal => values.iter_mut().for_each(|v| *v = 0.5 * (*v + 1.0)),
+                Aov::Depth | Aov::Position => normalize_axes(values),
+                Aov::ObjectId | Aov::MaterialId => {
+                    for pixel in values.chunks_mut(3) {
+                        pixel.copy_from_slice(&id_color(pixel[0] as u32));
+                    }
+                }
+                Aov::SampleCount => {
+                    normalize_axes(values);
+                    for pixel in values.chunks_mut(3) {
+                        pixel.copy_from_slice(&heat_color(pixel[0]));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Joins the rows of a render, numbering objects and materials in order of appearance.
+    pub(super) fn from_rows(width: u32, height: u32, aovs: &[Aov], rows: Vec<Row>) -> Self {
+        let capacity = (width * height * 3) as usize;
+        let mut beauty = Vec::with_capacity(capacity);
+        let mut variance = Vec::with_capacity((width * height) as usize);
+        let mut layers: Vec<(Aov, Vec<f32>)> = aovs
+            .iter()
+            .map(|aov| (*aov, Vec::with_capacity(capacity)))
+            .collect();
+        let mut objects = Numbering::default();
+        let mut materials = Numbering::default();
+
+        for row in rows {
+            beauty.extend(row.beauty);
+            variance.extend(row.variance);
+            for ((aov, values), row_values) in layers.iter_mut().zip(row.layers) {
+                if !aov.is_id() {
+                    values.extend(row_values);
+                }
+            }
+
+            for keys in row.keys {
+                let [object, material] = match keys {
+                    Some([object, material]) => [objects.id(object), materials.id(material)],
+                    None => [0.0, 0.0],
+                };
+                for (aov, values) in layers.iter_mut() {
+                    match aov {
+                        Aov::ObjectId => values.extend([object; 3]),
+                        Aov::MaterialId => values.extend([material; 3]),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            beauty,
+            variance,
+            layers,
+        }
+    }
+
+    /// Drops the layers not in `aovs`.
+    pub(super) fn retain_layers(&mut self, aovs: &[Aov]) {
+        self.layers.retain(|(aov, _)| aovs.contains(aov));
+    }
+}
+
+/// Numbers keys from 1 in the order they are first seen.
+#[derive(Default)]
+struct Numbering(HashMap<usize, u32>);
+
+impl Numbering {
+    fn id(&mut self, key: usize) -> f32 {
+        let next = self.0.len() as u32 + 1;
+        *self.0.entry(key).or_insert(next) as f32
+    }
+}
+
+/// Running sums over the samples of one pixel.
+#[derive(Clone)]
+pub(super) struct PixelSamples {
+    samples: u32,
+    light: LightPaths,
+    /// The light of the samples reaching the pixel through the [`Filter`], its own and its
+    /// neighbours', times their weights, and the sum of those weights.
+    filtered: LightPaths,
+    filter_weight: f32,
+    luminance_squares: f32,
+    albedo: Color,
+    normal: Vec3,
+    position: Vec3,
+    depth: f32,
+    surfaces: u32,
+    /// Object and material of the first sample that met a surface.
+    keys: Option<[usize; 2]>,
+}
+
+impl PixelSamples {
+    pub(super) fn new() -> Self {
+        Self {
+            samples: 0,
+            light: LightPaths::new(),
+            filtered: LightPaths::new(),
+            filter_weight: 0.0,
+            luminance_squares: 0.0,
+            albedo: BLACK,
+            normal: Vec3::new(0.0, 0.0, 0.0),
+            position: Vec3::new(0.0, 0.0, 0.0),
+            depth: 0.0,
+            surfaces: 0,
+            keys: None,
+        }
+    }
+
+    pub(super) fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// The sums as bits, for checkpoints. The keys of the object and material are addresses in
+    /// memory and are left out: a pixel read back finds them again with its next sample.
+    pub(super) fn to_words(&self) -> [u32; PIXEL_WORDS] {
+        let mut words = [0; PIXEL_WORDS];
+        let floats = [
+            &self.light.emitted,
+            &self.light.direct,
+            &self.light.indirect,
+            &self.albedo,
+            &self.normal,
+            &self.position,
+            &self.filtered.emitted,
+            &self.filtered.direct,
+            &self.filtered.indirect,
+        ]
+        .into_iter()
+        .flat_map(|v| [v.x(), v.y(), v.z()])
+        .chain([self.luminance_squares, self.depth, self.filter_weight]);
+        for (word, value) in words.iter_mut().zip(floats) {
+            *word = value.to_bits();
+        }
+        words[PIXEL_WORDS - 2] = self.samples;
+        words[PIXEL_WORDS - 1] = self.surfaces;
+        words
+    }
+
+    /// The inverse of [`to_words`](Self::to_words).
+    pub(super) fn from_words(words: &[u32; PIXEL_WORDS]) -> Self {
+        let float = |i: usize| f32::from_bits(words[i]);
+        let vector = |i: usize| Vec3::new(float(i), float(i + 1), float(i + 2));
+        Self {
+            samples: words[PIXEL_WORDS - 2],
+            light: LightPaths {
+                emitted: vector(0),
+                direct: vector(3),
+                indirect: vector(6),
+            },
+            albedo: vector(9),
+            normal: vector(12),
+            position: vector(15),
+            filtered: LightPaths {
+                emitted: vector(18),
+                direct: vector(21),
+                indirect: vector(24),
+            },
+            luminance_squares: float(27),
+            depth: float(28),
+            filter_weight: float(29),
+            surfaces: words[PIXEL_WORDS - 1],
+            keys: None,
+        }
+    }
+
+    /// Standard error of the mean luminance relative to the mean itself (or to
+    /// [`MIN_RELATIVE_LUMINANCE`] in dark pixels); infinite before there are two samples.
+    pub(super) fn relative_error(&self) -> f32 {
+        let mean = luminance(&self.light.total()) / self.samples as f32;
+        (self.variance(mean) / self.samples as f32).sqrt() / mean.max(MIN_RELATIVE_LUMINANCE)
+    }
+
+    /// Unbiased variance of the luminance of the samples, whose mean is `mean`.
+    fn variance(&self, mean: f32) -> f32 {
+        if self.samples < 2 {
+            return f32::INFINITY;
+        }
+        let n = self.samples as f32;
+        let spread = (self.luminance_squares / n - mean * mean).max(0.0);
+        spread * n / (n - 1.0)
+    }
+
+    pub(super) fn add_light(&mut self, light: &LightPaths) {
+        self.samples += 1;
+        self.luminance_squares += luminance(&light.total()).powi(2);
+        self.light.emitted += &light.emitted;
+        self.light.direct += &light.direct;
+        self.light.indirect += &light.indirect;
+    }
+
+    /// Adds the light of a sample, of this pixel or a neighbour, that reaches it through the
+    /// filter with `weight`.
+    pub(super) fn add_weighted(&mut self, weight: f32, light: &LightPaths) {
+        self.filtered.emitted += weight * &light.emitted;
+        self.filtered.direct += weight * &light.direct;
+        self.filtered.indirect += weight * &light.indirect;
+        self.filter_weight += weight;
+    }
+
+    /// Records the first surface `ray` meets.
+    pub(super) fn add_surface(&mut self, ray: &Ray, rec: &HitRecord) {
+        self.albedo += rec.material().albedo(rec);
+        self.normal += rec.normal().as_vec3();
+        self.position += rec.point().to_vec3();
+        self.depth += rec.t() * ray.direction().norm();
+        self.surfaces += 1;
+
+        if self.keys.is_none() {
+            let material = Arc::as_ptr(&rec.material()) as *const () as usize;
+            self.keys = Some([rec.object(), material]);
+        }
+    }
+}
+
+/// One row of a render, laid out like [`FrameBuffer`].
+pub(super) struct Row {
+    beauty: Vec<f32>,
+    variance: Vec<f32>,
+    layers: Vec<Vec<f32>>,
+    keys: Vec<Option<[usize; 2]>>,
+}
+
+impl Row {
+    pub(super) fn new(width: u32, aovs: &[Aov]) -> Self {
+        let capacity = width as usize * 3;
+        Self {
+            beauty: Vec::with_capacity(capacity),
+            variance: Vec::with_capacity(width as usize),
+            layers: aovs.iter().map(|_| Vec::with_capacity(capacity)).collect(),
+            keys: Vec::with_capacity(width as usize),
+        }
+    }
+
+    /// Appends a pixel, averaging its sums over its samples, and its light over the weights
+    /// of the samples reaching it. Filters with negative lobes can leave light below zero next
+    /// to bright edges, which is clamped.
+    pub(super) fn push(&mut self, aovs: &[Aov], pixel: &PixelSamples) {
+        let scale = 1.0 / pixel.samples.max(1) as f32;
+        let per_surface = 1.0 / pixel.surfaces.max(1) as f32;
+        let per_weight = match pixel.filter_weight {
+            weight if weight > 0.0 => 1.0 / weight,
+            _ => 0.0,
+        };
+        let rgb =
+            |color: &Vec3, scale: f32| [scale * color.x(), scale * color.y(), scale * color.z()];
+        let light = |color: &Vec3| rgb(color, per_weight).map(|c| c.max(0.0));
+
+        self.beauty.extend(light(&pixel.filtered.total()));
+
+        // Of the mean, rather than of the samples.
+        let mean = scale * luminance(&pixel.light.total());
+        self.variance.push(pixel.variance(mean) * scale);
+        for (aov, values) in aovs.iter().zip(self.layers.iter_mut()) {
+            let value = match aov {
+                Aov::Albedo => rgb(&pixel.albedo, scale),
+                Aov::Normal => rgb(&pixel.normal, per_surface),
+                Aov::Depth => [per_surface * pixel.depth; 3],
+                Aov::Position => rgb(&pixel.position, per_surface),
+                Aov::ObjectId | Aov::MaterialId => continue,
+                Aov::Emission => light(&pixel.filtered.emitted),
+                Aov::Direct => light(&pixel.filtered.direct),
+                Aov::Indirect => light(&pixel.filtered.indirect),
+                Aov::SampleCount => [pixel.samples as f32; 3],
+            };
+            values.extend(value);
+        }
+        if aovs.iter().any(|aov| aov.is_id()) {
+            self.keys.push(pixel.keys);
+        }
+    }
+}
+
+/// Rescales each of the three channels of `values` to `[0, 1]` over the range it spans.
+fn normalize_axes(values: &mut [f32]) {
+    for axis in 0..3 {
+        let (min, max) = values
+            .iter()
+            .skip(axis)
+            .step_by(3)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+                (min.min(*v), max.max(*v))
+            });
+        let range = max - min;
+        if !(range > 0.0 && range.is_finite()) {
+            continue;
+        }
+        values
+            .iter_mut()
+            .skip(axis)
+            .step_by(3)
+            .for_each(|v| *v = (*v - min) / range);
+    }
+}
+
+/// The colour of `t` in `[0, 1]` on a heatmap running from dark blue through teal and green
+/// to yellow.
+fn heat_color(t: f32) -> [f32; 3] {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.05, 0.03, 0.35],
+        [0.15, 0.35, 0.60],
+        [0.10, 0.65, 0.55],
+        [0.50, 0.85, 0.30],
+        [0.99, 0.91, 0.15],
+    ];
+    let x = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let i = (x as usize).min(STOPS.len() - 2);
+    let f = x - i as f32;
+    [0, 1, 2].map(|c| STOPS[i][c] + f * (STOPS[i + 1][c] - STOPS[i][c]))
+}
+
+/// A bright colour picked at random for `id`, black for 0.
+fn id_color(id: u32) -> [f32; 3] {
+    if id == 0 {
+        return [0.0; 3];
+    }
+    let mut hash = id;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7feb_352d);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846c_a68b);
+    hash ^= hash >> 16;
+    let channel = |shift: u32| 0.2 + 0.8 * ((hash >> shift) & 0xff) as f32 / 255.0;
+    [channel(0), channel(8), channel(16)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::{HittableList, Quad, Sphere},
+        materials::Lambertian,
+    };
+
+    /// A grey ball on a blue floor under a grey sky, seen level from 4.5 units away.
+    fn render() -> FrameBuffer {
+        let mut world = HittableList::new();
+        world.push(Arc::new(Quad::new(
+            Point3::new(-50.0, 0.0, -50.0),
+            Vec3::new(0.0, 0.0, 100.0),
+            Vec3::new(100.0, 0.0, 0.0),
+            Arc::new(Lambertian::from_color(Color::new(0.2, 0.3, 0.8), 1.0)),
+        )));
+        world.push(Arc::new(Sphere::new(
+            Point3::new(0.0, 1.0, 0.0),
+            0.5,
+            Arc::new(Lambertian::from_color(Color::new(0.5, 0.5, 0.5), 1.0)),
+        )));
+
+        let camera = CameraBuilder::uninitialized()
+            .look_from(Point3::new(0.0, 1.0, 5.0))
+            .look_at(Point3::new(0.0, 1.0, 0.0))
+            .vfov(40.0)
+            .aspect_ratio(1.0)
+            .image_width(15)
+            .samples_per_pixel(16)
+            .background(Color::new(0.5, 0.5, 0.5))
+            .aovs(Aov::ALL.to_vec())
+            .build();
+        camera.render(&world, &HittableList::new())
+    }
+
+    fn pixel(values: &[f32], x: usize, y: usize) -> [f32; 3] {
+        let i = 3 * (y * 15 + x);
+        [values[i], values[i + 1], values[i + 2]]
+    }
+
+    #[test]
+    fn records_every_layer() {
+        let frame = render();
+        let layer = |aov| frame.layer(aov).unwrap();
+        assert_eq!(frame.layers().count(), Aov::ALL.len());
+
+        // The lighting layers add up to the image.
+        for (i, beauty) in frame.beauty().iter().enumerate() {
+            let sum = layer(Aov::Emission)[i] + layer(Aov::Direct)[i] + layer(Aov::Indirect)[i];
+            assert!((beauty - sum).abs() < 1e-4, "{} vs {}", beauty, sum);
+        }
+        let (sky, ball, floor) = ((7, 0), (7, 7), (7, 14));
+
+        // Only the sky is seen without a bounce, and the ball only lit through the sky.
+        assert_eq!(pixel(layer(Aov::Emission), sky.0, sky.1), [0.5; 3]);
+        assert_eq!(pixel(layer(Aov::Emission), ball.0, ball.1), [0.0; 3]);
+        assert!(pixel(layer(Aov::Direct), ball.0, ball.1)[0] > 0.0);
+
+        assert_eq!(pixel(layer(Aov::Depth), sky.0, sky.1), [0.0; 3]);
+        let [depth, ..] = pixel(layer(Aov::Depth), ball.0, ball.1);
+        assert!((depth - 4.5).abs() < 0.05, "{}", depth);
+        let [_, y, _] = pixel(layer(Aov::Position), floor.0, floor.1);
+        assert!(y.abs() < 1e-3, "{}", y);
+
+        assert_eq!(pixel(layer(Aov::Normal), floor.0, floor.1), [0.0, 1.0, 0.0]);
+        let [_, _, z] = pixel(layer(Aov::Normal), ball.0, ball.1);
+        assert!(z > 0.95, "{}", z);
+        let albedo = pixel(layer(Aov::Albedo), floor.0, floor.1);
+        for (value, expected) in albedo.into_iter().zip([0.2, 0.3, 0.8]) {
+            assert!((value - expected).abs() < 1e-5, "{:?}", albedo);
+        }
+
+        // Numbered as they first appear row by row: the ball, then the floor.
+        for aov in [Aov::ObjectId, Aov::MaterialId] {
+            assert_eq!(pixel(layer(aov), sky.0, sky.1), [0.0; 3]);
+            assert_eq!(pixel(layer(aov), ball.0, ball.1), [1.0; 3]);
+            assert_eq!(pixel(layer(aov), floor.0, floor.1), [2.0; 3]);
+        }
+    }
+
+    #[test]
+    fn parses_names() {
+        for aov in Aov::ALL {
+            assert_eq!(aov.to_string().parse(), Ok(aov));
+        }
+        assert!("beauty".parse::<Aov>().is_err());
+    }
+}
@@ -1,8 +1,13 @@
+use std::sync::Arc;
+
 use super::{
     geometry::{Hittable, Primitive},
+    integrators::{first_surface, Integrator, LightPaths, PathTracer, RenderContext},
     math::{
         self,
-        pdf::{self, *},
+        color::luminance,
+        rng,
+        sampler::{self, Sampler, SamplerKind},
         *,
     },
     util::Progress,
@@ -12,6 +17,16 @@ pub(crate) use cam::*;
 
 pub use builder::CameraBuilder;
 pub use cam::Camera;
+pub use checkpoint::{CheckpointError, RenderState};
+pub use denoise::{Denoiser, DEFAULT_DENOISE_ITERATIONS, DENOISE_FEATURES};
+pub use filter::{Filter, FilterKind};
+pub use framebuffer::{Aov, FrameBuffer};
+pub use tiles::{Region, TileOrder};
 
 mod builder;
 mod cam;
+mod checkpoint;
+mod denoise;
+mod filter;
+mod framebuffer;
+mod tiles;
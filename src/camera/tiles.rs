@@ -0,0 +1,227 @@
+//! The order in which the pixels of a pass are handed out to the threads: the image is cut into
+//! square tiles, which threads take one after the other as they become free, so that a few
+//! expensive areas, such as glass, do not hold up a whole row of the image.
+
+use std::{fmt, str::FromStr};
+
+/// A rectangle of pixels, from column `x` and row `y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The part of the region inside a `width` × `height` image.
+    pub fn clip(self, width: u32, height: u32) -> Self {
+        let (x, y) = (self.x.min(width), self.y.min(height));
+        Self {
+            x,
+            y,
+            width: self.width.min(width - x),
+            height: self.height.min(height - y),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    /// Accepts `x,y,width,height`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(',').collect();
+        if parts.len() != 4 {
+            return Err(format!("Invalid region format: '{}' (expected x,y,w,h)", s));
+        }
+        let mut values = [0; 4];
+        for (value, part) in values.iter_mut().zip(parts) {
+            *value = part.trim().parse::<u32>().map_err(|e| e.to_string())?;
+        }
+        let [x, y, width, height] = values;
+        Ok(Region::new(x, y, width, height))
+    }
+}
+
+/// The orders tiles can be rendered in, selectable by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileOrder {
+    /// Row by row of tiles, from the top left.
+    Scanline,
+    /// Outwards from the centre of the image, around and around.
+    Spiral,
+    /// Along a Hilbert curve, so that consecutive tiles touch and share what they cache.
+    #[default]
+    Hilbert,
+}
+
+impl TileOrder {
+    /// The tiles of `size` × `size` pixels covering a `width` × `height` image, in this order.
+    /// Tiles on the right and bottom edges are cut to the image.
+    pub fn tiles(self, width: u32, height: u32, size: u32) -> Vec<Region> {
+        let size = size.max(1);
+        let (columns, rows) = (width.div_ceil(size), height.div_ceil(size));
+        let scanline = (0..rows).flat_map(|row| (0..columns).map(move |column| [column, row]));
+        let cells: Vec<[u32; 2]> = match self {
+            TileOrder::Scanline => scanline.collect(),
+            TileOrder::Spiral => spiral(columns, rows),
+            TileOrder::Hilbert => {
+                let side = columns.max(rows).next_power_of_two();
+                let mut cells: Vec<[u32; 2]> = scanline.collect();
+                cells.sort_by_key(|&[column, row]| hilbert_index(side, column, row));
+                cells
+            }
+        };
+
+        cells
+            .into_iter()
+            .map(|[column, row]| Region::new(column * size, row * size, size, size))
+            .map(|tile| tile.clip(width, height))
+            .collect()
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TileOrder::Scanline => "scanline",
+            TileOrder::Spiral => "spiral",
+            TileOrder::Hilbert => "hilbert",
+        }
+    }
+}
+
+impl FromStr for TileOrder {
+    type Err = String;
+
+    /// Accepts `scanline`, `spiral` and `hilbert`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [TileOrder::Scanline, TileOrder::Spiral, TileOrder::Hilbert]
+            .into_iter()
+            .find(|order| order.name() == s.trim())
+            .ok_or_else(|| {
+                format!(
+                    "Unknown tile order: '{}' (expected scanline, spiral or hilbert)",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for TileOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The cells of a `columns` × `rows` grid, walking right, down, left and up from the centre in
+/// ever longer legs, and skipping the steps outside the grid.
+fn spiral(columns: u32, rows: u32) -> Vec<[u32; 2]> {
+    let total = (columns * rows) as usize;
+    let mut cells = Vec::with_capacity(total);
+    let (mut x, mut y) = (((columns as i64) - 1) / 2, ((rows as i64) - 1) / 2);
+    let mut leg = 1;
+    while cells.len() < total {
+        for (dx, dy) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+            for _ in 0..leg {
+                if (0..columns as i64).contains(&x) && (0..rows as i64).contains(&y) {
+                    cells.push([x as u32, y as u32]);
+                }
+                x += dx;
+                y += dy;
+            }
+            // Legs grow after every second turn.
+            if dy != 0 {
+                leg += 1;
+            }
+        }
+    }
+    cells
+}
+
+/// Distance along the Hilbert curve through a `side` × `side` grid, `side` a power of two,
+/// to the cell `(x, y)`.
+fn hilbert_index(side: u32, mut x: u32, mut y: u32) -> u64 {
+    let mut index = 0;
+    let mut s = side / 2;
+    while s > 0 {
+        let rx = u32::from(x & s != 0);
+        let ry = u32::from(y & s != 0);
+        index += u64::from(s) * u64::from(s) * u64::from((3 * rx) ^ ry);
+        // Turn the quadrant so that the curve within it starts where the last one ended.
+        if ry == 0 {
+            if rx == 1 {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_cover_the_image_once() {
+        for order in [TileOrder::Scanline, TileOrder::Spiral, TileOrder::Hilbert] {
+            let tiles = order.tiles(37, 21, 8);
+            assert_eq!(tiles.len(), 5 * 3, "{}", order);
+            let mut covered = vec![0; 37 * 21];
+            for tile in tiles {
+                for y in tile.y..tile.y + tile.height {
+                    for x in tile.x..tile.x + tile.width {
+                        covered[(y * 37 + x) as usize] += 1;
+                    }
+                }
+            }
+            assert!(covered.iter().all(|&n| n == 1), "{}", order);
+        }
+    }
+
+    #[test]
+    fn orders_tiles() {
+        let cell = |tile: &Region| [tile.x / 4, tile.y / 4];
+
+        // From the centre outwards.
+        let spiral: Vec<_> = TileOrder::Spiral
+            .tiles(12, 12, 4)
+            .iter()
+            .map(cell)
+            .collect();
+        assert_eq!(spiral[..3], [[1, 1], [2, 1], [2, 2]]);
+
+        // Each tile touches the one before.
+        let hilbert = TileOrder::Hilbert.tiles(32, 32, 4);
+        assert_eq!(cell(&hilbert[0]), [0, 0]);
+        for pair in hilbert.windows(2) {
+            let ([x0, y0], [x1, y1]) = (cell(&pair[0]), cell(&pair[1]));
+            assert_eq!(x0.abs_diff(x1) + y0.abs_diff(y1), 1);
+        }
+    }
+
+    #[test]
+    fn parses_regions() {
+        assert_eq!("10, 20,30,40".parse(), Ok(Region::new(10, 20, 30, 40)));
+        assert!("10,20,30".parse::<Region>().is_err());
+        assert_eq!(
+            Region::new(90, 5, 20, 10).clip(100, 50),
+            Region::new(90, 5, 10, 10)
+        );
+    }
+}
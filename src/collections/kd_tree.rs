@@ -0,0 +1,134 @@
+use crate::math::{Measurable, Point3};
+
+/// A balanced 3-d tree over points carrying a value each, for fixed-radius neighbour queries.
+///
+/// The tree is built once and stored implicitly: every subslice of `nodes` holds its median,
+/// split along `axis`, in the middle, with the points on either side of the plane before and
+/// after it.
+pub struct KdTree<T> {
+    nodes: Vec<Node<T>>,
+}
+
+struct Node<T> {
+    point: Point3,
+    value: T,
+    axis: usize,
+}
+
+impl<T> KdTree<T> {
+    pub fn new(items: Vec<(Point3, T)>) -> Self {
+        let mut nodes: Vec<Node<T>> = items
+            .into_iter()
+            .map(|(point, value)| Node {
+                point,
+                value,
+                axis: 0,
+            })
+            .collect();
+        build(&mut nodes);
+
+        Self { nodes }
+    }
+
+    /// Calls `f` with every point no further than `radius` from `center`, and its value.
+    pub fn for_each_within<F>(&self, center: &Point3, radius: f32, mut f: F)
+    where
+        F: FnMut(&Point3, &T),
+    {
+        within(&self.nodes, center, radius, radius * radius, &mut f);
+    }
+}
+
+fn build<T>(nodes: &mut [Node<T>]) {
+    if nodes.len() <= 1 {
+        return;
+    }
+
+    // Split along the axis where the points spread the most.
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for node in nodes.iter() {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(node.point[axis]);
+            max[axis] = max[axis].max(node.point[axis]);
+        }
+    }
+    let axis = (0..3)
+        .max_by(|&a, &b| (max[a] - min[a]).total_cmp(&(max[b] - min[b])))
+        .unwrap();
+
+    let mid = nodes.len() / 2;
+    nodes.select_nth_unstable_by(mid, |a, b| a.point[axis].total_cmp(&b.point[axis]));
+    nodes[mid].axis = axis;
+
+    let (left, right) = nodes.split_at_mut(mid);
+    build(left);
+    build(&mut right[1..]);
+}
+
+fn within<T, F>(nodes: &[Node<T>], center: &Point3, radius: f32, radius_squared: f32, f: &mut F)
+where
+    F: FnMut(&Point3, &T),
+{
+    if nodes.is_empty() {
+        return;
+    }
+
+    let mid = nodes.len() / 2;
+    let node = &nodes[mid];
+    if (&node.point - center).len_squared() <= radius_squared {
+        f(&node.point, &node.value);
+    }
+    if nodes.len() == 1 {
+        return;
+    }
+
+    let offset = center[node.axis] - node.point[node.axis];
+    if offset <= radius {
+        within(&nodes[..mid], center, radius, radius_squared, f);
+    }
+    if offset >= -radius {
+        within(&nodes[mid + 1..], center, radius, radius_squared, f);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::random_float;
+
+    #[test]
+    fn finds_exactly_the_points_in_range() {
+        let points: Vec<Point3> = (0..2000)
+            .map(|_| Point3::new(random_float(), random_float(), random_float()))
+            .collect();
+        let tree = KdTree::new(
+            points
+                .iter()
+                .cloned()
+                .enumerate()
+                .map(|(i, p)| (p, i))
+                .collect(),
+        );
+
+        for _ in 0..50 {
+            let center = Point3::new(random_float(), random_float(), random_float());
+            let radius = 0.2 * random_float();
+
+            let mut found = Vec::new();
+            tree.for_each_within(&center, radius, |_, &i| found.push(i));
+            found.sort_unstable();
+
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&i| (&points[i] - &center).len_squared() <= radius * radius)
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let tree = KdTree::<()>::new(Vec::new());
+        tree.for_each_within(&Point3::from_origin(), 1.0, |_, _| panic!());
+    }
+}
@@ -1,4 +1,5 @@
-pub use array_list::ArrayList;
+pub use {array_list::ArrayList, kd_tree::KdTree};
 
 mod array_list;
 mod iter;
+mod kd_tree;
@@ -109,7 +109,10 @@ impl Hittable for Bvh {
                     None
                 }
             }
-            Self::Leaf(primitive) => primitive.hit(ray, ray_t),
+            Self::Leaf(primitive) => primitive.hit(ray, ray_t).map(|mut rec| {
+                rec.set_object(primitive);
+                rec
+            }),
             Self::Empty => None,
         }
     }
@@ -121,4 +124,21 @@ impl Hittable for Bvh {
             Self::Empty => &Aabb::EMPTY,
         }
     }
+
+    fn transmittance(&self, ray: &Ray, ray_t: Interval) -> f32 {
+        match self {
+            Self::Node { left, right, .. } => {
+                let Some(bbox_interval) = self.bounding_box().hit(ray, ray_t) else {
+                    return 1.0;
+                };
+                let left = left.transmittance(ray, bbox_interval.clone());
+                if left <= 0.0 {
+                    return 0.0;
+                }
+                left * right.transmittance(ray, bbox_interval)
+            }
+            Self::Leaf(primitive) => primitive.transmittance(ray, ray_t),
+            Self::Empty => 1.0,
+        }
+    }
 }
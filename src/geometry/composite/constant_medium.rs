@@ -28,8 +28,10 @@ impl ConstantMedium {
     }
 }
 
-impl Hittable for ConstantMedium {
-    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+impl ConstantMedium {
+    /// Where the ray enters the medium within `ray_t`, and how far it travels inside in world
+    /// units, assuming the boundary is convex.
+    fn chord(&self, ray: &Ray, ray_t: &Interval) -> Option<(f32, f32)> {
         let record1 = self.boundary.hit(ray, Interval::UNIVERSE)?;
 
         let record2 = self
@@ -45,8 +47,15 @@ impl Hittable for ConstantMedium {
 
         let t1 = t1.max(0.0);
 
+        Some((t1, (t2 - t1) * ray.direction().norm()))
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let (t1, distance_inside_boundary) = self.chord(ray, &ray_t)?;
+
         let ray_length = ray.direction().norm();
-        let distance_inside_boundary = (t2 - t1) * ray_length;
         let hit_distance = self.neg_inv_density * random_float().ln();
 
         if hit_distance > distance_inside_boundary {
@@ -64,6 +73,14 @@ impl Hittable for ConstantMedium {
     fn bounding_box(&self) -> &Aabb {
         self.boundary.bounding_box()
     }
+
+    /// Beer-Lambert attenuation over the chord through the medium.
+    fn transmittance(&self, ray: &Ray, ray_t: Interval) -> f32 {
+        match self.chord(ray, &ray_t) {
+            Some((_, distance)) => (self.neg_inv_density.recip() * distance).exp(),
+            None => 1.0,
+        }
+    }
 }
 
 impl Primitive for ConstantMedium {
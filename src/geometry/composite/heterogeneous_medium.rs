@@ -0,0 +1,258 @@
+use super::*;
+use crate::textures::SolidColor;
+
+/// Most boundary crossings followed along a single ray, to stay clear of endless loops on
+/// degenerate geometry.
+const MAX_CROSSINGS: usize = 64;
+
+/// Step past a boundary crossing before looking for the next one.
+const CROSSING_EPSILON: f32 = 0.0001;
+
+/// A participating medium whose density varies over space, following a [`DensityField`].
+///
+/// The extinction coefficient at a point is `density` times the value of the field there.
+/// Scattering events are sampled with delta tracking and shadow rays are attenuated with ratio
+/// tracking, both against the bound given by [`DensityField::max_density`]. Unlike
+/// [`ConstantMedium`], the boundary need not be convex: every stretch of the ray between
+/// entering and leaving it is tracked, as long as the boundary is closed.
+pub struct HeterogeneousMedium {
+    boundary: Arc<dyn Primitive>,
+    phase_function: Arc<dyn Material>,
+    field: Arc<dyn DensityField>,
+    density: f32,
+    majorant: f32,
+}
+
+impl HeterogeneousMedium {
+    pub fn from_texture(
+        boundary: Arc<dyn Primitive>,
+        density: f32,
+        field: Arc<dyn DensityField>,
+        texture: Arc<dyn Texture>,
+    ) -> Self {
+        let phase_function = Arc::new(Isotropic::from_texture(texture));
+        let majorant = density * field.max_density();
+        HeterogeneousMedium {
+            boundary,
+            phase_function,
+            field,
+            density,
+            majorant,
+        }
+    }
+
+    pub fn from_color(
+        boundary: Arc<dyn Primitive>,
+        density: f32,
+        field: Arc<dyn DensityField>,
+        color: Color,
+    ) -> Self {
+        let texture = Arc::new(SolidColor::from_color(color));
+        Self::from_texture(boundary, density, field, texture)
+    }
+
+    fn extinction(&self, p: &Point3) -> f32 {
+        self.density * self.field.density(p).clamp(0.0, self.field.max_density())
+    }
+
+    /// Calls `f` with the start and end of every stretch of the ray inside the boundary within
+    /// `ray_t`, nearest first, until it returns false.
+    ///
+    /// A stretch lies inside when it ends where the ray meets the back of the boundary, which
+    /// also covers rays starting inside the medium, or starts where it met the front. The latter
+    /// keeps overlapping pieces of a boundary, such as a group of spheres, filled throughout.
+    fn for_each_segment<F>(&self, ray: &Ray, ray_t: &Interval, mut f: F)
+    where
+        F: FnMut(f32, f32) -> bool,
+    {
+        let mut start = ray_t.min();
+        let mut entered = false;
+        for _ in 0..MAX_CROSSINGS {
+            let Some(rec) = self.boundary.hit(ray, Interval::new(start, f32::INFINITY)) else {
+                return;
+            };
+
+            let crossing = rec.t();
+            if entered || !rec.front_face() {
+                let end = crossing.min(ray_t.max());
+                if start < end && !f(start, end) {
+                    return;
+                }
+            }
+            if crossing >= ray_t.max() {
+                return;
+            }
+            start = crossing + CROSSING_EPSILON;
+            entered = rec.front_face();
+        }
+    }
+
+    /// Distance along the ray, in units of `t`, to the next tentative collision.
+    fn free_flight(&self, ray_length: f32) -> f32 {
+        -(1.0 - random_float()).ln() / (self.majorant * ray_length)
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if self.majorant <= 0.0 {
+            return None;
+        }
+
+        let ray_length = ray.direction().norm();
+        let mut t_hit = None;
+        self.for_each_segment(ray, &ray_t, |start, end| {
+            let mut t = start;
+            loop {
+                t += self.free_flight(ray_length);
+                if t >= end {
+                    return true;
+                }
+                if random_float() * self.majorant < self.extinction(&ray.at(t)) {
+                    t_hit = Some(t);
+                    return false;
+                }
+            }
+        });
+
+        let t_hit = t_hit?;
+        Some(HitRecord::new_arbitrary(
+            ray.at(t_hit),
+            t_hit,
+            self.phase_function.clone(),
+        ))
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        self.boundary.bounding_box()
+    }
+
+    fn transmittance(&self, ray: &Ray, ray_t: Interval) -> f32 {
+        if self.majorant <= 0.0 {
+            return 1.0;
+        }
+
+        let ray_length = ray.direction().norm();
+        let mut transmittance = 1.0;
+        self.for_each_segment(ray, &ray_t, |start, end| {
+            let mut t = start;
+            loop {
+                t += self.free_flight(ray_length);
+                if t >= end {
+                    return true;
+                }
+                transmittance *= 1.0 - self.extinction(&ray.at(t)) / self.majorant;
+                if transmittance <= 0.0 {
+                    return false;
+                }
+            }
+        });
+
+        transmittance
+    }
+}
+
+impl Primitive for HeterogeneousMedium {
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f32 {
+        self.boundary.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        self.boundary.random(origin)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{geometry::Sphere, materials::Empty, math::rng, textures::VoxelGrid};
+
+    fn uniform(density: f32) -> Arc<dyn DensityField> {
+        Arc::new(VoxelGrid::new(
+            Point3::new(-10.0, -10.0, -10.0),
+            Point3::new(10.0, 10.0, 10.0),
+            [1, 1, 1],
+            vec![density],
+        ))
+    }
+
+    fn sphere(center: Point3, radius: f32) -> Arc<dyn Primitive> {
+        Arc::new(Sphere::new(center, radius, Arc::new(Empty)))
+    }
+
+    #[test]
+    fn ratio_tracking_matches_beer_lambert() {
+        rng::seed_thread(1, 0);
+        let medium = HeterogeneousMedium::from_color(
+            sphere(Point3::from_origin(), 1.0),
+            0.5,
+            uniform(1.0),
+            WHITE,
+        );
+        let ray = Ray::new(Point3::new(-3.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let n = 20_000;
+        let mean = (0..n)
+            .map(|_| medium.transmittance(&ray, Interval::new(0.001, 10.0)))
+            .sum::<f32>()
+            / n as f32;
+        assert!((mean - (-1.0f32).exp()).abs() < 0.01, "{mean}");
+
+        // Starting inside, only the way out counts.
+        let inside = Ray::new(Point3::from_origin(), Vec3::new(0.0, 2.0, 0.0));
+        let mean = (0..n)
+            .map(|_| medium.transmittance(&inside, Interval::new(0.001, 10.0)))
+            .sum::<f32>()
+            / n as f32;
+        assert!((mean - (-0.5f32).exp()).abs() < 0.01, "{mean}");
+    }
+
+    #[test]
+    fn fills_overlapping_boundaries() {
+        rng::seed_thread(2, 0);
+        let mut blob = HittableList::new();
+        blob.push(sphere(Point3::new(-0.5, 0.0, 0.0), 1.0));
+        blob.push(sphere(Point3::new(0.5, 0.0, 0.0), 1.0));
+        let medium = HeterogeneousMedium::from_color(Arc::new(blob), 0.5, uniform(1.0), WHITE);
+        let ray = Ray::new(Point3::new(-3.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        // The union spans 3 units along the axis.
+        let n = 20_000;
+        let mean = (0..n)
+            .map(|_| medium.transmittance(&ray, Interval::new(0.001, 10.0)))
+            .sum::<f32>()
+            / n as f32;
+        assert!((mean - (-1.5f32).exp()).abs() < 0.01, "{mean}");
+    }
+
+    #[test]
+    fn tracks_every_segment_of_a_non_convex_boundary() {
+        rng::seed_thread(3, 0);
+        let mut lobes = HittableList::new();
+        lobes.push(sphere(Point3::new(-2.0, 0.0, 0.0), 1.0));
+        lobes.push(sphere(Point3::new(2.0, 0.0, 0.0), 1.0));
+        let medium = HeterogeneousMedium::from_color(Arc::new(lobes), 0.5, uniform(1.0), WHITE);
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        // Two chords of length 2 and nothing in the gap between them.
+        let n = 20_000;
+        let mean = (0..n)
+            .map(|_| medium.transmittance(&ray, Interval::new(0.001, 10.0)))
+            .sum::<f32>()
+            / n as f32;
+        assert!((mean - (-2.0f32).exp()).abs() < 0.01, "{mean}");
+
+        // Delta tracking agrees, and never stops in the gap.
+        let mut passed = 0;
+        for _ in 0..n {
+            match medium.hit(&ray, Interval::new(0.001, 10.0)) {
+                Some(rec) => {
+                    assert!((2.0..=4.0).contains(&rec.t()) || (6.0..=8.0).contains(&rec.t()))
+                }
+                None => passed += 1,
+            }
+        }
+        let passed = passed as f32 / n as f32;
+        assert!((passed - (-2.0f32).exp()).abs() < 0.015, "{passed}");
+    }
+}
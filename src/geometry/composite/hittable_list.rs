@@ -69,7 +69,8 @@ impl Hittable for HittableList {
         let mut temp_rec = None;
 
         for object in self.objects.iter() {
-            if let Some(record) = object.hit(ray, Interval::new(ray_t.min, closest_so_far)) {
+            if let Some(mut record) = object.hit(ray, Interval::new(ray_t.min, closest_so_far)) {
+                record.set_object(object);
                 closest_so_far = record.t();
                 temp_rec = Some(record);
             }
@@ -81,6 +82,17 @@ impl Hittable for HittableList {
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn transmittance(&self, ray: &Ray, ray_t: Interval) -> f32 {
+        let mut transmittance = 1.0;
+        for object in self.objects.iter() {
+            transmittance *= object.transmittance(ray, ray_t.clone());
+            if transmittance <= 0.0 {
+                return 0.0;
+            }
+        }
+        transmittance
+    }
 }
 
 impl Primitive for HittableList {
@@ -107,6 +119,37 @@ impl Primitive for HittableList {
             .unwrap()
             .random(origin)
     }
+
+    /// Picks an object uniformly, then a point on it.
+    fn sample_surface(&self, time: f32) -> Option<SurfaceSample> {
+        if self.objects.is_empty() {
+            return None;
+        }
+
+        let size = self.objects.len() as f32;
+        let mut sample = self
+            .objects
+            .get(random_int_beetwen(0.0, size - 1.0))
+            .unwrap()
+            .sample_surface(time)?;
+        sample.pdf /= size;
+        Some(sample)
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        let mut closest = f32::INFINITY;
+        let mut pdf = 0.0;
+
+        for object in self.objects.iter() {
+            if let Some(rec) = object.hit(ray, Interval::new(Interval::CAMERA_VIEW.min(), closest))
+            {
+                closest = rec.t();
+                pdf = object.surface_pdf(ray);
+            }
+        }
+
+        pdf / self.objects.len().max(1) as f32
+    }
 }
 
 impl Default for HittableList {
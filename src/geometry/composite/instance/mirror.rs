@@ -31,9 +31,11 @@ impl Hittable for MirrorYZ {
         let point_t = Self::transform_point(rec.point());
         let normal_t = Self::transform_vector(&rec.normal().as_vec3());
         let unit_normal = UnitVec3::new(normal_t);
+        let geometric_t = Self::transform_vector(&rec.geometric_normal().as_vec3());
 
         rec.front_face = !rec.front_face;
         rec.normal = unit_normal;
+        rec.geometric_normal = UnitVec3::new(geometric_t);
         rec.point = point_t;
 
         Some(rec)
@@ -42,6 +44,13 @@ impl Hittable for MirrorYZ {
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn transmittance(&self, ray: &Ray, t_interval: Interval) -> f32 {
+        let mirrored_origin = Self::transform_point(ray.origin());
+        let mirrored_direction = Self::transform_vector(ray.direction());
+        let mirrored_ray = Ray::new(mirrored_origin, mirrored_direction).set_time(ray.time());
+        self.object.transmittance(&mirrored_ray, t_interval)
+    }
 }
 impl Primitive for MirrorYZ {
     fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f32 {
@@ -54,4 +63,21 @@ impl Primitive for MirrorYZ {
         let mirrored_origin = Self::transform_point(origin);
         Self::transform_vector(&self.object.random(&mirrored_origin))
     }
+
+    fn sample_surface(&self, time: f32) -> Option<SurfaceSample> {
+        let sample = self.object.sample_surface(time)?;
+
+        Some(SurfaceSample {
+            point: Self::transform_point(&sample.point),
+            normal: UnitVec3::new(Self::transform_vector(&sample.normal.as_vec3())),
+            pdf: sample.pdf,
+        })
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        let mirrored_origin = Self::transform_point(ray.origin());
+        let mirrored_direction = Self::transform_vector(ray.direction());
+        let mirrored_ray = Ray::new(mirrored_origin, mirrored_direction).set_time(ray.time());
+        self.object.surface_pdf(&mirrored_ray)
+    }
 }
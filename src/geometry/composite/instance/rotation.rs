@@ -61,6 +61,13 @@ impl Hittable for Rotation {
             inv_normal.y(),
             inv_normal.z(),
         ));
+        let inv_geometric =
+            (self.inverse_transform_vector)(self, &rec.geometric_normal().as_vec3());
+        rec.set_geometric_normal(UnitVec3::unchecked_new(
+            inv_geometric.x(),
+            inv_geometric.y(),
+            inv_geometric.z(),
+        ));
 
         Some(rec)
     }
@@ -68,15 +75,43 @@ impl Hittable for Rotation {
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn transmittance(&self, ray: &Ray, t_interval: Interval) -> f32 {
+        let rotated_origin = (self.transform_point)(self, ray.origin());
+        let rotated_direction = (self.transform_vector)(self, ray.direction());
+        let rotated_ray = Ray::new(rotated_origin, rotated_direction).set_time(ray.time());
+        self.object.transmittance(&rotated_ray, t_interval)
+    }
 }
 
 impl Primitive for Rotation {
     fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f32 {
-        self.object.pdf_value(origin, direction)
+        let rotated_origin = (self.transform_point)(self, origin);
+        let rotated_direction = (self.transform_vector)(self, direction);
+        self.object.pdf_value(&rotated_origin, &rotated_direction)
     }
 
     fn random(&self, origin: &Point3) -> Vec3 {
-        self.object.random(origin)
+        let rotated_origin = (self.transform_point)(self, origin);
+        (self.inverse_transform_vector)(self, &self.object.random(&rotated_origin))
+    }
+
+    fn sample_surface(&self, time: f32) -> Option<SurfaceSample> {
+        let sample = self.object.sample_surface(time)?;
+        let normal = (self.inverse_transform_vector)(self, &sample.normal.as_vec3());
+
+        Some(SurfaceSample {
+            point: (self.inverse_transform_point)(self, &sample.point),
+            normal: UnitVec3::unchecked_new(normal.x(), normal.y(), normal.z()),
+            pdf: sample.pdf,
+        })
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        let rotated_origin = (self.transform_point)(self, ray.origin());
+        let rotated_direction = (self.transform_vector)(self, ray.direction());
+        let rotated_ray = Ray::new(rotated_origin, rotated_direction).set_time(ray.time());
+        self.object.surface_pdf(&rotated_ray)
     }
 }
 
@@ -144,6 +179,10 @@ impl Hittable for RotationX {
     fn bounding_box(&self) -> &Aabb {
         self.0.bounding_box()
     }
+
+    fn transmittance(&self, ray: &Ray, t_interval: Interval) -> f32 {
+        self.0.transmittance(ray, t_interval)
+    }
 }
 
 impl Primitive for RotationX {
@@ -154,6 +193,14 @@ impl Primitive for RotationX {
     fn random(&self, origin: &Point3) -> Vec3 {
         self.0.random(origin)
     }
+
+    fn sample_surface(&self, time: f32) -> Option<SurfaceSample> {
+        self.0.sample_surface(time)
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        self.0.surface_pdf(ray)
+    }
 }
 
 // ─────────────────────────────
@@ -219,6 +266,10 @@ impl Hittable for RotationY {
     fn bounding_box(&self) -> &Aabb {
         self.0.bounding_box()
     }
+
+    fn transmittance(&self, ray: &Ray, t_interval: Interval) -> f32 {
+        self.0.transmittance(ray, t_interval)
+    }
 }
 
 impl Primitive for RotationY {
@@ -228,6 +279,14 @@ impl Primitive for RotationY {
     fn random(&self, origin: &Point3) -> Vec3 {
         self.0.random(origin)
     }
+
+    fn sample_surface(&self, time: f32) -> Option<SurfaceSample> {
+        self.0.sample_surface(time)
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        self.0.surface_pdf(ray)
+    }
 }
 
 // ─────────────────────────────
@@ -293,6 +352,10 @@ impl Hittable for RotationZ {
     fn bounding_box(&self) -> &Aabb {
         self.0.bounding_box()
     }
+
+    fn transmittance(&self, ray: &Ray, t_interval: Interval) -> f32 {
+        self.0.transmittance(ray, t_interval)
+    }
 }
 
 impl Primitive for RotationZ {
@@ -302,4 +365,12 @@ impl Primitive for RotationZ {
     fn random(&self, origin: &Point3) -> Vec3 {
         self.0.random(origin)
     }
+
+    fn sample_surface(&self, time: f32) -> Option<SurfaceSample> {
+        self.0.sample_surface(time)
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        self.0.surface_pdf(ray)
+    }
 }
@@ -32,14 +32,32 @@ impl Hittable for Translation {
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn transmittance(&self, ray: &Ray, ray_t: Interval) -> f32 {
+        let offset_ray =
+            Ray::new(ray.origin() - &self.offset, ray.direction().clone()).set_time(ray.time());
+        self.object.transmittance(&offset_ray, ray_t)
+    }
 }
 
 impl Primitive for Translation {
     fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f32 {
-        self.object.pdf_value(origin, direction)
+        self.object.pdf_value(&(origin - &self.offset), direction)
     }
 
     fn random(&self, origin: &Point3) -> Vec3 {
-        self.object.random(origin)
+        self.object.random(&(origin - &self.offset))
+    }
+
+    fn sample_surface(&self, time: f32) -> Option<SurfaceSample> {
+        let mut sample = self.object.sample_surface(time)?;
+        sample.point = sample.point.translate(&self.offset);
+        Some(sample)
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        let offset_ray =
+            Ray::new(ray.origin() - &self.offset, ray.direction().clone()).set_time(ray.time());
+        self.object.surface_pdf(&offset_ray)
     }
 }
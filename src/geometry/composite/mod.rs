@@ -2,7 +2,8 @@ use super::*;
 
 mod bvh;
 mod constant_medium;
+mod heterogeneous_medium;
 mod hittable_list;
 mod instance;
 
-pub use {bvh::*, constant_medium::*, hittable_list::*, instance::*};
+pub use {bvh::*, constant_medium::*, heterogeneous_medium::*, hittable_list::*, instance::*};
@@ -4,10 +4,17 @@ use super::*;
 pub struct HitRecord {
     pub(super) point: Point3,
     pub(super) normal: UnitVec3,
+    /// Normal of the underlying geometry, on the same side as `normal`; differs from it only
+    /// where the shading normal is interpolated.
+    pub(super) geometric_normal: UnitVec3,
     pub(super) material: Arc<dyn Material>,
     t: f32,
     uv: Option<(f32, f32)>,
+    color: Option<Color>,
+    on_surface: bool,
     pub(super) front_face: bool,
+    /// Address of the scene object the ray hit, see [`HitRecord::object`].
+    object: usize,
 }
 
 impl HitRecord {
@@ -22,11 +29,48 @@ impl HitRecord {
 
         Self {
             point,
+            geometric_normal: normal.clone(),
             normal,
             material,
             t,
             front_face,
             uv: None,
+            color: None,
+            on_surface: true,
+            object: 0,
+        }
+    }
+
+    /// Like [`HitRecord::new`], but `front_face` is decided by the geometric normal while the
+    /// stored normal is the (interpolated) shading normal, turned to the same side as the ray.
+    ///
+    /// `shading_normal` is expected on the same side of the surface as `geometric_normal`.
+    pub fn with_shading_normal(
+        ray: &Ray,
+        point: Point3,
+        geometric_normal: &UnitVec3,
+        shading_normal: UnitVec3,
+        t: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let front_face = ray.direction().dot(geometric_normal) < 0.0;
+        let (normal, geometric_normal) = if front_face {
+            (shading_normal, geometric_normal.clone())
+        } else {
+            (-shading_normal, -geometric_normal)
+        };
+
+        Self {
+            point,
+            normal,
+            geometric_normal,
+            material,
+            t,
+            front_face,
+            uv: None,
+            color: None,
+            on_surface: true,
+            object: 0,
         }
     }
 
@@ -46,6 +90,12 @@ impl HitRecord {
         self.normal
     }
 
+    /// Normal of the surface itself, facing the ray like [`normal`](Self::normal), before any
+    /// shading normal interpolation.
+    pub fn geometric_normal(&self) -> &UnitVec3 {
+        &self.geometric_normal
+    }
+
     pub fn t(&self) -> f32 {
         self.t
     }
@@ -54,10 +104,27 @@ impl HitRecord {
         self.material.clone()
     }
 
+    /// False for scattering events inside a participating medium, whose normal is arbitrary.
+    pub fn on_surface(&self) -> bool {
+        self.on_surface
+    }
+
     pub fn front_face(&self) -> bool {
         self.front_face
     }
 
+    /// Tells apart the objects of a scene within one render: the containing [`Bvh`] or
+    /// [`HittableList`] stamps the record with the address of the child that was hit, so the
+    /// outermost one names the object as it was added to the scene. Zero when no container
+    /// was involved.
+    pub fn object(&self) -> usize {
+        self.object
+    }
+
+    pub(crate) fn set_object<T: ?Sized>(&mut self, object: &Arc<T>) {
+        self.object = Arc::as_ptr(object) as *const () as usize;
+    }
+
     pub fn u(&self) -> f32 {
         match self.uv {
             Some((u, _)) => u,
@@ -77,6 +144,16 @@ impl HitRecord {
         self
     }
 
+    /// Interpolated vertex colour, for meshes that carry one.
+    pub fn vertex_color(&self) -> Option<&Color> {
+        self.color.as_ref()
+    }
+
+    pub fn set_vertex_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
     pub fn set_point(&mut self, point: Point3) {
         self.point = point;
     }
@@ -85,8 +162,13 @@ impl HitRecord {
         self.normal = n;
     }
 
+    pub fn set_geometric_normal(&mut self, n: UnitVec3) {
+        self.geometric_normal = n;
+    }
+
     pub fn negate_normal(&mut self) {
         self.normal = -&self.normal;
+        self.geometric_normal = -&self.geometric_normal;
     }
 
     pub fn distance(&self) -> f32 {
@@ -111,11 +193,15 @@ impl HitRecord {
 
         Self {
             point,
+            geometric_normal: normal.clone(),
             normal,
             material,
             t,
             front_face,
             uv: None,
+            color: None,
+            on_surface: false,
+            object: 0,
         }
     }
 }
@@ -6,7 +6,7 @@ pub(crate) use {
     crate::{
         materials::{Isotropic, Material},
         math::*,
-        textures::Texture,
+        textures::{DensityField, Texture},
     },
     std::sync::Arc,
 };
@@ -17,4 +17,17 @@ pub trait Hittable: Send + Sync + std::any::Any {
     fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord>;
 
     fn bounding_box(&self) -> &Aabb;
+
+    /// Fraction of the light that gets through along `ray` within `ray_t`, for shadow rays.
+    ///
+    /// Opaque geometry lets nothing through if it is hit at all. Participating media override
+    /// this with an estimate of their transmittance, and containers with the product over their
+    /// children.
+    fn transmittance(&self, ray: &Ray, ray_t: Interval) -> f32 {
+        if self.hit(ray, ray_t).is_some() {
+            0.0
+        } else {
+            1.0
+        }
+    }
 }
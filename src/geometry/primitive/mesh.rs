@@ -0,0 +1,535 @@
+use std::cmp::Ordering;
+
+use super::*;
+
+/// Faces per BVH leaf.
+const LEAF_SIZE: usize = 4;
+
+/// A triangle of a [`MeshData`], given as indices into its buffers.
+///
+/// `normals`, `uvs` and `colors` are optional per face, so a mesh may mix smooth and flat faces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshFace {
+    pub positions: [u32; 3],
+    pub normals: Option<[u32; 3]>,
+    pub uvs: Option<[u32; 3]>,
+    pub colors: Option<[u32; 3]>,
+    pub material: u32,
+}
+
+/// Indexed vertex buffers shared by every face of a [`TriangleMesh`].
+#[derive(Default)]
+pub struct MeshData {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<UnitVec3>,
+    pub uvs: Vec<(f32, f32)>,
+    pub colors: Vec<Color>,
+    pub materials: Vec<Arc<dyn Material>>,
+    pub faces: Vec<MeshFace>,
+}
+
+impl MeshData {
+    fn vertices(&self, face: &MeshFace) -> [&Point3; 3] {
+        face.positions.map(|i| &self.positions[i as usize])
+    }
+
+    /// The vertex `p0` with the edges `p1 - p0` and `p2 - p0`.
+    fn edges(&self, face: &MeshFace) -> (&Point3, Vec3, Vec3) {
+        let [p0, p1, p2] = self.vertices(face);
+        (p0, p1 - p0, p2 - p0)
+    }
+
+    fn face_area(&self, face: &MeshFace) -> f32 {
+        let (_, e1, e2) = self.edges(face);
+        0.5 * e1.cross(&e2).norm()
+    }
+
+    fn face_bbox(&self, face: &MeshFace) -> Aabb {
+        let [p0, p1, p2] = self.vertices(face);
+        Aabb::from_points(p0.min(p1).min(p2), p0.max(p1).max(p2))
+    }
+
+    fn check_face(&self, face: &MeshFace) {
+        let in_range = |indices: [u32; 3], len: usize| indices.iter().all(|&i| (i as usize) < len);
+
+        assert!(
+            in_range(face.positions, self.positions.len()),
+            "mesh face {:?} references a missing position",
+            face.positions
+        );
+        assert!(
+            face.normals.is_none_or(|n| in_range(n, self.normals.len())),
+            "mesh face {:?} references a missing normal",
+            face.normals
+        );
+        assert!(
+            face.uvs.is_none_or(|uv| in_range(uv, self.uvs.len())),
+            "mesh face {:?} references a missing texture coordinate",
+            face.uvs
+        );
+        assert!(
+            face.colors.is_none_or(|c| in_range(c, self.colors.len())),
+            "mesh face {:?} references a missing vertex colour",
+            face.colors
+        );
+        assert!(
+            (face.material as usize) < self.materials.len(),
+            "mesh face references missing material {}",
+            face.material
+        );
+    }
+}
+
+/// Closest intersection found while traversing the mesh BVH.
+struct FaceHit {
+    face: usize,
+    t: f32,
+    b1: f32,
+    b2: f32,
+}
+
+/// A node of the mesh BVH, stored flat. Interior nodes have `count == 0`: their left child
+/// follows them in the array and `start` is the index of the right child. Leaves cover
+/// `order[start..start + count]`.
+struct Node {
+    bbox: Aabb,
+    start: u32,
+    count: u32,
+}
+
+/// A polygonal surface stored as indexed buffers.
+///
+/// Positions, normals and texture coordinates live once in a shared [`MeshData`]; each face is
+/// just a few indices, and the mesh keeps its own flat BVH over them instead of one
+/// `Arc<dyn Primitive>` per triangle. Hits interpolate vertex normals for shading while
+/// `front_face` follows the geometric normal, and the mesh can be sampled by area as a light.
+pub struct TriangleMesh {
+    data: Arc<MeshData>,
+    nodes: Vec<Node>,
+    order: Vec<u32>,
+    /// Running sum of face areas, used to pick faces proportionally to their area.
+    cdf: Vec<f32>,
+    area: f32,
+}
+
+impl TriangleMesh {
+    /// Builds the mesh and its BVH. Degenerate (zero area) faces are dropped.
+    ///
+    /// # Panics
+    ///
+    /// If a face references a position, normal, texture coordinate, colour or material outside the
+    /// buffers of `data`.
+    pub fn new(mut data: MeshData) -> Self {
+        for face in &data.faces {
+            data.check_face(face);
+        }
+
+        let mut faces = std::mem::take(&mut data.faces);
+        // Only faces without any area go: however small, the others may be all a finely
+        // detailed or small-scale model is made of.
+        faces.retain(|face| {
+            let area = data.face_area(face);
+            area.is_finite() && area > 0.0
+        });
+        data.faces = faces;
+
+        let cdf: Vec<f32> = data
+            .faces
+            .iter()
+            .scan(0.0, |acc, face| {
+                *acc += data.face_area(face);
+                Some(*acc)
+            })
+            .collect();
+        let area = cdf.last().copied().unwrap_or(0.0);
+
+        let bboxes: Vec<Aabb> = data.faces.iter().map(|f| data.face_bbox(f)).collect();
+        let mut order: Vec<u32> = (0..data.faces.len() as u32).collect();
+        let mut nodes = Vec::with_capacity(2 * data.faces.len() / LEAF_SIZE + 1);
+
+        if !order.is_empty() {
+            build_recursive(&mut nodes, &mut order, 0, &bboxes);
+        }
+
+        Self {
+            data: Arc::new(data),
+            nodes,
+            order,
+            cdf,
+            area,
+        }
+    }
+
+    pub fn data(&self) -> &Arc<MeshData> {
+        &self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.faces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.faces.is_empty()
+    }
+
+    pub fn area(&self) -> f32 {
+        self.area
+    }
+
+    fn intersect(&self, ray: &Ray, ray_t: Interval) -> Option<FaceHit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut closest: Option<FaceHit> = None;
+        let mut closest_so_far = ray_t.max();
+        let mut stack = vec![0usize];
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let range = Interval::new(ray_t.min(), closest_so_far);
+            if node.bbox.hit(ray, range).is_none() {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.start as usize);
+                stack.push(index + 1);
+                continue;
+            }
+
+            let leaf = &self.order[node.start as usize..(node.start + node.count) as usize];
+            for &face in leaf {
+                let range = Interval::new(ray_t.min(), closest_so_far);
+                if let Some(hit) = self.intersect_face(face as usize, ray, range) {
+                    closest_so_far = hit.t;
+                    closest = Some(hit);
+                }
+            }
+        }
+
+        closest
+    }
+
+    /// Möller–Trumbore ray/triangle intersection.
+    fn intersect_face(&self, face: usize, ray: &Ray, ray_t: Interval) -> Option<FaceHit> {
+        let (p0, e1, e2) = self.data.edges(&self.data.faces[face]);
+
+        let pvec = ray.direction().cross(&e2);
+        let det = e1.dot(&pvec);
+
+        // Only rays exactly parallel to the face are rejected here: `det` scales with the size
+        // of the face, so any fixed threshold would also miss every face of a small mesh.
+        if det == 0.0 {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let tvec = ray.origin() - p0;
+
+        let b1 = tvec.dot(&pvec) * inv_det;
+        if !(0.0..=1.0).contains(&b1) {
+            return None;
+        }
+
+        let qvec = tvec.cross(&e1);
+        let b2 = ray.direction().dot(&qvec) * inv_det;
+        if b2 < 0.0 || b1 + b2 > 1.0 {
+            return None;
+        }
+
+        let t = e2.dot(&qvec) * inv_det;
+        if !ray_t.contains(t) {
+            return None;
+        }
+
+        Some(FaceHit { face, t, b1, b2 })
+    }
+
+    fn geometric_normal(&self, face: &MeshFace) -> UnitVec3 {
+        let (_, e1, e2) = self.data.edges(face);
+        e1.cross(&e2).unchecked_into_unit_vector()
+    }
+
+    /// Interpolated vertex normal, flipped if needed to lie on the side of the face normal.
+    fn shading_normal(&self, face: &MeshFace, geometric: &UnitVec3, b1: f32, b2: f32) -> UnitVec3 {
+        let Some(indices) = face.normals else {
+            return geometric.clone();
+        };
+
+        let [n0, n1, n2] = indices.map(|i| &self.data.normals[i as usize]);
+        let b0 = 1.0 - b1 - b2;
+        let n = (b0 * n0) + (b1 * n1) + (b2 * n2);
+
+        match n.try_into_unit() {
+            Ok(n) if n.dot(geometric) >= 0.0 => n,
+            Ok(n) => -n,
+            Err(_) => geometric.clone(),
+        }
+    }
+
+    fn uv(&self, face: &MeshFace, b1: f32, b2: f32) -> (f32, f32) {
+        let Some(indices) = face.uvs else {
+            return (b1, b2);
+        };
+
+        let [t0, t1, t2] = indices.map(|i| self.data.uvs[i as usize]);
+        let b0 = 1.0 - b1 - b2;
+        (
+            b0 * t0.0 + b1 * t1.0 + b2 * t2.0,
+            b0 * t0.1 + b1 * t1.1 + b2 * t2.1,
+        )
+    }
+
+    fn vertex_color(&self, face: &MeshFace, b1: f32, b2: f32) -> Option<Color> {
+        let [c0, c1, c2] = face.colors?.map(|i| &self.data.colors[i as usize]);
+        let b0 = 1.0 - b1 - b2;
+        Some((b0 * c0) + (b1 * c1) + (b2 * c2))
+    }
+}
+
+fn build_recursive(nodes: &mut Vec<Node>, order: &mut [u32], start: usize, bboxes: &[Aabb]) {
+    let bbox = order
+        .iter()
+        .fold(Aabb::EMPTY, |acc, &i| acc.merge(&bboxes[i as usize]));
+
+    let index = nodes.len();
+    nodes.push(Node {
+        bbox,
+        start: start as u32,
+        count: order.len() as u32,
+    });
+
+    if order.len() <= LEAF_SIZE {
+        return;
+    }
+
+    let axis = nodes[index].bbox.longest_axis();
+    order.sort_unstable_by(|&a, &b| {
+        let a = bboxes[a as usize].center()[axis];
+        let b = bboxes[b as usize].center()[axis];
+        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+    });
+
+    let mid = order.len() / 2;
+    let (left, right) = order.split_at_mut(mid);
+
+    build_recursive(nodes, left, start, bboxes);
+    let right_index = nodes.len();
+    build_recursive(nodes, right, start + mid, bboxes);
+
+    nodes[index].start = right_index as u32;
+    nodes[index].count = 0;
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let FaceHit { face, t, b1, b2 } = self.intersect(ray, ray_t)?;
+        let face = &self.data.faces[face];
+
+        let geometric = self.geometric_normal(face);
+        let shading = self.shading_normal(face, &geometric, b1, b2);
+        let material = self.data.materials[face.material as usize].clone();
+
+        let rec = HitRecord::with_shading_normal(ray, ray.at(t), &geometric, shading, t, material);
+
+        let rec = rec.set_uv(self.uv(face, b1, b2));
+
+        match self.vertex_color(face, b1, b2) {
+            Some(color) => Some(rec.set_vertex_color(color)),
+            None => Some(rec),
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        match self.nodes.first() {
+            Some(root) => &root.bbox,
+            None => &Aabb::EMPTY,
+        }
+    }
+}
+
+impl Primitive for TriangleMesh {
+    /// Solid angle density of sampling a point uniformly over the whole surface area.
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f32 {
+        let ray = Ray::new(origin.clone(), direction.clone());
+        let Some(hit) = self.intersect(&ray, Interval::CAMERA_VIEW) else {
+            return 0.0;
+        };
+
+        let normal = self.geometric_normal(&self.data.faces[hit.face]);
+        let distance_squared = hit.t.powi(2) * direction.len_squared();
+        let cosine = (direction.dot(&normal) / direction.norm()).abs();
+
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        match self.random_point() {
+            Some((point, _)) => point - origin,
+            None => UnitVec3::I.as_vec3(),
+        }
+    }
+
+    fn sample_surface(&self, _time: f32) -> Option<SurfaceSample> {
+        let (point, face) = self.random_point()?;
+
+        Some(SurfaceSample {
+            point,
+            normal: self.geometric_normal(&self.data.faces[face]),
+            pdf: 1.0 / self.area,
+        })
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        match self.intersect(ray, Interval::CAMERA_VIEW) {
+            Some(_) => 1.0 / self.area,
+            None => 0.0,
+        }
+    }
+}
+
+impl TriangleMesh {
+    /// A point uniformly distributed over the surface area, and the face it lies on.
+    fn random_point(&self) -> Option<(Point3, usize)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let target = random_float() * self.area;
+        let face = self
+            .cdf
+            .partition_point(|&acc| acc < target)
+            .min(self.cdf.len() - 1);
+
+        let [r1, r2] = random_float_2d();
+        let (b1, b2) = if r1 + r2 > 1.0 {
+            (1.0 - r1, 1.0 - r2)
+        } else {
+            (r1, r2)
+        };
+
+        let (p0, e1, e2) = self.data.edges(&self.data.faces[face]);
+        Some((p0 + (b1 * &e1) + (b2 * &e2), face))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::Empty;
+
+    /// Unit square in the z = 0 plane, split in two faces, with normals tilted towards +x.
+    fn square() -> TriangleMesh {
+        let material: Arc<dyn Material> = Arc::new(Empty);
+        let tilted = UnitVec3::try_new(1.0, 0.0, 1.0).unwrap();
+
+        TriangleMesh::new(MeshData {
+            positions: vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+                Point3::new(2.0, 0.0, 0.0),
+            ],
+            normals: vec![tilted],
+            uvs: vec![],
+            colors: vec![],
+            materials: vec![material],
+            faces: vec![
+                MeshFace {
+                    positions: [0, 1, 2],
+                    normals: Some([0, 0, 0]),
+                    uvs: None,
+                    colors: None,
+                    material: 0,
+                },
+                MeshFace {
+                    positions: [0, 2, 3],
+                    normals: None,
+                    uvs: None,
+                    colors: None,
+                    material: 0,
+                },
+                // Degenerate, dropped on construction.
+                MeshFace {
+                    positions: [0, 1, 4],
+                    normals: None,
+                    uvs: None,
+                    colors: None,
+                    material: 0,
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn drops_degenerate_faces() {
+        let mesh = square();
+
+        assert_eq!(mesh.len(), 2);
+        assert!((mesh.area() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn keeps_small_faces() {
+        let material: Arc<dyn Material> = Arc::new(Empty);
+        let face = MeshFace {
+            positions: [0, 1, 2],
+            normals: None,
+            uvs: None,
+            colors: None,
+            material: 0,
+        };
+        let mesh = TriangleMesh::new(MeshData {
+            positions: vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1e-4, 0.0, 0.0),
+                Point3::new(0.0, 1e-4, 0.0),
+            ],
+            normals: vec![],
+            uvs: vec![],
+            colors: vec![],
+            materials: vec![material],
+            faces: vec![face],
+        });
+
+        assert_eq!(mesh.len(), 1);
+        assert!((mesh.area() - 0.5e-8).abs() < 1e-12);
+
+        let ray = Ray::new(Point3::new(2e-5, 2e-5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, Interval::CAMERA_VIEW).unwrap();
+        assert!((rec.t() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn front_face_follows_geometric_normal() {
+        let mesh = square();
+
+        // Hits the smooth face from above: shading normal is tilted, front face is geometric.
+        let ray = Ray::new(Point3::new(0.75, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, Interval::CAMERA_VIEW).unwrap();
+        assert!(rec.front_face());
+        assert!(rec.normal().x() > 0.5);
+
+        // Same face from below: the shading normal flips with the record.
+        let ray = Ray::new(Point3::new(0.75, 0.25, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = mesh.hit(&ray, Interval::CAMERA_VIEW).unwrap();
+        assert!(!rec.front_face());
+        assert!(rec.normal().z() < 0.0);
+    }
+
+    #[test]
+    fn samples_points_on_the_surface() {
+        let mesh = square();
+        let origin = Point3::new(0.5, 0.5, 1.0);
+
+        for _ in 0..64 {
+            let direction = mesh.random(&origin);
+            let point = &origin + &direction;
+            assert!(point.z().abs() < 1e-5);
+            assert!((0.0..=1.0).contains(&point.x()) && (0.0..=1.0).contains(&point.y()));
+            assert!(mesh.pdf_value(&origin, &direction) > 0.0);
+        }
+    }
+}
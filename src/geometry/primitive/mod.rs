@@ -1,14 +1,37 @@
 use super::*;
 
+mod mesh;
 mod three_d;
 mod two_d;
 
-pub use self::{three_d::*, two_d::*};
+pub use self::{mesh::*, three_d::*, two_d::*};
 
 pub trait Primitive: Hittable {
     fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f32;
 
     fn random(&self, origin: &Point3) -> Vec3;
+
+    /// Samples a point on the surface as it is at `time`, e.g. to start a path on a light.
+    /// `None` for primitives that do not support area sampling.
+    fn sample_surface(&self, _time: f32) -> Option<SurfaceSample> {
+        None
+    }
+
+    /// Density per unit area with which [`Primitive::sample_surface`] picks the first point
+    /// that `ray` hits; 0 if it misses.
+    fn surface_pdf(&self, _ray: &Ray) -> f32 {
+        0.0
+    }
+}
+
+/// A point sampled on the surface of a [`Primitive`].
+#[derive(Debug, Clone)]
+pub struct SurfaceSample {
+    pub point: Point3,
+    /// Outward geometric normal at `point`.
+    pub normal: UnitVec3,
+    /// Density per unit area of having picked `point`.
+    pub pdf: f32,
 }
 
 pub trait PlaneShape: Hittable {
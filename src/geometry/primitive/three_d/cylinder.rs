@@ -152,24 +152,27 @@ impl Primitive for Cylinder {
         let r_area = random_float() * total_area;
 
         let sampled_point = if r_area < cap_area {
-            let theta = 2.0 * PI * random_float();
-            let r = self.radius * random_float().sqrt();
+            let [r1, r2] = random_float_2d();
+            let theta = 2.0 * PI * r1;
+            let r = self.radius * r2.sqrt();
             Point3::new(
                 self.center.x() + r * theta.cos(),
                 self.center.y() - self.height * 0.5,
                 self.center.z() + r * theta.sin(),
             )
         } else if r_area < 2.0 * cap_area {
-            let theta = 2.0 * PI * random_float();
-            let r = self.radius * random_float().sqrt();
+            let [r1, r2] = random_float_2d();
+            let theta = 2.0 * PI * r1;
+            let r = self.radius * r2.sqrt();
             Point3::new(
                 self.center.x() + r * theta.cos(),
                 self.center.y() + self.height * 0.5,
                 self.center.z() + r * theta.sin(),
             )
         } else {
-            let theta = 2.0 * PI * random_float();
-            let y = self.center.y() - self.height * 0.5 + random_float() * self.height;
+            let [r1, r2] = random_float_2d();
+            let theta = 2.0 * PI * r1;
+            let y = self.center.y() - self.height * 0.5 + r2 * self.height;
             Point3::new(
                 self.center.x() + self.radius * theta.cos(),
                 y,
@@ -127,8 +127,7 @@ impl Primitive for Sphere {
         let u = onb.u();
         let v = onb.v();
 
-        let r1 = random_float();
-        let r2 = random_float();
+        let [r1, r2] = random_float_2d();
 
         let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
         let z = 1.0 + r2 * (cos_theta_max - 1.0);
@@ -140,6 +139,25 @@ impl Primitive for Sphere {
 
         x * u + y * v + z * &w
     }
+
+    /// Uniform over the sphere where it is at `time`.
+    fn sample_surface(&self, time: f32) -> Option<SurfaceSample> {
+        let normal = UnitVec3::unchecked_random();
+        let point = self.center.at(time) + self.radius * normal.as_vec3();
+
+        Some(SurfaceSample {
+            point,
+            normal,
+            pdf: 1.0 / (4.0 * PI * self.radius * self.radius),
+        })
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        match self.hit(ray, Interval::CAMERA_VIEW) {
+            Some(_) => 1.0 / (4.0 * PI * self.radius * self.radius),
+            None => 0.0,
+        }
+    }
 }
 
 impl Hemisphere {
@@ -182,3 +200,32 @@ pub(super) fn get_sphere_uv(n: &UnitVec3) -> (f32, f32) {
     let v = theta / PI;
     (u, v)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::Empty;
+
+    #[test]
+    fn samples_moving_spheres_where_they_are() {
+        let sphere = Sphere::new_moving(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Empty),
+        );
+
+        for time in [0.0, 0.5, 1.0] {
+            let center = Point3::new(4.0 * time, 0.0, 0.0);
+            for _ in 0..16 {
+                let sample = sphere.sample_surface(time).unwrap();
+                assert!(((&sample.point - &center).len_squared() - 1.0).abs() < 1e-4);
+
+                // The sampled point is where rays at that time hit the sphere.
+                let from = &sample.point + &(2.0 * sample.normal.as_vec3());
+                let ray = Ray::new(from, -(2.0 * sample.normal.as_vec3())).set_time(time);
+                assert!(sphere.surface_pdf(&ray) > 0.0);
+            }
+        }
+    }
+}
@@ -58,8 +58,7 @@ impl Primitive for Disk {
     /// the underlying quad. The disk is inscribed in the square, so the center is at (0.5,0.5) and the
     /// radius is 0.5 in parameter space.
     fn random(&self, origin: &Point3) -> Vec3 {
-        let r1: f32 = random_float();
-        let r2: f32 = random_float();
+        let [r1, r2] = random_float_2d();
 
         let r = r1.sqrt();
         let theta = 2.0 * PI * r2;
@@ -77,4 +76,31 @@ impl Primitive for Disk {
 
         (point_on_disk - origin).unchecked_normalize().into_vec3()
     }
+
+    /// Uniform over the disk, mapped from the unit disk like [`random`](Self::random); the
+    /// disk covers π/4 of the underlying quad.
+    fn sample_surface(&self, _time: f32) -> Option<SurfaceSample> {
+        let [r1, r2] = random_float_2d();
+        let (r, theta) = (0.5 * r1.sqrt(), 2.0 * PI * r2);
+        let (alpha, beta) = (0.5 + r * theta.cos(), 0.5 + r * theta.sin());
+
+        Some(SurfaceSample {
+            point: self.0.q() + alpha * self.0.u() + beta * self.0.v(),
+            normal: self.0.u().cross(self.0.v()).unchecked_normalize(),
+            pdf: 1.0 / self.area(),
+        })
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        match self.hit(ray, Interval::CAMERA_VIEW) {
+            Some(_) => 1.0 / self.area(),
+            None => 0.0,
+        }
+    }
+}
+
+impl Disk {
+    fn area(&self) -> f32 {
+        self.0.area() * PI / 4.0
+    }
 }
@@ -9,3 +9,48 @@ mod disk;
 mod quad;
 mod trapezoid;
 mod triangle;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::Empty;
+
+    /// Samples `shape`, in the z = 0 plane within the unit square, checking that each point
+    /// lies on it, and returns the fraction in the lower half.
+    fn lower_half(shape: &dyn Primitive, area: f32) -> f32 {
+        let count = 4000;
+        let mut lower = 0;
+        for _ in 0..count {
+            let sample = shape.sample_surface(0.0).unwrap();
+            assert!((sample.pdf - 1.0 / area).abs() < 1e-4);
+            assert!(sample.point.z().abs() < 1e-6);
+
+            let from = &sample.point + &Vec3::new(0.0, 0.0, 1.0);
+            let ray = Ray::new(from, Vec3::new(0.0, 0.0, -1.0));
+            assert!(shape.surface_pdf(&ray) > 0.0, "{:?}", sample.point);
+            if sample.point.y() < 0.5 {
+                lower += 1;
+            }
+        }
+        lower as f32 / count as f32
+    }
+
+    #[test]
+    fn samples_disks_and_trapezoids_uniformly() {
+        let (q, u, v) = (
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+
+        let disk = Disk::new(q.clone(), u.clone(), v.clone(), Arc::new(Empty));
+        assert!((lower_half(&disk, PI / 4.0) - 0.5).abs() < 0.04);
+
+        // A triangle: three quarters of its area lie below half its height.
+        let triangle = Trapezoid::new(q.clone(), u.clone(), v.clone(), 0.0, Arc::new(Empty));
+        assert!((lower_half(&triangle, 0.5) - 0.75).abs() < 0.04);
+
+        let trapezoid = Trapezoid::new(q, u, v, 0.5, Arc::new(Empty));
+        assert!((lower_half(&trapezoid, 0.75) - 7.0 / 12.0).abs() < 0.04);
+    }
+}
@@ -137,10 +137,27 @@ impl Primitive for Quad {
     }
 
     fn random(&self, origin: &Point3) -> Vec3 {
-        let r1 = random_float();
-        let r2 = random_float();
+        let [r1, r2] = random_float_2d();
         let p = &self.q + (r1 * &self.u) + (r2 * &self.v);
 
         p - origin
     }
+
+    fn sample_surface(&self, _time: f32) -> Option<SurfaceSample> {
+        let [r1, r2] = random_float_2d();
+        let point = &self.q + (r1 * &self.u) + (r2 * &self.v);
+
+        Some(SurfaceSample {
+            point,
+            normal: self.normal.clone(),
+            pdf: 1.0 / self.area,
+        })
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        match self.hit(ray, Interval::CAMERA_VIEW) {
+            Some(_) => 1.0 / self.area,
+            None => 0.0,
+        }
+    }
 }
@@ -77,12 +77,42 @@ impl Primitive for Trapezoid {
         let u = self.quad.u();
         let v = self.quad.v();
 
-        let beta = random_float(); // Must return f32 in [0, 1]
+        let [beta, r] = random_float_2d(); // Must return f32 in [0, 1]
         let alpha_min = beta * 0.5 * (1.0 - self.ratio);
         let alpha_max = 1.0 - beta * 0.5 * (1.0 - self.ratio);
-        let alpha = alpha_min + (alpha_max - alpha_min) * random_float();
+        let alpha = alpha_min + (alpha_max - alpha_min) * r;
 
         let point = q + alpha * u + beta * v;
         (point - origin).unchecked_into_unit().into_vec3()
     }
+
+    /// Uniform over the trapezoid: unlike [`random`](Self::random), rows are picked in
+    /// proportion to their width, `1 - k β` with `k = 1 - ratio`, by inverting the integral of
+    /// the width.
+    fn sample_surface(&self, _time: f32) -> Option<SurfaceSample> {
+        let k = 1.0 - self.ratio;
+        let [xi, r] = random_float_2d();
+        let beta = xi * (2.0 - k) / (1.0 + (1.0 - k * xi * (2.0 - k)).max(0.0).sqrt());
+        let alpha_min = beta * 0.5 * k;
+        let alpha = alpha_min + (1.0 - 2.0 * alpha_min) * r;
+
+        Some(SurfaceSample {
+            point: self.quad.q() + alpha * self.quad.u() + beta * self.quad.v(),
+            normal: self.quad.u().cross(self.quad.v()).unchecked_normalize(),
+            pdf: 1.0 / self.area(),
+        })
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        match self.hit(ray, Interval::CAMERA_VIEW) {
+            Some(_) => 1.0 / self.area(),
+            None => 0.0,
+        }
+    }
+}
+
+impl Trapezoid {
+    fn area(&self) -> f32 {
+        self.quad.area() * (1.0 + self.ratio) / 2.0
+    }
 }
@@ -48,12 +48,36 @@ impl Primitive for Triangle {
     /// 3. Compute the point on the triangle as: `p + u * alpha + v * beta`.
     /// 4. Return the normalized direction from the `origin` to this point.
     fn random(&self, origin: &Point3) -> Vec3 {
+        (self.random_point() - origin)
+            .unchecked_normalize()
+            .into_vec3()
+    }
+
+    fn sample_surface(&self, _time: f32) -> Option<SurfaceSample> {
+        let normal = self.0.u().cross(self.0.v()).unchecked_normalize();
+
+        Some(SurfaceSample {
+            point: self.random_point(),
+            normal,
+            pdf: 2.0 / self.0.area(),
+        })
+    }
+
+    fn surface_pdf(&self, ray: &Ray) -> f32 {
+        match self.hit(ray, Interval::CAMERA_VIEW) {
+            Some(_) => 2.0 / self.0.area(),
+            None => 0.0,
+        }
+    }
+}
+
+impl Triangle {
+    fn random_point(&self) -> Point3 {
         let p = self.0.q();
         let u = self.0.u();
         let v = self.0.v();
 
-        let r1: f32 = random_float();
-        let r2: f32 = random_float();
+        let [r1, r2] = random_float_2d();
 
         let (alpha, beta) = if r1 + r2 > 1.0 {
             (1.0 - r1, 1.0 - r2)
@@ -61,9 +85,6 @@ impl Primitive for Triangle {
             (r1, r2)
         };
 
-        let point_on_triangle = p + alpha * u + beta * v;
-        (point_on_triangle - origin)
-            .unchecked_normalize()
-            .into_vec3()
+        p + alpha * u + beta * v
     }
 }
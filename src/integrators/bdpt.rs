@@ -0,0 +1,431 @@
+use super::{
+    light::{is_black, is_finite, sample_emitter},
+    *,
+};
+
+/// Relative slack on the distance between two vertices when testing their mutual visibility.
+const SHADOW_EPSILON: f32 = 1e-3;
+
+/// Bidirectional path tracer after Veach, in the formulation of _pbrt_.
+///
+/// For every camera sample a path is traced from the camera and another one from a point sampled
+/// on the lights with [`Primitive::sample_surface`]. Every vertex of the first is then joined to
+/// every vertex of the second with a shadow ray, and the resulting estimates of each path length
+/// are combined with the power heuristic, so each one is weighted by how likely the other
+/// strategies were to produce the same path.
+///
+/// The strategies that join a light subpath directly to the camera are left out, since they
+/// would splat their contribution onto other pixels; caustics seen directly through specular
+/// surfaces are therefore only found by the camera subpaths. Lights without surface sampling,
+/// and emitters missing from the lights list, are picked up by the camera subpaths alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BidirectionalPathTracer;
+
+impl BidirectionalPathTracer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Integrator for BidirectionalPathTracer {
+    fn radiance(&self, ray: &Ray, ctx: &RenderContext) -> Color {
+        self.light_paths(ray, ctx).total()
+    }
+
+    fn light_paths(&self, ray: &Ray, ctx: &RenderContext) -> LightPaths {
+        let tracer = Subpaths {
+            ctx,
+            time: ray.time(),
+        };
+        let max_vertices = ctx.max_depth as usize + 1;
+
+        let mut camera = vec![Vertex {
+            point: ray.origin().clone(),
+            normal: None,
+            kind: Kind::Camera,
+            beta: Color::new(1.0, 1.0, 1.0),
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }];
+        let escaped = tracer.random_walk(
+            ray.clone(),
+            Color::new(1.0, 1.0, 1.0),
+            0.0,
+            max_vertices,
+            &mut camera,
+        );
+        // Every vertex after the camera's is a bounce, and the path of `s` light and `t`
+        // camera vertices bounces at all but its ends.
+        let mut radiance = LightPaths::new();
+        radiance.add(camera.len() - 1, escaped);
+
+        let mut light = Vec::new();
+        if let Some(origin) = tracer.light_origin() {
+            light.push(origin);
+            if let Some((ray, pdf)) = tracer.emit(&light[0]) {
+                // Cosine-weighted emission leaves the radiance scaled by cosine / pdf = pi.
+                let beta = PI * &light[0].beta;
+                tracer.random_walk(ray, beta, pdf, max_vertices - 1, &mut light);
+            }
+        }
+
+        for t in 2..=camera.len() {
+            for s in 0..=light.len().min(max_vertices - t) {
+                let contribution = tracer.connect(&camera, &light, s, t);
+                if is_finite(&contribution) {
+                    radiance.add(s + t - 2, contribution);
+                }
+            }
+        }
+
+        if !is_finite(&radiance.total()) {
+            return LightPaths::new();
+        }
+        radiance
+    }
+}
+
+/// A vertex of a camera or light subpath.
+struct Vertex {
+    point: Point3,
+    /// Geometric normal; `None` for the camera and for scattering inside media.
+    normal: Option<UnitVec3>,
+    kind: Kind,
+    /// Throughput of the subpath arriving at this vertex, before it scatters.
+    beta: Color,
+    /// Whether the vertex scattered specularly, and so cannot be connected to.
+    delta: bool,
+    /// Area density of this vertex when sampled along its own subpath.
+    pdf_fwd: f32,
+    /// Area density of this vertex when sampled from the other end of the path.
+    pdf_rev: f32,
+}
+
+enum Kind {
+    Camera,
+    Light,
+    Surface(HitRecord),
+}
+
+/// Traces and joins the subpaths of one camera sample.
+struct Subpaths<'a, 'b> {
+    ctx: &'a RenderContext<'b>,
+    time: f32,
+}
+
+impl Subpaths<'_, '_> {
+    /// Samples a point on the lights, with the emission of its emitting side.
+    fn light_origin(&self) -> Option<Vertex> {
+        let emitter = sample_emitter(self.ctx, self.time)?;
+        Some(Vertex {
+            point: emitter.point,
+            normal: Some(emitter.normal),
+            beta: (1.0 / emitter.pdf) * &emitter.emitted,
+            kind: Kind::Light,
+            delta: false,
+            pdf_fwd: emitter.pdf,
+            pdf_rev: 0.0,
+        })
+    }
+
+    /// Samples a cosine-weighted direction leaving a light vertex, with its solid angle density.
+    fn emit(&self, light: &Vertex) -> Option<(Ray, f32)> {
+        let normal = light.normal.clone()?;
+        let cosine = pdf::Cosine::new(normal);
+        let direction = cosine.generate();
+        let pdf = cosine.value(direction.clone());
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some((
+            Ray::new(light.point.clone(), direction).set_time(self.time),
+            pdf,
+        ))
+    }
+
+    /// Extends `path` by following `ray` until it escapes, is absorbed or holds `max_vertices`.
+    ///
+    /// `beta` is the throughput carried along `ray` and `pdf` the solid angle density with which
+    /// it was sampled. Returns the background radiance carried by an escaping path.
+    fn random_walk(
+        &self,
+        mut ray: Ray,
+        mut beta: Color,
+        mut pdf: f32,
+        max_vertices: usize,
+        path: &mut Vec<Vertex>,
+    ) -> Color {
+        while path.len() < max_vertices {
+            let prev = path.last().unwrap();
+
+            let rec = match self.ctx.world.hit(&ray, Interval::CAMERA_VIEW) {
+                Some(rec) => rec,
+                None => return beta.mul(self.ctx.background),
+            };
+            let scatter = rec.material().scatter(&ray, &rec);
+
+            let mut vertex = Vertex {
+                point: rec.point().clone(),
+                normal: rec.on_surface().then(|| rec.normal().clone()),
+                kind: Kind::Surface(rec),
+                beta: beta.clone(),
+                delta: false,
+                pdf_fwd: 0.0,
+                pdf_rev: 0.0,
+            };
+            vertex.pdf_fwd = to_area(pdf, prev, &vertex);
+            path.push(vertex);
+
+            let Some(srec) = scatter else {
+                break;
+            };
+            let n = path.len();
+            let Kind::Surface(rec) = &path[n - 1].kind else {
+                unreachable!()
+            };
+
+            if let Some(spec) = srec.specular() {
+                beta = beta.mul(srec.attenuation());
+                ray = spec.clone();
+                path[n - 1].delta = true;
+                path[n - 2].pdf_rev = 0.0;
+                pdf = 0.0;
+                continue;
+            }
+
+            let bsdf = srec.pdf().unwrap();
+            let scattered = Ray::new(rec.point().clone(), bsdf.generate()).set_time(ray.time());
+            let pdf_val = bsdf.value(scattered.direction().clone());
+            let scattering_pdf = rec.material().scattering_pdf(&ray, rec, &scattered);
+
+            let weight = scattering_pdf / pdf_val;
+            if pdf_val.abs() < EPSILON || !weight.is_finite() {
+                break;
+            }
+
+            // Density of scattering back towards the previous vertex, as the other subpath
+            // would if it arrived along `scattered`.
+            let reverse_in = Ray::new(rec.point() + scattered.direction(), -scattered.direction());
+            let towards_prev = Ray::new(rec.point().clone(), &path[n - 2].point - rec.point());
+            let pdf_rev = rec
+                .material()
+                .scattering_pdf(&reverse_in, rec, &towards_prev);
+
+            beta = weight * beta.mul(srec.attenuation());
+            path[n - 2].pdf_rev = to_area(pdf_rev, &path[n - 1], &path[n - 2]);
+            ray = scattered;
+            pdf = scattering_pdf;
+        }
+
+        BLACK
+    }
+
+    /// Contribution of the path made of the first `s` light and `t` camera vertices.
+    fn connect(&self, camera: &[Vertex], light: &[Vertex], s: usize, t: usize) -> Color {
+        let pt = &camera[t - 1];
+        let pt_minus = &camera[t - 2];
+
+        let unweighted = if s == 0 {
+            match &pt.kind {
+                Kind::Surface(rec) => pt.beta.mul(&rec.material().emitted(rec)),
+                _ => BLACK,
+            }
+        } else {
+            let qs = &light[s - 1];
+            if pt.delta || qs.delta {
+                return BLACK;
+            }
+
+            let to_qs = &qs.point - &pt.point;
+            let distance_squared = to_qs.len_squared();
+            let f_pt = eval(pt, &pt_minus.point, &qs.point);
+            let f_qs = match &qs.kind {
+                Kind::Light => {
+                    // The emission itself is part of the throughput of the light vertex.
+                    let cosine = qs
+                        .normal
+                        .as_ref()
+                        .map_or(0.0, |n| -to_qs.dot(n) / distance_squared.sqrt());
+                    if cosine <= 0.0 {
+                        return BLACK;
+                    }
+                    Color::new(cosine, cosine, cosine)
+                }
+                _ => eval(qs, &light[s - 2].point, &pt.point),
+            };
+
+            let contribution =
+                (1.0 / distance_squared) * pt.beta.mul(&f_pt).mul(&f_qs).mul(&qs.beta);
+            if is_black(&contribution) {
+                return BLACK;
+            }
+            self.transmittance(pt, qs) * contribution
+        };
+
+        if is_black(&unweighted) {
+            return BLACK;
+        }
+        self.mis_weight(camera, light, s, t) * unweighted
+    }
+
+    /// Fraction of the light let through along the segment between two vertices: 0 if
+    /// something opaque is in the way, less than 1 across participating media.
+    fn transmittance(&self, a: &Vertex, b: &Vertex) -> f32 {
+        let offset = &b.point - &a.point;
+        let distance = offset.norm();
+        let ray = Ray::new(a.point.clone(), (1.0 / distance) * offset).set_time(self.time);
+        let segment = Interval::new(
+            Interval::CAMERA_VIEW.min(),
+            distance * (1.0 - SHADOW_EPSILON),
+        );
+        self.ctx.world.transmittance(&ray, segment)
+    }
+
+    /// Area density of sampling `next` from `v`, reached from `prev`.
+    fn pdf(&self, v: &Vertex, prev: Option<&Vertex>, next: &Vertex) -> f32 {
+        match &v.kind {
+            Kind::Camera => 0.0,
+            Kind::Light => pdf_emission(v, next),
+            Kind::Surface(rec) => {
+                let Some(prev) = prev else {
+                    return 0.0;
+                };
+                let r_in = Ray::new(prev.point.clone(), &v.point - &prev.point);
+                let scattered = Ray::new(v.point.clone(), &next.point - &v.point);
+                to_area(
+                    rec.material().scattering_pdf(&r_in, rec, &scattered),
+                    v,
+                    next,
+                )
+            }
+        }
+    }
+
+    /// Area density with which the light subpath would start at `v`, seen from `from`.
+    fn pdf_light_origin(&self, from: &Vertex, v: &Vertex) -> f32 {
+        let ray = Ray::new(from.point.clone(), &v.point - &from.point).set_time(self.time);
+        match self.ctx.lights.hit(&ray, Interval::CAMERA_VIEW) {
+            Some(rec) if (rec.t() - 1.0).abs() <= SHADOW_EPSILON => {
+                self.ctx.lights.surface_pdf(&ray)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Power heuristic weight of the strategy with `s` light and `t` camera vertices against
+    /// every other strategy that could have produced the same path.
+    fn mis_weight(&self, camera: &[Vertex], light: &[Vertex], s: usize, t: usize) -> f32 {
+        if s + t == 2 {
+            return 1.0;
+        }
+
+        // `(pdf_fwd, pdf_rev, delta)` of each vertex, as seen by this strategy.
+        let mut cam: Vec<_> = camera[..t]
+            .iter()
+            .map(|v| (v.pdf_fwd, v.pdf_rev, v.delta))
+            .collect();
+        let mut lig: Vec<_> = light[..s]
+            .iter()
+            .map(|v| (v.pdf_fwd, v.pdf_rev, v.delta))
+            .collect();
+
+        let pt = &camera[t - 1];
+        let pt_minus = &camera[t - 2];
+        if s > 0 {
+            let qs = &light[s - 1];
+            let qs_minus = s.checked_sub(2).map(|i| &light[i]);
+
+            cam[t - 1].1 = self.pdf(qs, qs_minus, pt);
+            cam[t - 2].1 = self.pdf(pt, Some(qs), pt_minus);
+            lig[s - 1].1 = self.pdf(pt, Some(pt_minus), qs);
+            if let Some(qs_minus) = qs_minus {
+                lig[s - 2].1 = self.pdf(qs, Some(pt), qs_minus);
+            }
+            lig[s - 1].2 = false;
+        } else {
+            cam[t - 1].1 = self.pdf_light_origin(pt_minus, pt);
+            cam[t - 2].1 = pdf_emission(pt, pt_minus);
+        }
+        cam[t - 1].2 = false;
+
+        let mut sum = 0.0;
+
+        // A zero reverse density means the other subpath cannot reach the vertex, and so neither
+        // this nor any later strategy, unless it comes from scattering off a specular neighbour,
+        // whose density cancels out like the remapped ones.
+        let mut ratio = 1.0;
+        for i in (2..t).rev() {
+            let (fwd, rev, delta) = cam[i];
+            if rev == 0.0 && !delta && !(i + 1 < t && cam[i + 1].2) {
+                break;
+            }
+            ratio *= remap0(rev) / remap0(fwd);
+            if !delta && !cam[i - 1].2 {
+                sum += ratio * ratio;
+            }
+        }
+
+        let mut ratio = 1.0;
+        for i in (0..s).rev() {
+            let (fwd, rev, delta) = lig[i];
+            if rev == 0.0 && !delta && !(i + 1 < s && lig[i + 1].2) {
+                break;
+            }
+            ratio *= remap0(rev) / remap0(fwd);
+            if !(delta || i > 0 && lig[i - 1].2) {
+                sum += ratio * ratio;
+            }
+        }
+
+        1.0 / (1.0 + sum)
+    }
+}
+
+/// BSDF times cosine at a surface vertex, for light arriving from `prev` and leaving to `next`.
+fn eval(v: &Vertex, prev: &Point3, next: &Point3) -> Color {
+    match &v.kind {
+        Kind::Surface(rec) => {
+            let r_in = Ray::new(prev.clone(), &v.point - prev);
+            let scattered = Ray::new(v.point.clone(), next - &v.point);
+            rec.material().eval(&r_in, rec, &scattered)
+        }
+        _ => BLACK,
+    }
+}
+
+/// Area density of the cosine-weighted emission from `v` landing on `to`.
+fn pdf_emission(v: &Vertex, to: &Vertex) -> f32 {
+    let Some(normal) = &v.normal else {
+        return 0.0;
+    };
+    let w = &to.point - &v.point;
+    let cosine = w.dot(normal) / w.norm();
+    if cosine.is_nan() || cosine <= 0.0 {
+        return 0.0;
+    }
+    to_area(cosine / PI, v, to)
+}
+
+/// Converts a solid angle density at `from` into an area density at `to`.
+fn to_area(pdf: f32, from: &Vertex, to: &Vertex) -> f32 {
+    let w = &to.point - &from.point;
+    let distance_squared = w.len_squared();
+    if distance_squared == 0.0 {
+        return 0.0;
+    }
+    let cosine = to
+        .normal
+        .as_ref()
+        .map_or(1.0, |n| (w.dot(n) / distance_squared.sqrt()).abs());
+    pdf * cosine / distance_squared
+}
+
+fn remap0(pdf: f32) -> f32 {
+    if pdf == 0.0 {
+        1.0
+    } else {
+        pdf
+    }
+}
@@ -0,0 +1,124 @@
+use super::*;
+
+/// Rays cast per camera sample to estimate ambient occlusion.
+const AO_RAYS: usize = 16;
+
+/// Default ambient occlusion radius, as a fraction of the diagonal of the scene's bounding box.
+const DEFAULT_AO_FRACTION: f32 = 0.1;
+
+/// Participating media stepped through before giving up on finding a surface.
+const MAX_MEDIA_STEPS: usize = 16;
+
+/// What a [`DebugIntegrator`] shows of the first surface seen along each camera ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugView {
+    /// Outward shading normal, mapped from `[-1, 1]` to `[0, 1]` per axis.
+    ShadingNormal,
+    /// Outward normal of the geometry itself, ignoring interpolated vertex normals.
+    GeometricNormal,
+    /// Distance from the camera as grey, black up close and white at `max_distance` (the size
+    /// of the scene when `None`).
+    Depth { max_distance: Option<f32> },
+    /// Surface coordinates, `u` in red and `v` in green.
+    Uv,
+    /// Reflectance of the material, from [`Material::albedo`](crate::materials::Material::albedo).
+    Albedo,
+    /// Green where the ray hits the front of a surface, red where it hits the back.
+    FrontFace,
+    /// Share of directions over the hemisphere that are not blocked within `radius` (a tenth
+    /// of the size of the scene when `None`), weighted by cosine.
+    AmbientOcclusion { radius: Option<f32> },
+}
+
+/// Shows one property of the geometry seen by each camera ray, with no light transport, to
+/// check a scene quickly. A single sample per pixel is usually enough.
+///
+/// Participating media are looked through, so the surfaces inside and behind them show.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugIntegrator {
+    view: DebugView,
+}
+
+impl DebugIntegrator {
+    pub fn new(view: DebugView) -> Self {
+        Self { view }
+    }
+
+    pub fn view(&self) -> DebugView {
+        self.view
+    }
+}
+
+impl Integrator for DebugIntegrator {
+    fn radiance(&self, ray: &Ray, ctx: &RenderContext) -> Color {
+        let Some(rec) = first_surface(ray, ctx.world) else {
+            return BLACK;
+        };
+        let outward = |normal: &UnitVec3| {
+            let normal = if rec.front_face() {
+                normal.as_vec3()
+            } else {
+                (-normal).into_vec3()
+            };
+            0.5 * (normal + Vec3::new(1.0, 1.0, 1.0))
+        };
+
+        match self.view {
+            DebugView::ShadingNormal => outward(rec.normal()),
+            DebugView::GeometricNormal => outward(rec.geometric_normal()),
+            DebugView::Depth { max_distance } => {
+                let max_distance = max_distance
+                    .or_else(|| scene_diagonal(ctx.world))
+                    .unwrap_or(1.0);
+                let depth = (rec.t() * ray.direction().norm() / max_distance).clamp(0.0, 1.0);
+                Color::new(depth, depth, depth)
+            }
+            DebugView::Uv => {
+                let (u, v) = rec.get_uv();
+                Color::new(u, v, 0.0)
+            }
+            DebugView::Albedo => rec.material().albedo(&rec),
+            DebugView::FrontFace => {
+                if rec.front_face() {
+                    Color::new(0.0, 1.0, 0.0)
+                } else {
+                    Color::new(1.0, 0.0, 0.0)
+                }
+            }
+            DebugView::AmbientOcclusion { radius } => {
+                let radius = radius
+                    .or_else(|| scene_diagonal(ctx.world).map(|d| DEFAULT_AO_FRACTION * d))
+                    .unwrap_or(1.0);
+                let ao = ambient_occlusion(ctx.world, ray, &rec, radius);
+                Color::new(ao, ao, ao)
+            }
+        }
+    }
+}
+
+/// The first hit along `ray` that lies on a surface, stepping through participating media.
+pub(crate) fn first_surface(ray: &Ray, world: &dyn Hittable) -> Option<HitRecord> {
+    let mut t_min = Interval::CAMERA_VIEW.min();
+    for _ in 0..MAX_MEDIA_STEPS {
+        let rec = world.hit(ray, Interval::new(t_min, f32::INFINITY))?;
+        if rec.on_surface() {
+            return Some(rec);
+        }
+        t_min = rec.t();
+    }
+    None
+}
+
+/// Cosine-weighted share of the hemisphere around `rec` left open within `radius`.
+fn ambient_occlusion(world: &dyn Hittable, ray: &Ray, rec: &HitRecord, radius: f32) -> f32 {
+    let hemisphere = pdf::Cosine::new(rec.normal().clone());
+    let reach = Interval::new(Interval::CAMERA_VIEW.min(), radius);
+    let unblocked: f32 = (0..AO_RAYS)
+        .map(|_| {
+            let direction = hemisphere.generate().unchecked_normalize().into_vec3();
+            let probe = Ray::new(rec.point().clone(), direction).set_time(ray.time());
+            world.transmittance(&probe, reach.clone())
+        })
+        .sum();
+    unblocked / AO_RAYS as f32
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+/// Relative slack on the light distance when matching a surface hit against the lights.
+const SHADOW_EPSILON: f32 = 1e-3;
+
+/// Distance, relative to the magnitude of the point, from which a sampled light point is looked
+/// at to find out which of its sides emits.
+const PROBE_DISTANCE: f32 = 1e-5;
+
+/// Emission reaching a surface point from a point sampled on the lights.
+pub(crate) struct LightSample {
+    /// Shadow ray from the surface point towards the light.
+    pub ray: Ray,
+    /// Solid angle density of the direction under `lights.random`.
+    pub pdf: f32,
+    pub emitted: Color,
+}
+
+/// Samples a point on `ctx.lights` with [`Primitive::random`] and casts a shadow ray to it.
+///
+/// `None` when there are no lights or something blocks the way. The emission is read from the
+/// world rather than the lights list, whose copies may carry a placeholder material, and is
+/// attenuated by any participating media along the shadow ray.
+pub(crate) fn sample_light(ctx: &RenderContext, point: &Point3, time: f32) -> Option<LightSample> {
+    let direction = ctx.lights.random(point);
+    let pdf = ctx.lights.pdf_value(point, &direction);
+    if !(pdf > 0.0 && pdf.is_finite()) {
+        return None;
+    }
+
+    let ray = Ray::new(point.clone(), direction).set_time(time);
+    let target = ctx.lights.hit(&ray, Interval::CAMERA_VIEW)?.t();
+
+    let near = target * (1.0 - SHADOW_EPSILON);
+    let transmittance = ctx
+        .world
+        .transmittance(&ray, Interval::new(Interval::CAMERA_VIEW.min(), near));
+    if transmittance <= 0.0 {
+        return None;
+    }
+
+    let rec = ctx
+        .world
+        .hit(&ray, Interval::new(near, target * (1.0 + SHADOW_EPSILON)))?;
+
+    let emitted = transmittance * rec.material().emitted(&rec);
+    Some(LightSample { ray, pdf, emitted })
+}
+
+/// A point sampled on the surface of the lights, for paths that start there.
+pub(crate) struct EmitterSample {
+    pub point: Point3,
+    /// Normal on the emitting side.
+    pub normal: UnitVec3,
+    /// Area density of the point.
+    pub pdf: f32,
+    pub emitted: Color,
+}
+
+/// Samples a point on `ctx.lights` with [`Primitive::sample_surface`].
+///
+/// `None` when the lights cannot be sampled by area or the point does not emit. As with
+/// [`sample_light`], the emission is read from the world, looking at the point from either side.
+pub(crate) fn sample_emitter(ctx: &RenderContext, time: f32) -> Option<EmitterSample> {
+    let sample = ctx.lights.sample_surface(time)?;
+    if !(sample.pdf > 0.0 && sample.pdf.is_finite()) {
+        return None;
+    }
+
+    let magnitude = sample
+        .point
+        .x()
+        .abs()
+        .max(sample.point.y().abs())
+        .max(sample.point.z().abs());
+    let reach = PROBE_DISTANCE * (1.0 + magnitude);
+    for normal in [sample.normal.clone(), -&sample.normal] {
+        let origin = &sample.point + &(reach * &normal);
+        let probe = Ray::new(origin, -(reach * &normal)).set_time(time);
+        let Some(rec) = ctx.world.hit(&probe, Interval::new(0.5, 2.0)) else {
+            continue;
+        };
+
+        let emitted = rec.material().emitted(&rec);
+        if !is_black(&emitted) {
+            return Some(EmitterSample {
+                point: sample.point,
+                normal,
+                pdf: sample.pdf,
+                emitted,
+            });
+        }
+    }
+    None
+}
+
+/// Density with which [`sample_light`] from the origin of `ray` would have produced the hit
+/// `rec`, or 0 if `rec` is not on one of the lights.
+pub(crate) fn light_pdf(ctx: &RenderContext, ray: &Ray, rec: &HitRecord) -> f32 {
+    match ctx.lights.hit(ray, Interval::CAMERA_VIEW) {
+        Some(light) if (light.t() - rec.t()).abs() <= SHADOW_EPSILON * rec.t() => {
+            ctx.lights.pdf_value(ray.origin(), ray.direction())
+        }
+        _ => 0.0,
+    }
+}
+
+/// Whether every channel of `color` is finite, to drop NaN and infinite samples.
+pub(crate) fn is_finite(color: &Color) -> bool {
+    color.x().is_finite() && color.y().is_finite() && color.z().is_finite()
+}
+
+pub(crate) fn is_black(color: &Color) -> bool {
+    color.x() == 0.0 && color.y() == 0.0 && color.z() == 0.0
+}
@@ -0,0 +1,127 @@
+use super::{
+    light::{is_finite, light_pdf, sample_light},
+    path::russian_roulette,
+    *,
+};
+
+/// Path tracer combining light and BSDF sampling with multiple importance sampling.
+///
+/// At every non-specular vertex a shadow ray is cast to a point on `lights` and one direction
+/// is drawn from the BSDF.
+/// Emission reached by either is weighted with the power heuristic, so each light is counted
+/// once and by whichever strategy samples it best: the lights for small or distant emitters,
+/// the BSDF for large emitters and glossy lobes. The BSDF direction also continues the path.
+#[derive(Debug, Clone, Copy)]
+pub struct MisPathTracer {
+    roulette_depth: u16,
+}
+
+impl MisPathTracer {
+    pub fn new() -> Self {
+        Self::with_roulette_depth(DEFAULT_ROULETTE_DEPTH)
+    }
+
+    /// Russian roulette starts after `depth` bounces; `u16::MAX` disables it.
+    pub fn with_roulette_depth(depth: u16) -> Self {
+        Self {
+            roulette_depth: depth,
+        }
+    }
+}
+
+impl Default for MisPathTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How the current ray was sampled, to weight the emission it finds.
+enum Sampled {
+    /// Camera rays and specular bounces, which light sampling cannot produce.
+    Deterministic,
+    /// A BSDF sample with solid angle density `pdf`.
+    Bsdf { pdf: f32 },
+}
+
+impl Integrator for MisPathTracer {
+    fn radiance(&self, ray: &Ray, ctx: &RenderContext) -> Color {
+        self.light_paths(ray, ctx).total()
+    }
+
+    fn light_paths(&self, ray: &Ray, ctx: &RenderContext) -> LightPaths {
+        let mut radiance = LightPaths::new();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray.clone();
+        let mut sampled = Sampled::Deterministic;
+
+        for depth in 0..ctx.max_depth {
+            let rec = match ctx.world.hit(&ray, Interval::CAMERA_VIEW) {
+                Some(rec) => rec,
+                None => {
+                    radiance.add(depth as usize, throughput.mul(ctx.background));
+                    break;
+                }
+            };
+
+            let emitted = rec.material().emitted(&rec);
+            let weight = match &sampled {
+                Sampled::Deterministic => 1.0,
+                Sampled::Bsdf { pdf } => {
+                    pdf::power_heuristic(1.0, *pdf, 1.0, light_pdf(ctx, &ray, &rec))
+                }
+            };
+            radiance.add(depth as usize, weight * throughput.mul(&emitted));
+
+            let srec = match rec.material().scatter(&ray, &rec) {
+                Some(srec) => srec,
+                None => break,
+            };
+
+            if let Some(spec) = srec.specular() {
+                throughput = throughput.mul(srec.attenuation());
+                ray = spec.clone();
+                sampled = Sampled::Deterministic;
+            } else {
+                let bsdf_pdf = srec.pdf().unwrap();
+
+                // A shadow ray extends the path by one segment.
+                if depth + 1 < ctx.max_depth {
+                    if let Some(light) = sample_light(ctx, rec.point(), ray.time()) {
+                        let f = rec.material().scattering_pdf(&ray, &rec, &light.ray);
+                        let bsdf_value = bsdf_pdf.value(light.ray.direction().clone());
+                        let weight = pdf::power_heuristic(1.0, light.pdf, 1.0, bsdf_value);
+                        let contribution = (weight * f / light.pdf)
+                            * throughput.mul(srec.attenuation()).mul(&light.emitted);
+
+                        if is_finite(&contribution) {
+                            radiance.add(depth as usize + 1, contribution);
+                        }
+                    }
+                }
+
+                let scattered =
+                    Ray::new(rec.point().clone(), bsdf_pdf.generate()).set_time(ray.time());
+                let pdf_val = bsdf_pdf.value(scattered.direction().clone());
+                let scattering_pdf = rec.material().scattering_pdf(&ray, &rec, &scattered);
+
+                let weight = scattering_pdf / pdf_val;
+                if pdf_val.abs() < EPSILON || !weight.is_finite() {
+                    break;
+                }
+
+                throughput = weight * throughput.mul(srec.attenuation());
+                sampled = Sampled::Bsdf { pdf: pdf_val };
+                ray = scattered;
+            }
+
+            if depth + 1 >= self.roulette_depth {
+                match russian_roulette(throughput) {
+                    Some(survivor) => throughput = survivor,
+                    None => break,
+                }
+            }
+        }
+
+        radiance
+    }
+}
@@ -0,0 +1,636 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use crate::{
+    geometry::{HitRecord, Hittable, Primitive},
+    math::{
+        pdf::{self, Pdf},
+        *,
+    },
+};
+
+pub use {
+    bdpt::BidirectionalPathTracer,
+    debug::{DebugIntegrator, DebugView},
+    mis::MisPathTracer,
+    path::{PathTracer, DEFAULT_ROULETTE_DEPTH},
+    photon::{PhotonMapper, DEFAULT_PHOTONS},
+    spectral::SpectralPathTracer,
+};
+
+pub(crate) use debug::first_surface;
+
+mod bdpt;
+mod debug;
+mod light;
+mod mis;
+mod path;
+mod photon;
+mod spectral;
+
+/// Everything an [`Integrator`] sees of the scene while estimating one sample.
+pub struct RenderContext<'a> {
+    pub world: &'a dyn Hittable,
+    /// Emitters to importance sample; may be empty.
+    pub lights: &'a dyn Primitive,
+    /// Radiance of rays that escape the scene.
+    pub background: &'a Color,
+    /// Maximum number of bounces along a path.
+    pub max_depth: u16,
+}
+
+/// Length of the diagonal of the bounding box of `world`, or `None` when it is empty or
+/// unbounded.
+pub(crate) fn scene_diagonal(world: &dyn Hittable) -> Option<f32> {
+    let bbox = world.bounding_box();
+    let diagonal = Vec3::new(bbox.x().size(), bbox.y().size(), bbox.z().size()).norm();
+    (diagonal.is_finite() && diagonal > 0.0).then_some(diagonal)
+}
+
+/// Radiance arriving along a camera ray, split by the number of times the light bounced on
+/// its way from the emitter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightPaths {
+    /// Light seen without any bounce: emitters met by the camera ray, or the background when
+    /// it escapes.
+    pub emitted: Color,
+    /// Light reflected or transmitted once towards the camera.
+    pub direct: Color,
+    /// Light that bounced two or more times.
+    pub indirect: Color,
+}
+
+impl LightPaths {
+    pub const fn new() -> Self {
+        Self {
+            emitted: BLACK,
+            direct: BLACK,
+            indirect: BLACK,
+        }
+    }
+
+    /// All of `radiance` as seen directly, for integrators that do not follow bounces.
+    pub fn emitted(radiance: Color) -> Self {
+        Self {
+            emitted: radiance,
+            ..Self::new()
+        }
+    }
+
+    /// Adds `light` that reached the camera after `bounces` bounces.
+    pub fn add(&mut self, bounces: usize, light: Color) {
+        match bounces {
+            0 => self.emitted += light,
+            1 => self.direct += light,
+            _ => self.indirect += light,
+        }
+    }
+
+    pub fn total(&self) -> Color {
+        &self.emitted + &self.direct + &self.indirect
+    }
+}
+
+impl Default for LightPaths {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A light transport algorithm: estimates the radiance arriving along a camera ray.
+///
+/// `Camera::render` handles pixel sampling and calls the integrator once per sample.
+pub trait Integrator: Send + Sync {
+    /// Called once per render before any sample is taken, for integrators that first gather
+    /// information about the scene.
+    fn preprocess(&self, _ctx: &RenderContext) {}
+
+    fn radiance(&self, ray: &Ray, ctx: &RenderContext) -> Color;
+
+    /// The same estimate as [`Integrator::radiance`], split by bounces for the emission, direct
+    /// and indirect lighting passes. Integrators that do not keep track report it all as
+    /// emitted.
+    fn light_paths(&self, ray: &Ray, ctx: &RenderContext) -> LightPaths {
+        LightPaths::emitted(self.radiance(ray, ctx))
+    }
+}
+
+/// The integrators selectable by name, e.g. from the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntegratorKind {
+    /// Path tracer with next-event estimation, with Russian roulette after `roulette_depth`
+    /// bounces.
+    Path { roulette_depth: u16 },
+    /// Path tracer sampling a mixture of the lights and the BSDF, without shadow rays.
+    Mixture { roulette_depth: u16 },
+    /// Path tracer taking separate light and BSDF samples, combined with the power heuristic.
+    Mis { roulette_depth: u16 },
+    /// Bidirectional path tracer joining camera and light subpaths.
+    Bdpt,
+    /// Progressive photon mapper emitting `photons` photons per pass, gathered within `radius`
+    /// (derived from the scene size when `None`).
+    Photon { photons: usize, radius: Option<f32> },
+    /// Path tracer with next-event estimation carrying sampled wavelengths rather than RGB,
+    /// for dispersion.
+    Spectral { roulette_depth: u16 },
+    /// One of the geometry views of [`DebugIntegrator`].
+    Debug(DebugView),
+}
+
+impl IntegratorKind {
+    pub fn build(self) -> Arc<dyn Integrator> {
+        match self {
+            IntegratorKind::Path { roulette_depth } => {
+                Arc::new(PathTracer::with_roulette_depth(roulette_depth))
+            }
+            IntegratorKind::Mixture { roulette_depth } => {
+                Arc::new(PathTracer::with_roulette_depth(roulette_depth).with_next_event(false))
+            }
+            IntegratorKind::Mis { roulette_depth } => {
+                Arc::new(MisPathTracer::with_roulette_depth(roulette_depth))
+            }
+            IntegratorKind::Bdpt => Arc::new(BidirectionalPathTracer::new()),
+            IntegratorKind::Photon { photons, radius } => {
+                let mapper = PhotonMapper::with_photons(photons);
+                Arc::new(match radius {
+                    Some(radius) => mapper.with_radius(radius),
+                    None => mapper,
+                })
+            }
+            IntegratorKind::Spectral { roulette_depth } => {
+                Arc::new(SpectralPathTracer::with_roulette_depth(roulette_depth))
+            }
+            IntegratorKind::Debug(view) => Arc::new(DebugIntegrator::new(view)),
+        }
+    }
+}
+
+impl Default for IntegratorKind {
+    fn default() -> Self {
+        IntegratorKind::Path {
+            roulette_depth: DEFAULT_ROULETTE_DEPTH,
+        }
+    }
+}
+
+impl FromStr for IntegratorKind {
+    type Err = String;
+
+    /// Accepts `path`, `mixture`, `mis` and `spectral`, each with an optional `:roulette_depth`,
+    /// `bdpt`, `photon` with optional `:photons_per_pass` and `:radius`, and the debug views
+    /// `normal`, `geometric-normal`, `depth[:max_distance]`, `uv`, `albedo`, `facing` and
+    /// `ao[:radius]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = match s.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (s, None),
+        };
+
+        let roulette_depth = || match argument {
+            Some(depth) => depth
+                .trim()
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid roulette depth: '{}'", depth)),
+            None => Ok(DEFAULT_ROULETTE_DEPTH),
+        };
+        let distance = |what: &str| match argument {
+            Some(distance) => distance
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|d| *d > 0.0 && d.is_finite())
+                .map(Some)
+                .ok_or_else(|| format!("Invalid {}: '{}'", what, distance)),
+            None => Ok(None),
+        };
+        let debug = |view: DebugView| match argument {
+            Some(_) => Err(format!("Unexpected argument: '{}'", s)),
+            None => Ok(IntegratorKind::Debug(view)),
+        };
+
+        match name.trim().to_ascii_lowercase().as_str() {
+            "path" => Ok(IntegratorKind::Path {
+                roulette_depth: roulette_depth()?,
+            }),
+            "mixture" => Ok(IntegratorKind::Mixture {
+                roulette_depth: roulette_depth()?,
+            }),
+            "mis" => Ok(IntegratorKind::Mis {
+                roulette_depth: roulette_depth()?,
+            }),
+            "spectral" => Ok(IntegratorKind::Spectral {
+                roulette_depth: roulette_depth()?,
+            }),
+            "bdpt" if argument.is_none() => Ok(IntegratorKind::Bdpt),
+            "photon" => {
+                let (photons, radius) = match argument.map(|a| a.split_once(':')) {
+                    None => (None, None),
+                    Some(None) => (argument, None),
+                    Some(Some((photons, radius))) => (Some(photons), Some(radius)),
+                };
+                let photons = match photons {
+                    Some(photons) => photons
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("Invalid photon count: '{}'", photons))?,
+                    None => DEFAULT_PHOTONS,
+                };
+                let radius = match radius {
+                    Some(radius) => Some(
+                        radius
+                            .trim()
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|r| *r > 0.0 && r.is_finite())
+                            .ok_or_else(|| format!("Invalid photon radius: '{}'", radius))?,
+                    ),
+                    None => None,
+                };
+                Ok(IntegratorKind::Photon { photons, radius })
+            }
+            "normal" => debug(DebugView::ShadingNormal),
+            "geometric-normal" => debug(DebugView::GeometricNormal),
+            "depth" => Ok(IntegratorKind::Debug(DebugView::Depth {
+                max_distance: distance("depth range")?,
+            })),
+            "uv" => debug(DebugView::Uv),
+            "albedo" => debug(DebugView::Albedo),
+            "facing" => debug(DebugView::FrontFace),
+            "ao" => Ok(IntegratorKind::Debug(DebugView::AmbientOcclusion {
+                radius: distance("occlusion radius")?,
+            })),
+            _ => Err(format!("Unknown integrator: '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for IntegratorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegratorKind::Path { roulette_depth } => write!(f, "path:{}", roulette_depth),
+            IntegratorKind::Mixture { roulette_depth } => write!(f, "mixture:{}", roulette_depth),
+            IntegratorKind::Mis { roulette_depth } => write!(f, "mis:{}", roulette_depth),
+            IntegratorKind::Bdpt => write!(f, "bdpt"),
+            IntegratorKind::Photon {
+                photons,
+                radius: None,
+            } => write!(f, "photon:{}", photons),
+            IntegratorKind::Photon {
+                photons,
+                radius: Some(radius),
+            } => write!(f, "photon:{}:{}", photons, radius),
+            IntegratorKind::Spectral { roulette_depth } => {
+                write!(f, "spectral:{}", roulette_depth)
+            }
+            IntegratorKind::Debug(view) => {
+                let (name, argument) = match view {
+                    DebugView::ShadingNormal => ("normal", None),
+                    DebugView::GeometricNormal => ("geometric-normal", None),
+                    DebugView::Depth { max_distance } => ("depth", *max_distance),
+                    DebugView::Uv => ("uv", None),
+                    DebugView::Albedo => ("albedo", None),
+                    DebugView::FrontFace => ("facing", None),
+                    DebugView::AmbientOcclusion { radius } => ("ao", *radius),
+                };
+                match argument {
+                    Some(argument) => write!(f, "{}:{}", name, argument),
+                    None => write!(f, "{}", name),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::{HittableList, Quad, Sphere},
+        integrators::light::is_finite,
+        materials::{Dielectric, DiffuseLight, Lambertian, RefractiveIndex},
+        math::rng,
+    };
+
+    #[test]
+    fn parses_names() {
+        for kind in [
+            IntegratorKind::Path { roulette_depth: 7 },
+            IntegratorKind::Mixture { roulette_depth: 0 },
+            IntegratorKind::Mis { roulette_depth: 2 },
+            IntegratorKind::Bdpt,
+            IntegratorKind::Photon {
+                photons: 5000,
+                radius: None,
+            },
+            IntegratorKind::Photon {
+                photons: 20,
+                radius: Some(0.25),
+            },
+            IntegratorKind::Spectral { roulette_depth: 4 },
+            IntegratorKind::Debug(DebugView::ShadingNormal),
+            IntegratorKind::Debug(DebugView::GeometricNormal),
+            IntegratorKind::Debug(DebugView::Depth { max_distance: None }),
+            IntegratorKind::Debug(DebugView::Depth {
+                max_distance: Some(12.5),
+            }),
+            IntegratorKind::Debug(DebugView::Uv),
+            IntegratorKind::Debug(DebugView::Albedo),
+            IntegratorKind::Debug(DebugView::FrontFace),
+            IntegratorKind::Debug(DebugView::AmbientOcclusion { radius: Some(2.0) }),
+        ] {
+            assert_eq!(kind.to_string().parse::<IntegratorKind>(), Ok(kind));
+        }
+        assert_eq!("path".parse(), Ok(IntegratorKind::default()));
+        assert!("path:-1".parse::<IntegratorKind>().is_err());
+        assert!("bdpt:3".parse::<IntegratorKind>().is_err());
+        assert!("photon:0".parse::<IntegratorKind>().is_err());
+        assert!("photon:100:-1".parse::<IntegratorKind>().is_err());
+        assert!("uv:1".parse::<IntegratorKind>().is_err());
+        assert!("ao:0".parse::<IntegratorKind>().is_err());
+        assert!("whitted".parse::<IntegratorKind>().is_err());
+    }
+
+    #[test]
+    fn path_tracer_sees_background_and_emitters() {
+        let mut world = HittableList::new();
+        world.push(Arc::new(Sphere::new(
+            Point3::new(0.0, 0.0, -5.0),
+            1.0,
+            Arc::new(DiffuseLight::from_color(Color::new(4.0, 2.0, 1.0))),
+        )));
+        let lights = HittableList::new();
+        let background = Color::new(0.1, 0.2, 0.3);
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: &background,
+            max_depth: 4,
+        };
+
+        let origin = Point3::from_origin();
+        let miss = Ray::new(origin.clone(), Vec3::new(0.0, 0.0, 1.0));
+        let hit = Ray::new(origin, Vec3::new(0.0, 0.0, -1.0));
+
+        let integrator = PathTracer::new();
+        assert_eq!(integrator.radiance(&miss, &ctx), background);
+        assert_eq!(integrator.radiance(&hit, &ctx), Color::new(4.0, 2.0, 1.0));
+    }
+
+    /// A grey floor lit by a small quad light and a dim sky, seen from just above.
+    fn lit_floor() -> (HittableList, HittableList) {
+        let light = Arc::new(Quad::new(
+            Point3::new(-1.0, 2.0, -1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            Arc::new(DiffuseLight::from_color(Color::new(4.0, 4.0, 4.0))),
+        ));
+        let mut world = HittableList::new();
+        world.push(Arc::new(Quad::new(
+            Point3::new(-50.0, 0.0, -50.0),
+            Vec3::new(0.0, 0.0, 100.0),
+            Vec3::new(100.0, 0.0, 0.0),
+            Arc::new(Lambertian::from_color(Color::new(0.5, 0.5, 0.5), 1.0)),
+        )));
+        world.push(light.clone());
+
+        (world, HittableList::from_object(light))
+    }
+
+    fn mean_radiance(integrator: &dyn Integrator, samples: usize) -> f32 {
+        let (world, lights) = lit_floor();
+        let background = Color::new(0.2, 0.2, 0.2);
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: &background,
+            max_depth: 8,
+        };
+
+        let ray = Ray::new(Point3::new(0.3, 1.0, 0.1), Vec3::new(0.0, -1.0, 0.0));
+        let total: f32 = (0..samples)
+            .map(|_| integrator.radiance(&ray, &ctx).x())
+            .sum();
+        total / samples as f32
+    }
+
+    #[test]
+    fn russian_roulette_is_unbiased() {
+        rng::seed_thread(1, 0);
+        let full = mean_radiance(&PathTracer::with_roulette_depth(u16::MAX), 40_000);
+        let roulette = mean_radiance(&PathTracer::with_roulette_depth(0), 40_000);
+        assert!(
+            (full - roulette).abs() < 0.03 * full,
+            "{} vs {}",
+            full,
+            roulette
+        );
+    }
+
+    #[test]
+    fn light_sampling_strategies_agree() {
+        rng::seed_thread(2, 0);
+        let mixture = mean_radiance(&PathTracer::new().with_next_event(false), 40_000);
+        let next_event = mean_radiance(&PathTracer::new(), 40_000);
+        let mis = mean_radiance(&MisPathTracer::new(), 40_000);
+
+        for (name, estimate) in [("next event", next_event), ("mis", mis)] {
+            assert!(
+                (mixture - estimate).abs() < 0.03 * mixture,
+                "{}: {} vs {}",
+                name,
+                mixture,
+                estimate
+            );
+        }
+    }
+
+    #[test]
+    fn bidirectional_matches_path_tracer() {
+        rng::seed_thread(3, 0);
+        let path = mean_radiance(&PathTracer::new(), 40_000);
+        let bdpt = mean_radiance(&BidirectionalPathTracer::new(), 40_000);
+        assert!((path - bdpt).abs() < 0.03 * path, "{} vs {}", path, bdpt);
+    }
+
+    #[test]
+    fn spectral_matches_path_tracer() {
+        rng::seed_thread(4, 0);
+        let path = mean_radiance(&PathTracer::new(), 40_000);
+        let spectral = mean_radiance(&SpectralPathTracer::new(), 40_000);
+        assert!(
+            (path - spectral).abs() < 0.03 * path,
+            "{} vs {}",
+            path,
+            spectral
+        );
+    }
+
+    #[test]
+    fn dispersion_splits_white_light() {
+        rng::seed_thread(5, 0);
+        // Looking at a white sky through a diamond ball, each wavelength leaves in its own
+        // direction but the colour averages back to white.
+        let mut world = HittableList::new();
+        world.push(Arc::new(Sphere::new(
+            Point3::from_origin(),
+            1.0,
+            Arc::new(Dielectric::with_index(RefractiveIndex::DIAMOND)),
+        )));
+        let lights = HittableList::new();
+        let background = Color::new(1.0, 1.0, 1.0);
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: &background,
+            max_depth: 16,
+        };
+
+        let ray = Ray::new(Point3::new(0.5, 0.0, -3.0), Vec3::new(0.0, 0.0, 1.0));
+        let integrator = SpectralPathTracer::new();
+        let mut sum = BLACK;
+        let mut coloured = 0;
+        for _ in 0..40_000 {
+            let sample = integrator.radiance(&ray, &ctx);
+            assert!(is_finite(&sample));
+            // Only the hero wavelength survives the diamond, so samples are tinted.
+            if (sample.x() - sample.z()).abs() > 0.5 * (sample.x() + sample.z()) {
+                coloured += 1;
+            }
+            sum += sample;
+        }
+        let mean = (1.0 / 40_000.0) * sum;
+        let grey = (mean.x() + mean.y() + mean.z()) / 3.0;
+        for channel in [mean.x(), mean.y(), mean.z()] {
+            assert!((channel - grey).abs() < 0.05 * grey, "{:?}", mean);
+        }
+        assert!(coloured > 10_000, "{}", coloured);
+    }
+
+    #[test]
+    fn photon_mapper_matches_path_tracer() {
+        rng::seed_thread(6, 0);
+        // A closed white room, so that plenty of photons land around the point being shaded.
+        let white = Arc::new(Lambertian::from_color(Color::new(0.7, 0.7, 0.7), 1.0));
+        let mut world = HittableList::new();
+        for (q, u, v) in [
+            ((0.0, 0.0, 0.0), (0.0, 0.0, 4.0), (4.0, 0.0, 0.0)),
+            ((0.0, 3.0, 0.0), (4.0, 0.0, 0.0), (0.0, 0.0, 4.0)),
+            ((0.0, 0.0, 0.0), (0.0, 3.0, 0.0), (0.0, 0.0, 4.0)),
+            ((4.0, 0.0, 0.0), (0.0, 0.0, 4.0), (0.0, 3.0, 0.0)),
+            ((0.0, 0.0, 0.0), (4.0, 0.0, 0.0), (0.0, 3.0, 0.0)),
+            ((0.0, 0.0, 4.0), (0.0, 3.0, 0.0), (4.0, 0.0, 0.0)),
+        ] {
+            world.push(Arc::new(Quad::new(
+                Point3::new(q.0, q.1, q.2),
+                Vec3::new(u.0, u.1, u.2),
+                Vec3::new(v.0, v.1, v.2),
+                white.clone(),
+            )));
+        }
+        let light = Arc::new(Quad::new(
+            Point3::new(1.5, 2.99, 1.5),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Arc::new(DiffuseLight::from_color(Color::new(4.0, 4.0, 4.0))),
+        ));
+        world.push(light.clone());
+        let lights = HittableList::from_object(light);
+
+        let background = BLACK;
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: &background,
+            max_depth: 8,
+        };
+        let ray = Ray::new(Point3::new(1.0, 1.0, 1.0), Vec3::new(0.5, -1.0, 0.2));
+        let mean = |integrator: &dyn Integrator| {
+            integrator.preprocess(&ctx);
+            let total: f32 = (0..20_000)
+                .map(|_| integrator.radiance(&ray, &ctx).x())
+                .sum();
+            total / 20_000.0
+        };
+
+        let path = mean(&PathTracer::new());
+        let photon = mean(&PhotonMapper::with_photons(50_000).with_radius(0.1));
+        // Density estimation is only consistent, so allow for some blur and noise in the maps.
+        assert!((path - photon).abs() < 0.1 * path, "{} vs {}", path, photon);
+    }
+
+    #[test]
+    fn debug_views_show_the_geometry() {
+        rng::seed_thread(7, 0);
+        let (world, lights) = lit_floor();
+        let background = BLACK;
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: &background,
+            max_depth: 1,
+        };
+        let view = |view: DebugView, ray: &Ray| DebugIntegrator::new(view).radiance(ray, &ctx);
+
+        let down = Ray::new(Point3::new(0.3, 1.0, 0.1), Vec3::new(0.0, -2.0, 0.0));
+        assert_eq!(
+            view(DebugView::ShadingNormal, &down),
+            Color::new(0.5, 1.0, 0.5)
+        );
+        assert_eq!(
+            view(DebugView::GeometricNormal, &down),
+            Color::new(0.5, 1.0, 0.5)
+        );
+        assert_eq!(view(DebugView::FrontFace, &down), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(view(DebugView::Albedo, &down), Color::new(0.5, 0.5, 0.5));
+        let depth = view(
+            DebugView::Depth {
+                max_distance: Some(4.0),
+            },
+            &down,
+        );
+        assert!((depth.x() - 0.25).abs() < 1e-5);
+
+        // The light quad faces down, so it is seen from the back from above.
+        let above = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(
+            view(DebugView::FrontFace, &above),
+            Color::new(1.0, 0.0, 0.0)
+        );
+
+        // Under the light, a small radius sees nothing and a large one sees the light.
+        let open = view(DebugView::AmbientOcclusion { radius: Some(0.5) }, &down);
+        assert_eq!(open, Color::new(1.0, 1.0, 1.0));
+        let covered = view(DebugView::AmbientOcclusion { radius: Some(10.0) }, &down);
+        assert!(covered.x() < 1.0);
+
+        let sky = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(view(DebugView::Uv, &sky), BLACK);
+    }
+
+    #[test]
+    fn occluded_lights_cast_shadows() {
+        rng::seed_thread(8, 0);
+        let (mut world, lights) = lit_floor();
+        world.push(Arc::new(Quad::new(
+            Point3::new(-5.0, 1.5, -5.0),
+            Vec3::new(10.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 10.0),
+            Arc::new(Lambertian::from_color(BLACK, 1.0)),
+        )));
+        let background = BLACK;
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: &background,
+            max_depth: 4,
+        };
+
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        for _ in 0..100 {
+            assert_eq!(PathTracer::new().radiance(&ray, &ctx), BLACK);
+            assert_eq!(MisPathTracer::new().radiance(&ray, &ctx), BLACK);
+            assert_eq!(BidirectionalPathTracer::new().radiance(&ray, &ctx), BLACK);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use super::{
+    light::{is_finite, light_pdf, sample_light},
+    *,
+};
+
+/// Bounces always traced before Russian roulette may end a path.
+pub const DEFAULT_ROULETTE_DEPTH: u16 = 3;
+
+/// Lower bound on the survival probability, so that roulette never divides the throughput by
+/// a vanishing number and turns a dim path into a firefly.
+pub(super) const MIN_SURVIVAL: f32 = 0.05;
+
+/// Monte Carlo path tracer in the style of _The Rest of Your Life_.
+///
+/// With next-event estimation (the default) every non-specular vertex casts a shadow ray to a
+/// point sampled on the lights, and the path continues with a BSDF sample whose hits on those
+/// lights are then ignored, so direct light is counted exactly once. Without it, directions are
+/// drawn from a mixture of the light and BSDF densities (see [`PathTracer::with_light_weight`])
+/// and emission is picked up wherever the path lands.
+///
+/// Paths are traced iteratively while tracking their throughput, and after `roulette_depth`
+/// bounces Russian roulette terminates them with a probability that grows as the throughput
+/// falls, reweighting the survivors so the estimate stays unbiased.
+#[derive(Debug, Clone, Copy)]
+pub struct PathTracer {
+    roulette_depth: u16,
+    light_weight: f32,
+    next_event: bool,
+}
+
+impl PathTracer {
+    pub fn new() -> Self {
+        Self::with_roulette_depth(DEFAULT_ROULETTE_DEPTH)
+    }
+
+    /// Russian roulette starts after `depth` bounces; `u16::MAX` disables it.
+    pub fn with_roulette_depth(depth: u16) -> Self {
+        Self {
+            roulette_depth: depth,
+            light_weight: 0.5,
+            next_event: true,
+        }
+    }
+
+    /// Probability of drawing a scattered direction towards the lights rather than from the
+    /// BSDF. Only used without next-event estimation.
+    pub fn with_light_weight(mut self, weight: f32) -> Self {
+        self.light_weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Toggles explicit light sampling with shadow rays.
+    pub fn with_next_event(mut self, next_event: bool) -> Self {
+        self.next_event = next_event;
+        self
+    }
+
+    pub fn roulette_depth(&self) -> u16 {
+        self.roulette_depth
+    }
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(&self, ray: &Ray, ctx: &RenderContext) -> Color {
+        self.light_paths(ray, ctx).total()
+    }
+
+    fn light_paths(&self, ray: &Ray, ctx: &RenderContext) -> LightPaths {
+        let mut radiance = LightPaths::new();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray.clone();
+        // Whether the current ray came from a BSDF sample at a vertex that also sampled the
+        // lights; those already account for light sources it may hit.
+        let mut after_next_event = false;
+
+        for depth in 0..ctx.max_depth {
+            let rec = match ctx.world.hit(&ray, Interval::CAMERA_VIEW) {
+                Some(rec) => rec,
+                None => {
+                    radiance.add(depth as usize, throughput.mul(ctx.background));
+                    break;
+                }
+            };
+
+            if !after_next_event || light_pdf(ctx, &ray, &rec) <= 0.0 {
+                radiance.add(
+                    depth as usize,
+                    throughput.mul(&rec.material().emitted(&rec)),
+                );
+            }
+
+            let srec = match rec.material().scatter(&ray, &rec) {
+                Some(srec) => srec,
+                None => break,
+            };
+
+            if let Some(spec) = srec.specular() {
+                throughput = throughput.mul(srec.attenuation());
+                ray = spec.clone();
+                after_next_event = false;
+            } else {
+                let pdf = srec.pdf().unwrap();
+
+                // A shadow ray extends the path by one segment.
+                if self.next_event && depth + 1 < ctx.max_depth {
+                    if let Some(light) = sample_light(ctx, rec.point(), ray.time()) {
+                        let f = rec.material().scattering_pdf(&ray, &rec, &light.ray);
+                        let contribution = (f / light.pdf)
+                            * throughput.mul(srec.attenuation()).mul(&light.emitted);
+
+                        if is_finite(&contribution) {
+                            radiance.add(depth as usize + 1, contribution);
+                        }
+                    }
+                }
+
+                let light_weight = if self.next_event {
+                    0.0
+                } else {
+                    self.light_weight
+                };
+                let light_pdf = pdf::Primitive::new(ctx.lights, rec.point());
+                let mixture_pdf = pdf::Mixture::weighted(&light_pdf, pdf, light_weight);
+
+                let scattered =
+                    Ray::new(rec.point().clone(), mixture_pdf.generate()).set_time(ray.time());
+                let pdf_val = mixture_pdf.value(scattered.direction().clone());
+                let scattering_pdf = rec.material().scattering_pdf(&ray, &rec, &scattered);
+
+                let weight = scattering_pdf / pdf_val;
+                if pdf_val.abs() < EPSILON || !weight.is_finite() {
+                    break;
+                }
+
+                throughput = weight * throughput.mul(srec.attenuation());
+                ray = scattered;
+                after_next_event = self.next_event;
+            }
+
+            if depth + 1 >= self.roulette_depth {
+                match russian_roulette(throughput) {
+                    Some(survivor) => throughput = survivor,
+                    None => break,
+                }
+            }
+        }
+
+        radiance
+    }
+}
+
+/// Ends a path with a probability that grows as its throughput falls, returning the reweighted
+/// throughput of a surviving path.
+pub(super) fn russian_roulette(throughput: Color) -> Option<Color> {
+    let max_component = throughput.x().max(throughput.y()).max(throughput.z());
+    let survival = max_component.clamp(MIN_SURVIVAL, 1.0);
+
+    if random_float() >= survival {
+        return None;
+    }
+    Some((1.0 / survival) * throughput)
+}
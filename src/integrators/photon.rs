@@ -0,0 +1,354 @@
+use std::sync::OnceLock;
+
+use rayon::prelude::*;
+
+use super::{
+    light::{is_black, is_finite, light_pdf, sample_emitter, sample_light},
+    path::russian_roulette,
+    *,
+};
+use crate::{collections::KdTree, math::rng};
+
+/// Photons emitted per pass unless configured otherwise.
+pub const DEFAULT_PHOTONS: usize = 100_000;
+
+const DEFAULT_PASSES: usize = 8;
+
+/// Share of the photons kept from one pass to the next in progressive photon mapping, which sets
+/// how fast the gather radius shrinks.
+const ALPHA: f32 = 2.0 / 3.0;
+
+/// Initial gather radius, as a fraction of the diagonal of the scene's bounding box.
+const DEFAULT_RADIUS_FRACTION: f32 = 0.01;
+
+/// Share of the photons emitted by the background, when it is not black.
+const ENVIRONMENT_SHARE: f32 = 0.5;
+
+/// Progressive photon mapper, following the probabilistic formulation of Knaus and Zwicker.
+///
+/// Before rendering, photons are emitted from points sampled on the lights with
+/// [`Primitive::sample_surface`] and from the background, and traced through the scene. Every
+/// bounce that lands on a diffuse surface is stored in a kd-tree, except for the first one of
+/// photons from the lights, whose direct light is better sampled with shadow rays. This is
+/// done in several independent passes, each with its own photon map and a gather radius
+/// shrinking from pass to pass.
+///
+/// Camera paths follow specular bounces until the first diffuse surface, where direct light is
+/// sampled with a shadow ray and the rest is estimated from the density of the photons of a
+/// randomly chosen pass around the hit. Caustics seen through glass thus come out smooth, at
+/// the cost of some blur that fades as more photons are traced. Inside participating media the
+/// camera path carries on as in [`PathTracer`].
+pub struct PhotonMapper {
+    photons: usize,
+    passes: usize,
+    radius: Option<f32>,
+    maps: OnceLock<Vec<PhotonMap>>,
+}
+
+impl PhotonMapper {
+    pub fn new() -> Self {
+        Self::with_photons(DEFAULT_PHOTONS)
+    }
+
+    /// Emits `photons` photons in each pass.
+    pub fn with_photons(photons: usize) -> Self {
+        Self {
+            photons: photons.max(1),
+            passes: DEFAULT_PASSES,
+            radius: None,
+            maps: OnceLock::new(),
+        }
+    }
+
+    /// Number of photon maps to trace; more passes average out the blur of the first ones.
+    pub fn with_passes(mut self, passes: usize) -> Self {
+        self.passes = passes.max(1);
+        self
+    }
+
+    /// Gather radius of the first pass, in scene units. Defaults to a hundredth of the size of
+    /// the scene.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    fn trace_photons(&self, ctx: &RenderContext) -> Vec<PhotonMap> {
+        let mut radius_squared = self
+            .radius
+            .unwrap_or_else(|| default_radius(ctx.world))
+            .powi(2);
+        let radii: Vec<f32> = (1..=self.passes)
+            .map(|i| {
+                let radius = radius_squared.sqrt();
+                radius_squared *= (i as f32 + ALPHA) / (i as f32 + 1.0);
+                radius
+            })
+            .collect();
+
+        // Each pass draws from its own stream, whichever thread traces it.
+        let seeds: Vec<u64> = radii.iter().map(|_| rng::random_u64()).collect();
+
+        radii
+            .into_par_iter()
+            .zip(seeds)
+            .map(|(radius, seed)| {
+                rng::seed_thread(seed, 0);
+                PhotonMap {
+                    photons: KdTree::new(self.trace_pass(ctx)),
+                    radius,
+                }
+            })
+            .collect()
+    }
+
+    /// Traces the photons of one pass, returning the ones to be gathered.
+    fn trace_pass(&self, ctx: &RenderContext) -> Vec<(Point3, Photon)> {
+        let mut stored = Vec::new();
+
+        let bounds = ctx.world.bounding_box();
+        let [x, y, z] = bounds.center();
+        let center = Point3::new(x, y, z);
+        let radius =
+            0.5 * Vec3::new(bounds.x().size(), bounds.y().size(), bounds.z().size()).norm();
+        let environment = if is_black(ctx.background) || !radius.is_finite() {
+            0.0
+        } else {
+            ENVIRONMENT_SHARE
+        };
+
+        for _ in 0..self.photons {
+            let time = random_float();
+            let from_environment = random_float() < environment;
+
+            let (mut ray, power) = if from_environment {
+                // Parallel rays from a random direction, through a disc covering the scene.
+                let direction = UnitVec3::unchecked_random();
+                let (sin, cos) = (2.0 * PI * random_float()).sin_cos();
+                let r = radius * random_float().sqrt();
+                let offset = Onb::from_unit_vec3(direction.clone()).transform(&Vec3::new(
+                    r * cos,
+                    r * sin,
+                    0.0,
+                ));
+                let origin = &center - &(radius * &direction) + &offset;
+
+                let area = PI * radius * radius;
+                let power =
+                    (4.0 * PI * area / (environment * self.photons as f32)) * ctx.background;
+                (
+                    Ray::new(origin, direction.into_vec3()).set_time(time),
+                    power,
+                )
+            } else {
+                let Some(emitter) = sample_emitter(ctx, time) else {
+                    continue;
+                };
+                // Cosine-weighted emission carries the radiance scaled by cosine / pdf = pi.
+                let scale = PI / ((1.0 - environment) * self.photons as f32 * emitter.pdf);
+                let emission = pdf::Cosine::new(emitter.normal);
+                (
+                    Ray::new(emitter.point, emission.generate()).set_time(time),
+                    scale * &emitter.emitted,
+                )
+            };
+            let mut throughput = Color::new(1.0, 1.0, 1.0);
+
+            for depth in 0..ctx.max_depth {
+                let Some(rec) = ctx.world.hit(&ray, Interval::CAMERA_VIEW) else {
+                    break;
+                };
+                let Some(srec) = rec.material().scatter(&ray, &rec) else {
+                    break;
+                };
+
+                if let Some(spec) = srec.specular() {
+                    throughput = throughput.mul(srec.attenuation());
+                    ray = spec.clone();
+                    continue;
+                }
+
+                // Direct light is left to the shadow rays of the camera paths.
+                if (depth > 0 || from_environment) && rec.on_surface() {
+                    stored.push((
+                        rec.point().clone(),
+                        Photon {
+                            direction: ray.direction().unchecked_normalize(),
+                            power: power.mul(&throughput),
+                            bounces: depth as usize,
+                        },
+                    ));
+                }
+
+                let pdf = srec.pdf().unwrap();
+                let scattered = Ray::new(rec.point().clone(), pdf.generate()).set_time(time);
+                let pdf_val = pdf.value(scattered.direction().clone());
+                let scattering_pdf = rec.material().scattering_pdf(&ray, &rec, &scattered);
+
+                let weight = scattering_pdf / pdf_val;
+                if pdf_val.abs() < EPSILON || !weight.is_finite() {
+                    break;
+                }
+
+                throughput = weight * throughput.mul(srec.attenuation());
+                ray = scattered;
+
+                if depth + 1 >= DEFAULT_ROULETTE_DEPTH {
+                    match russian_roulette(throughput) {
+                        Some(survivor) => throughput = survivor,
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        stored
+    }
+}
+
+impl Default for PhotonMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Integrator for PhotonMapper {
+    fn preprocess(&self, ctx: &RenderContext) {
+        self.maps.get_or_init(|| self.trace_photons(ctx));
+    }
+
+    fn radiance(&self, ray: &Ray, ctx: &RenderContext) -> Color {
+        self.light_paths(ray, ctx).total()
+    }
+
+    fn light_paths(&self, ray: &Ray, ctx: &RenderContext) -> LightPaths {
+        let maps = self.maps.get_or_init(|| self.trace_photons(ctx));
+        let map = &maps[((random_float() * maps.len() as f32) as usize).min(maps.len() - 1)];
+
+        let mut radiance = LightPaths::new();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray.clone();
+        let mut after_next_event = false;
+
+        for depth in 0..ctx.max_depth {
+            let rec = match ctx.world.hit(&ray, Interval::CAMERA_VIEW) {
+                Some(rec) => rec,
+                None => {
+                    radiance.add(depth as usize, throughput.mul(ctx.background));
+                    break;
+                }
+            };
+
+            if !after_next_event || light_pdf(ctx, &ray, &rec) <= 0.0 {
+                radiance.add(
+                    depth as usize,
+                    throughput.mul(&rec.material().emitted(&rec)),
+                );
+            }
+
+            let srec = match rec.material().scatter(&ray, &rec) {
+                Some(srec) => srec,
+                None => break,
+            };
+
+            if let Some(spec) = srec.specular() {
+                throughput = throughput.mul(srec.attenuation());
+                ray = spec.clone();
+                after_next_event = false;
+                continue;
+            }
+
+            if depth + 1 < ctx.max_depth {
+                if let Some(light) = sample_light(ctx, rec.point(), ray.time()) {
+                    let f = rec.material().scattering_pdf(&ray, &rec, &light.ray);
+                    let contribution =
+                        (f / light.pdf) * throughput.mul(srec.attenuation()).mul(&light.emitted);
+
+                    if is_finite(&contribution) {
+                        radiance.add(depth as usize + 1, contribution);
+                    }
+                }
+            }
+
+            if rec.on_surface() {
+                map.estimate(&ray, &rec, depth as usize, &throughput, &mut radiance);
+                break;
+            }
+
+            let pdf = srec.pdf().unwrap();
+            let scattered = Ray::new(rec.point().clone(), pdf.generate()).set_time(ray.time());
+            let pdf_val = pdf.value(scattered.direction().clone());
+            let scattering_pdf = rec.material().scattering_pdf(&ray, &rec, &scattered);
+
+            let weight = scattering_pdf / pdf_val;
+            if pdf_val.abs() < EPSILON || !weight.is_finite() {
+                break;
+            }
+
+            throughput = weight * throughput.mul(srec.attenuation());
+            ray = scattered;
+            after_next_event = true;
+
+            if depth + 1 >= DEFAULT_ROULETTE_DEPTH {
+                match russian_roulette(throughput) {
+                    Some(survivor) => throughput = survivor,
+                    None => break,
+                }
+            }
+        }
+
+        radiance
+    }
+}
+
+struct Photon {
+    /// Direction of travel when the photon landed.
+    direction: UnitVec3,
+    power: Color,
+    /// Bounces made before landing: none for photons from the background, whose first hit
+    /// is stored too.
+    bounces: usize,
+}
+
+/// The photons of one pass, with the radius they are gathered from.
+struct PhotonMap {
+    photons: KdTree<Photon>,
+    radius: f32,
+}
+
+impl PhotonMap {
+    /// Adds to `radiance` the light reflected along `ray` at `rec` by the photons within the
+    /// gather radius, times `throughput`, for a camera path that bounced `bounces` times
+    /// before reaching `rec`.
+    fn estimate(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        bounces: usize,
+        throughput: &Color,
+        radiance: &mut LightPaths,
+    ) {
+        let scale = 1.0 / (PI * self.radius * self.radius);
+        self.photons
+            .for_each_within(rec.point(), self.radius, |_, photon| {
+                let towards_light = -&photon.direction;
+                let cosine = towards_light.dot(rec.normal());
+                if cosine <= EPSILON {
+                    return;
+                }
+
+                // `eval` includes the cosine, which the photon power already accounts for.
+                let scattered =
+                    Ray::new(rec.point().clone(), towards_light.into_vec3()).set_time(ray.time());
+                let f = rec.material().eval(ray, rec, &scattered);
+                let reflected = (scale / cosine) * throughput.mul(&f).mul(&photon.power);
+                if is_finite(&reflected) {
+                    radiance.add(bounces + photon.bounces + 1, reflected);
+                }
+            });
+    }
+}
+
+fn default_radius(world: &dyn Hittable) -> f32 {
+    scene_diagonal(world).map_or(1.0, |diagonal| DEFAULT_RADIUS_FRACTION * diagonal)
+}
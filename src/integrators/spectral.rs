@@ -0,0 +1,135 @@
+use super::{
+    light::{light_pdf, sample_light},
+    path::MIN_SURVIVAL,
+    *,
+};
+use crate::math::spectrum::{SampledSpectrum, SampledWavelengths};
+
+/// Path tracer with next-event estimation, like [`PathTracer`], that transports light at a few
+/// sampled wavelengths instead of in RGB.
+///
+/// Each camera sample draws a hero wavelength and its rotations (see [`SampledWavelengths`]),
+/// and every ray of the path carries the hero so that dispersive materials such as
+/// [`Dielectric::with_index`](crate::materials::Dielectric::with_index) can bend it by the
+/// matching index. The first dispersive bounce drops the other wavelengths. RGB textures,
+/// emission and background are upsampled to spectra where they are met, and the radiance is
+/// converted back to RGB at the end, so the rest of the renderer is unaware of the mode.
+#[derive(Debug, Clone, Copy)]
+pub struct SpectralPathTracer {
+    roulette_depth: u16,
+}
+
+impl SpectralPathTracer {
+    pub fn new() -> Self {
+        Self::with_roulette_depth(DEFAULT_ROULETTE_DEPTH)
+    }
+
+    /// Russian roulette starts after `depth` bounces; `u16::MAX` disables it.
+    pub fn with_roulette_depth(depth: u16) -> Self {
+        Self {
+            roulette_depth: depth,
+        }
+    }
+}
+
+impl Default for SpectralPathTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Integrator for SpectralPathTracer {
+    fn radiance(&self, ray: &Ray, ctx: &RenderContext) -> Color {
+        self.light_paths(ray, ctx).total()
+    }
+
+    fn light_paths(&self, ray: &Ray, ctx: &RenderContext) -> LightPaths {
+        let mut wavelengths = SampledWavelengths::sample(random_float());
+        let spectrum = |wavelengths: &SampledWavelengths, rgb: &Color| {
+            SampledSpectrum::from_rgb(rgb, wavelengths)
+        };
+
+        // Emitted, direct and indirect radiance, as in `LightPaths`.
+        let mut radiance = [SampledSpectrum::ZERO; 3];
+        let bounced = |bounces: u16| usize::from(bounces.min(2));
+        let mut throughput = SampledSpectrum::ONE;
+        let mut ray = ray.clone().set_wavelength(wavelengths.hero());
+        let mut after_next_event = false;
+
+        for depth in 0..ctx.max_depth {
+            let rec = match ctx.world.hit(&ray, Interval::CAMERA_VIEW) {
+                Some(rec) => rec,
+                None => {
+                    radiance[bounced(depth)] += throughput * spectrum(&wavelengths, ctx.background);
+                    break;
+                }
+            };
+
+            if !after_next_event || light_pdf(ctx, &ray, &rec) <= 0.0 {
+                radiance[bounced(depth)] +=
+                    throughput * spectrum(&wavelengths, &rec.material().emitted(&rec));
+            }
+
+            let srec = match rec.material().scatter(&ray, &rec) {
+                Some(srec) => srec,
+                None => break,
+            };
+            if rec.material().is_dispersive() {
+                wavelengths.terminate_secondary();
+            }
+            let attenuation = spectrum(&wavelengths, srec.attenuation());
+
+            if let Some(spec) = srec.specular() {
+                throughput = throughput * attenuation;
+                ray = spec.clone().set_wavelength(wavelengths.hero());
+                after_next_event = false;
+            } else {
+                let pdf = srec.pdf().unwrap();
+
+                if depth + 1 < ctx.max_depth {
+                    if let Some(light) = sample_light(ctx, rec.point(), ray.time()) {
+                        let f = rec.material().scattering_pdf(&ray, &rec, &light.ray);
+                        let contribution = (f / light.pdf)
+                            * throughput
+                            * attenuation
+                            * spectrum(&wavelengths, &light.emitted);
+
+                        if contribution.is_finite() {
+                            radiance[bounced(depth + 1)] += contribution;
+                        }
+                    }
+                }
+
+                let scattered = Ray::new(rec.point().clone(), pdf.generate())
+                    .set_time(ray.time())
+                    .set_wavelength(wavelengths.hero());
+                let pdf_val = pdf.value(scattered.direction().clone());
+                let scattering_pdf = rec.material().scattering_pdf(&ray, &rec, &scattered);
+
+                let weight = scattering_pdf / pdf_val;
+                if pdf_val.abs() < EPSILON || !weight.is_finite() {
+                    break;
+                }
+
+                throughput = weight * (throughput * attenuation);
+                ray = scattered;
+                after_next_event = true;
+            }
+
+            if depth + 1 >= self.roulette_depth {
+                let survival = throughput.max_value().clamp(MIN_SURVIVAL, 1.0);
+                if random_float() >= survival {
+                    break;
+                }
+                throughput = (1.0 / survival) * throughput;
+            }
+        }
+
+        let [emitted, direct, indirect] = radiance.map(|radiance| wavelengths.to_rgb(&radiance));
+        LightPaths {
+            emitted,
+            direct,
+            indirect,
+        }
+    }
+}
@@ -2,6 +2,7 @@ mod collections;
 
 pub mod camera;
 pub mod geometry;
+pub mod integrators;
 pub mod materials;
 pub mod math;
 pub mod scenes;
@@ -1,12 +1,54 @@
-use ray_tracer::util::{parse_config, save_as_png_from_floats, ImageError};
+use std::time::Instant;
+
+use ray_tracer::{
+    camera::FrameBuffer,
+    math::tonemap::ToneMap,
+    util::{is_hdr_output, layer_filename, parse_config, save_image_from_floats, ImageError},
+};
 
 fn main() -> Result<(), ImageError> {
-    let (scene, camera, output) = parse_config();
+    let (scene, camera, output, tonemap, mut checkpoints) = parse_config();
+    let resume = checkpoints.as_mut().and_then(|c| c.resume.take());
+
+    // Long renders save the image so far along with their progress, so neither is lost if
+    // the process dies.
+    let mut last_save = Instant::now();
+    let state = scene.render_progressive(&camera, resume, &mut |state| {
+        let Some(checkpoints) = &checkpoints else {
+            return;
+        };
+        if !checkpoints.is_due(state.passes(), last_save.elapsed()) {
+            return;
+        }
+        if let Err(err) = state.save(&checkpoints.path, checkpoints.key) {
+            eprintln!("\nCould not save checkpoint: {}", err);
+        }
+        if let Err(err) = save_frame(camera.frame(state), &output, &tonemap) {
+            eprintln!("\nCould not save intermediate image: {}", err);
+        }
+        last_save = Instant::now();
+    });
+
+    if let Some(checkpoints) = &checkpoints {
+        if let Err(err) = state.save(&checkpoints.path, checkpoints.key) {
+            eprintln!("\nCould not save checkpoint: {}", err);
+        }
+    }
+    save_frame(camera.frame(&state), &output, &tonemap)
+}
 
-    let pixels = scene.render(&camera);
+/// Writes the image to `output` and each layer next to it.
+fn save_frame(mut frame: FrameBuffer, output: &str, tonemap: &ToneMap) -> Result<(), ImageError> {
+    // Float formats keep the linear values for compositing.
+    if !is_hdr_output(output) {
+        frame.prepare_for_display(tonemap);
+    }
 
-    let (width, height) = (camera.width(), camera.height());
+    let (width, height) = (frame.width(), frame.height());
 
-    save_as_png_from_floats(width, height, &pixels, &output)?;
+    save_image_from_floats(width, height, frame.beauty(), output)?;
+    for (aov, layer) in frame.layers() {
+        save_image_from_floats(width, height, layer, &layer_filename(output, aov.name()))?;
+    }
     Ok(())
 }
@@ -1,11 +1,78 @@
 use super::*;
 
+/// Wavelength, in nanometres, at which dispersive indices are evaluated for rays that carry
+/// none: the helium d line, used for the usual `n_d` of optical glass.
+pub const REFERENCE_WAVELENGTH: f32 = 587.6;
+
+/// How the refractive index of a dielectric varies with wavelength.
+///
+/// Coefficients follow the usual catalogue conventions, with wavelengths in micrometres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefractiveIndex {
+    /// The same index at every wavelength.
+    Constant(f32),
+    /// Cauchy's equation, `n = a + b / λ²`.
+    Cauchy { a: f32, b: f32 },
+    /// The Sellmeier equation, `n² = 1 + Σ b_i λ² / (λ² - c_i)`.
+    Sellmeier { b: [f32; 3], c: [f32; 3] },
+}
+
+impl RefractiveIndex {
+    /// Schott N-BK7 borosilicate crown glass.
+    pub const BK7: Self = Self::Sellmeier {
+        b: [1.039_612, 0.231_792_34, 1.010_469_5],
+        c: [0.006_000_699, 0.020_017_914, 103.560_65],
+    };
+
+    /// Diamond, which disperses strongly and gives gems their fire.
+    pub const DIAMOND: Self = Self::Sellmeier {
+        b: [4.3356, 0.3306, 0.0],
+        c: [0.011_236, 0.030_625, 0.0],
+    };
+
+    /// The Cauchy fit of a glass given by its index `n_d` at [`REFERENCE_WAVELENGTH`] and its
+    /// Abbe number, `(n_d - 1) / (n_F - n_C)`, as glass catalogues list them. Lower Abbe
+    /// numbers disperse more.
+    pub fn from_abbe(n_d: f32, abbe: f32) -> Self {
+        let inverse_squared = |nanometres: f32| (1000.0 / nanometres).powi(2);
+        let b = (n_d - 1.0) / (abbe * (inverse_squared(486.1) - inverse_squared(656.3)));
+        Self::Cauchy {
+            a: n_d - b * inverse_squared(REFERENCE_WAVELENGTH),
+            b,
+        }
+    }
+
+    /// Index at `wavelength` nanometres.
+    pub fn at(&self, wavelength: f32) -> f32 {
+        let micrometres = wavelength / 1000.0;
+        let squared = micrometres * micrometres;
+        match *self {
+            Self::Constant(n) => n,
+            Self::Cauchy { a, b } => a + b / squared,
+            Self::Sellmeier { b, c } => {
+                let sum: f32 = (0..3).map(|i| b[i] * squared / (squared - c[i])).sum();
+                (1.0 + sum).sqrt()
+            }
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, Self::Constant(_))
+    }
+}
+
 pub struct Dielectric {
-    refraction_index: f32,
+    refraction_index: RefractiveIndex,
 }
 
 impl Dielectric {
     pub const fn new(refraction_index: f32) -> Self {
+        Self::with_index(RefractiveIndex::Constant(refraction_index))
+    }
+
+    /// A dielectric whose index may depend on wavelength. Only spectral renders show the
+    /// dispersion; RGB renders use the index at [`REFERENCE_WAVELENGTH`].
+    pub const fn with_index(refraction_index: RefractiveIndex) -> Self {
         Self { refraction_index }
     }
 
@@ -18,10 +85,13 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let refraction_index = self
+            .refraction_index
+            .at(r_in.wavelength().unwrap_or(REFERENCE_WAVELENGTH));
         let ri = if rec.front_face() {
-            1.0 / self.refraction_index
+            1.0 / refraction_index
         } else {
-            self.refraction_index
+            refraction_index
         };
 
         let unit_direction = UnitVec3::unchecked_from_vec3(r_in.direction());
@@ -38,6 +108,45 @@ impl Material for Dielectric {
         };
 
         let scattered = Ray::new(rec.point().clone(), direction).set_time(r_in.time());
+        let scattered = match r_in.wavelength() {
+            Some(wavelength) => scattered.set_wavelength(wavelength),
+            None => scattered,
+        };
         Some(ScatterRecord::new_specular(WHITE, scattered))
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        WHITE
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.refraction_index.is_dispersive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dispersion_models() {
+        // Catalogue values of N-BK7 at the F, d and C lines.
+        let bk7 = RefractiveIndex::BK7;
+        assert!((bk7.at(486.1) - 1.5224).abs() < 1e-3);
+        assert!((bk7.at(587.6) - 1.5168).abs() < 1e-3);
+        assert!((bk7.at(656.3) - 1.5143).abs() < 1e-3);
+        assert!((RefractiveIndex::DIAMOND.at(587.6) - 2.417).abs() < 5e-3);
+
+        // Dense flint, from its catalogue n_d and Abbe number.
+        let flint = RefractiveIndex::from_abbe(1.7847, 26.1);
+        assert!((flint.at(587.6) - 1.7847).abs() < 1e-4);
+        let abbe = (flint.at(587.6) - 1.0) / (flint.at(486.1) - flint.at(656.3));
+        assert!((abbe - 26.1).abs() < 0.05);
+
+        let cauchy = RefractiveIndex::Cauchy { a: 1.5, b: 0.004 };
+        assert!(cauchy.at(450.0) > cauchy.at(650.0));
+        assert!(cauchy.is_dispersive());
+        assert!(!RefractiveIndex::Constant(1.5).is_dispersive());
+        assert_eq!(RefractiveIndex::Constant(1.5).at(400.0), 1.5);
+    }
 }
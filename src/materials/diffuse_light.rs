@@ -21,7 +21,6 @@ impl Material for DiffuseLight {
             return BLACK;
         }
 
-        self.texture
-            .value(hit_record.u(), hit_record.v(), hit_record.point())
+        self.texture.value_at(hit_record)
     }
 }
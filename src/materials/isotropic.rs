@@ -18,7 +18,7 @@ impl Isotropic {
 
 impl Material for Isotropic {
     fn scatter(&self, _r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
-        let attenuation = self.text.value(rec.u(), rec.v(), rec.point());
+        let attenuation = self.text.value_at(rec);
         let pdf = pdf::Sphere::new();
 
         Some(ScatterRecord::new(attenuation, Box::new(pdf)))
@@ -27,4 +27,12 @@ impl Material for Isotropic {
     fn scattering_pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f32 {
         1.0 / (4.0 * PI)
     }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        self.scattering_pdf(r_in, rec, scattered) * self.text.value_at(rec)
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.text.value_at(rec)
+    }
 }
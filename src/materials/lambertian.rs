@@ -28,7 +28,7 @@ impl Material for Lambertian {
             return None;
         }
 
-        let attenuation = self.texture.value(rec.u(), rec.v(), rec.point());
+        let attenuation = self.texture.value_at(rec);
         let pdf = pdf::Cosine::new(rec.normal().clone());
 
         let srec = ScatterRecord::new(attenuation, Box::new(pdf));
@@ -47,4 +47,13 @@ impl Material for Lambertian {
 
         cos_theta / PI
     }
+
+    /// `scatter` absorbs with probability `1 - scatter_prob`, which darkens the albedo.
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        (self.scatter_prob * self.scattering_pdf(r_in, rec, scattered)) * self.texture.value_at(rec)
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.scatter_prob * self.texture.value_at(rec)
+    }
 }
@@ -2,13 +2,26 @@ use super::*;
 
 pub struct Metal {
     albedo: Color,
+    /// Multiplies `albedo` where set.
+    texture: Option<Arc<dyn Texture>>,
     fuzz: f32,
 }
 
 impl Metal {
     pub const fn new(albedo: Color, fuzz: f32) -> Self {
         let fuzz = if fuzz < 1.0 { fuzz } else { 1.0 };
-        Self { albedo, fuzz }
+        Self {
+            albedo,
+            texture: None,
+            fuzz,
+        }
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture>, fuzz: f32) -> Self {
+        Self {
+            texture: Some(texture),
+            ..Self::new(Color::splat(1.0), fuzz)
+        }
     }
 }
 
@@ -19,7 +32,7 @@ impl Material for Metal {
             UnitVec3::unchecked_from_vec3(&reflected) + (self.fuzz * UnitVec3::unchecked_random());
 
         let scattered = Ray::new(rec.point().clone(), reflected).set_time(r_in.time());
-        let attenuation = self.albedo.clone();
+        let attenuation = self.albedo(rec);
 
         if scattered.direction().dot(rec.normal()) <= 0.0 {
             return None;
@@ -27,4 +40,11 @@ impl Material for Metal {
 
         Some(ScatterRecord::new_specular(attenuation, scattered))
     }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        match &self.texture {
+            Some(texture) => self.albedo.mul(&texture.value_at(rec)),
+            None => self.albedo.clone(),
+        }
+    }
 }
@@ -30,7 +30,26 @@ pub trait Material: Send + Sync {
         0.0
     }
 
+    /// BSDF for an arbitrary `scattered` direction, including the cosine term, i.e. the weight
+    /// `attenuation * scattering_pdf` that `scatter` would give that direction, averaged over
+    /// its random choices. Used to connect path vertices; black for specular materials.
+    fn eval(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> Color {
+        BLACK
+    }
+
     fn emitted(&self, _hit_record: &HitRecord) -> Color {
         BLACK
     }
+
+    /// Fraction of light the surface reflects at `rec`, ignoring direction: the colour shown by
+    /// the albedo debug view. Black for materials that only emit or absorb.
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        BLACK
+    }
+
+    /// Whether scattered directions depend on the wavelength of the incoming ray, so that a
+    /// spectral path can only carry on with a single wavelength.
+    fn is_dispersive(&self) -> bool {
+        false
+    }
 }
@@ -300,3 +300,18 @@ impl Add<&Aabb> for &Vec3 {
         rhs + self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_flat_boxes_evenly_by_delta() {
+        let flat = Aabb::from_points(Point3::new(0.0, 0.0, 1.0), Point3::new(2.0, 3.0, 1.0));
+
+        assert_eq!((flat.x().min(), flat.x().max()), (0.0, 2.0));
+        assert!((flat.z().size() - Aabb::DELTA).abs() < 1e-6);
+        assert!((flat.z().min() - (1.0 - Aabb::DELTA / 2.0)).abs() < 1e-6);
+        assert!((flat.z().max() - (1.0 + Aabb::DELTA / 2.0)).abs() < 1e-6);
+    }
+}
@@ -62,22 +62,25 @@ impl Point3 {
 
     /// Generates a random point inside a unit disk on the xy-plane (z=0).
     ///
-    /// Uses rejection sampling to generate points uniformly within
-    /// a disk of radius 1 centered at the origin.
+    /// Maps a 2D sample uniformly onto the disk of radius 1 centered at the origin with Shirley
+    /// and Chiu's concentric mapping, which keeps samples that are evenly spread over the
+    /// square evenly spread over the disk.
     ///
     /// # Returns
     /// A point (x,y,0) where x² + y² < 1
-    ///
-    /// # Performance
-    /// Uses rejection sampling which may require multiple iterations.
-    /// For performance-critical code, consider caching the results.
     pub fn random_in_unit_disk() -> Self {
-        loop {
-            let point = Self::new(random_float(), random_float(), 0.0);
-            if point.length_squared() < 1.0 {
-                return point;
-            }
+        let [u, v] = random_float_2d();
+        let (x, y) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+        if x == 0.0 && y == 0.0 {
+            return Self::new(0.0, 0.0, 0.0);
         }
+
+        let (r, theta) = if x.abs() > y.abs() {
+            (x, 0.25 * PI * (y / x))
+        } else {
+            (y, 0.5 * PI - 0.25 * PI * (x / y))
+        };
+        Self::new(r * theta.cos(), r * theta.sin(), 0.0)
     }
 
     pub fn random_between(min: f32, max: f32) -> Self {
@@ -88,10 +91,6 @@ impl Point3 {
         )
     }
 
-    fn length_squared(&self) -> f32 {
-        self.as_coord().len_squared()
-    }
-
     /// Returns a new point with each coordinate being the minimum of
     /// the corresponding coordinates from this point and another.
     ///
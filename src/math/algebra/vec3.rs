@@ -94,8 +94,7 @@ impl Vec3 {
     /// ```
     #[inline]
     pub fn random_cosine_direction() -> Self {
-        let r1 = random_float();
-        let r2 = random_float();
+        let [r1, r2] = random_float_2d();
         let sqrt_r2 = f32::sqrt(r2);
 
         let phi = 2.0 * PI * r1;
@@ -8,17 +8,60 @@ pub const GRAY: Vec3 = Vec3::new(0.67, 0.7, 0.73);
 pub const SKY_BLUE: Vec3 = Vec3::new(0.5, 0.7, 1.0);
 
 const RGB_RANGE: f32 = 256.0;
-static GAMMA_INTERVAL: Interval = Interval::new(0.0, 0.999);
+static BYTE_INTERVAL: Interval = Interval::new(0.0, 0.999);
+
+/// How the values of an image relate to linear light.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// sRGB encoded colour, as stored by almost every 8-bit image format.
+    #[default]
+    Srgb,
+    /// Values already proportional to light, or non-colour data (normals, roughness, ...)
+    /// that must be read back unchanged.
+    Linear,
+}
+
+impl ColorSpace {
+    /// Decodes an encoded `[0, 1]` component to linear.
+    pub fn to_linear(self, encoded: f32) -> f32 {
+        match self {
+            ColorSpace::Srgb => srgb_to_linear(encoded),
+            ColorSpace::Linear => encoded,
+        }
+    }
+}
+
+/// sRGB electro-optical transfer function (IEC 61966-2-1): encoded value to linear.
+pub fn srgb_to_linear(encoded: f32) -> f32 {
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Inverse of [`srgb_to_linear`]: linear value to sRGB encoded.
+pub fn linear_to_srgb(linear: f32) -> f32 {
+    if linear <= 0.003_130_8 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Relative luminance of linear Rec. 709 / sRGB primaries.
+pub fn luminance(color: &Color) -> f32 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
 
 pub fn color_to_bytes(pixel_color: Color) -> [u8; 3] {
     let to_byte = |component: f32| {
-        let gamma_corrected = if component > 0.0 {
-            component.powf(1.0 / 2.2)
-            // component.sqrt()
+        let encoded = if component > 0.0 {
+            linear_to_srgb(component)
         } else {
             0.0
         };
-        let clamped = GAMMA_INTERVAL.clamp(gamma_corrected);
+        let clamped = BYTE_INTERVAL.clamp(encoded);
 
         (clamped * RGB_RANGE) as u8
     };
@@ -46,3 +89,26 @@ impl Color {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_round_trips() {
+        for i in 0..=255 {
+            let encoded = i as f32 / 255.0;
+            let back = linear_to_srgb(srgb_to_linear(encoded));
+            assert!((encoded - back).abs() < 1e-5, "{} -> {}", encoded, back);
+        }
+    }
+
+    #[test]
+    fn srgb_matches_reference_values() {
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+        // Mid grey: 50% encoded is about 21.4% linear.
+        assert!((srgb_to_linear(0.5) - 0.214_041).abs() < 1e-5);
+        assert_eq!(color_to_bytes(Color::new(1.0, 0.0, 2.0)), [255, 0, 255]);
+    }
+}
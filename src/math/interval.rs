@@ -82,13 +82,13 @@ impl Interval {
 
     #[inline(always)]
     pub fn expand(&self, delta: f32) -> Self {
-        let padding = 2.0 / delta;
+        let padding = delta / 2.0;
         Self::new(self.min - padding, self.max + padding)
     }
 
     #[inline(always)]
     pub fn expand_inplace(&mut self, delta: f32) {
-        let padding = 2.0 / delta;
+        let padding = delta / 2.0;
         self.min -= padding;
         self.max += padding;
     }
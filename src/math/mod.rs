@@ -1,9 +1,11 @@
-use rand::distr::{Distribution, Uniform};
-
 pub(crate) use self::{aabb::Aabb, algebra::*, color::*, interval::Interval, ray::Ray};
 
 pub mod color;
 pub mod pdf;
+pub mod rng;
+pub mod sampler;
+pub mod spectrum;
+pub mod tonemap;
 
 mod aabb;
 mod algebra;
@@ -20,10 +22,19 @@ pub fn degrees_to_radians(degrees: f32) -> f32 {
     degrees.to_radians()
 }
 
+/// Uniform in `[0, 1)`: the next dimension of the sample being taken (see [`sampler`]), or
+/// else from the generator of the current thread (see [`rng::seed_thread`]).
+#[inline]
 pub fn random_float() -> f32 {
-    let between = Uniform::new(0.0, 1.0).unwrap();
-    let mut rng = rand::rng();
-    between.sample(&mut rng)
+    sampler::next_1d()
+}
+
+/// Uniform over `[0, 1)²`, from the next two dimensions of the sample being taken, which
+/// samplers spread evenly over the square. Draws such as points on a light or BSDF directions
+/// should take their two numbers together from here.
+#[inline]
+pub fn random_float_2d() -> [f32; 2] {
+    sampler::next_2d()
 }
 
 #[inline]
@@ -1,23 +1,43 @@
 use super::*;
 
+/// Draws from `ptr1` with probability `weight` and from `ptr2` otherwise; the density is the
+/// matching blend of both.
 pub struct Mixture<'ptr> {
     ptr1: &'ptr dyn Pdf,
     ptr2: &'ptr dyn Pdf,
+    weight: f32,
 }
 
 impl<'ptr> Mixture<'ptr> {
+    /// Even 50/50 blend.
     pub fn new(ptr1: &'ptr dyn Pdf, ptr2: &'ptr dyn Pdf) -> Self {
-        Self { ptr1, ptr2 }
+        Self::weighted(ptr1, ptr2, 0.5)
+    }
+
+    /// `weight` is the probability of sampling `ptr1`, clamped to `[0, 1]`.
+    pub fn weighted(ptr1: &'ptr dyn Pdf, ptr2: &'ptr dyn Pdf, weight: f32) -> Self {
+        Self {
+            ptr1,
+            ptr2,
+            weight: weight.clamp(0.0, 1.0),
+        }
     }
 }
 
 impl Pdf for Mixture<'_> {
     fn value(&self, dir: Vec3) -> f32 {
-        0.5 * self.ptr1.value(dir.clone()) + 0.5 * self.ptr2.value(dir)
+        // Skip a zero-weighted density, whose value may be meaningless.
+        if self.weight >= 1.0 {
+            self.ptr1.value(dir)
+        } else if self.weight <= 0.0 {
+            self.ptr2.value(dir)
+        } else {
+            self.weight * self.ptr1.value(dir.clone()) + (1.0 - self.weight) * self.ptr2.value(dir)
+        }
     }
 
     fn generate(&self) -> Vec3 {
-        if random_float() < 0.5 {
+        if random_float() < self.weight {
             self.ptr1.generate()
         } else {
             self.ptr2.generate()
@@ -11,3 +11,65 @@ pub trait Pdf {
     fn value(&self, direction: Vec3) -> f32;
     fn generate(&self) -> Vec3;
 }
+
+/// Balance heuristic: MIS weight of a sample from `f` when `nf` samples of density `f_pdf` are
+/// combined with `ng` samples of density `g_pdf`.
+pub fn balance_heuristic(nf: f32, f_pdf: f32, ng: f32, g_pdf: f32) -> f32 {
+    let f = nf * f_pdf;
+    let g = ng * g_pdf;
+
+    if f.is_infinite() {
+        return 1.0;
+    }
+    if f + g <= 0.0 {
+        return 0.0;
+    }
+    f / (f + g)
+}
+
+/// Power heuristic with exponent 2, which sharpens the balance heuristic towards whichever
+/// strategy has the larger density.
+pub fn power_heuristic(nf: f32, f_pdf: f32, ng: f32, g_pdf: f32) -> f32 {
+    let f = nf * f_pdf;
+    let g = ng * g_pdf;
+
+    if f.is_infinite() {
+        return 1.0;
+    }
+    if f <= 0.0 {
+        return 0.0;
+    }
+    // In terms of the ratio, so that squaring large densities cannot overflow.
+    1.0 / (1.0 + (g / f).powi(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heuristic_weights_sum_to_one() {
+        for (a, b) in [(0.3, 2.0), (1.0, 1.0), (5.0, 0.0), (1e20, 3e20)] {
+            let balance = balance_heuristic(1.0, a, 1.0, b) + balance_heuristic(1.0, b, 1.0, a);
+            let power = power_heuristic(1.0, a, 1.0, b) + power_heuristic(1.0, b, 1.0, a);
+            assert!((balance - 1.0).abs() < 1e-6);
+            assert!((power - 1.0).abs() < 1e-6);
+        }
+        assert_eq!(power_heuristic(1.0, 0.0, 1.0, 0.0), 0.0);
+        assert_eq!(power_heuristic(1.0, f32::INFINITY, 1.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn weighted_mixture_blends_densities() {
+        let sphere = Sphere::new();
+        let cosine = Cosine::new(UnitVec3::J);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+
+        let mixture = Mixture::weighted(&sphere, &cosine, 0.25);
+        let expected = 0.25 / (4.0 * PI) + 0.75 / PI;
+        assert!((mixture.value(up.clone()) - expected).abs() < 1e-6);
+
+        let only_cosine = Mixture::weighted(&sphere, &cosine, 0.0);
+        assert!((only_cosine.value(up) - 1.0 / PI).abs() < 1e-6);
+    }
+}
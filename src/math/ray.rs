@@ -21,11 +21,17 @@ pub struct Ray {
     orig: Point3,
     dir: Vec3,
     tm: f32,
+    wavelength: Option<f32>,
 }
 
 impl Ray {
     pub fn new(orig: Point3, dir: Vec3) -> Self {
-        Self { orig, dir, tm: 0.0 }
+        Self {
+            orig,
+            dir,
+            tm: 0.0,
+            wavelength: None,
+        }
     }
 
     pub fn from_array(orig: [f32; 3], dir: [f32; 3]) -> Self {
@@ -33,6 +39,7 @@ impl Ray {
             orig: Point3::from_array(orig),
             dir: Vec3::from_array(dir),
             tm: 0.0,
+            wavelength: None,
         }
     }
 
@@ -88,4 +95,15 @@ impl Ray {
         self.tm = tm;
         self
     }
+
+    /// Wavelength in nanometres carried by rays of a spectral render, for materials whose
+    /// behaviour depends on it. `None` when rendering in RGB.
+    pub fn wavelength(&self) -> Option<f32> {
+        self.wavelength
+    }
+
+    pub fn set_wavelength(mut self, wavelength: f32) -> Self {
+        self.wavelength = Some(wavelength);
+        self
+    }
 }
@@ -0,0 +1,123 @@
+//! The random number generator behind [`random_float`](super::random_float), one per thread,
+//! which renders restart at known points so that identical seeds give identical images.
+
+use std::cell::Cell;
+
+/// Stream on which scenes draw their random content, such as Perlin noise tables.
+pub const SCENE_STREAM: u64 = 1 << 62;
+
+/// Stream on which integrators prepare their render, such as photon maps.
+pub const PREPROCESS_STREAM: u64 = SCENE_STREAM + 1;
+
+/// O'Neill's PCG32 (XSH RR 64/32): 64 bits of state, 32 bits of output, and 2^63 independent
+/// streams selected by an odd increment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+    /// The generator of `stream`, started at `seed`, as `pcg32_srandom_r` does.
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        (u64::from(self.next_u32()) << 32) | u64::from(self.next_u32())
+    }
+
+    /// Uniform in `[0, 1)`, with the 24 bits of precision of an `f32` mantissa.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+}
+
+thread_local! {
+    /// Seeded from the operating system until [`seed_thread`] restarts it.
+    static RNG: Cell<Pcg32> = Cell::new(Pcg32::new(rand::random(), rand::random::<u64>() >> 1));
+}
+
+/// Restarts the generator of the current thread on `stream` of `seed`.
+///
+/// The camera does so before every sample, keyed by pixel and sample index, so what a sample
+/// draws does not depend on which thread runs it or on what ran there before. Streams from
+/// [`SCENE_STREAM`] on are reserved for work outside the samples.
+pub fn seed_thread(seed: u64, stream: u64) {
+    // Nearby streams of the same seed would otherwise start from related states.
+    let state = mix(seed ^ mix(stream));
+    RNG.with(|rng| rng.set(Pcg32::new(state, stream)));
+}
+
+pub fn random_u64() -> u64 {
+    RNG.with(|cell| {
+        let mut rng = cell.get();
+        let value = rng.next_u64();
+        cell.set(rng);
+        value
+    })
+}
+
+pub(super) fn next_f32() -> f32 {
+    RNG.with(|cell| {
+        let mut rng = cell.get();
+        let value = rng.next_f32();
+        cell.set(rng);
+        value
+    })
+}
+
+/// The SplitMix64 finalizer, a bijection that scrambles every bit of `x`.
+pub(super) fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_reference_implementation() {
+        // First outputs of pcg32-demo for seed 42 on stream 54.
+        let mut rng = Pcg32::new(42, 54);
+        let outputs: Vec<u32> = (0..6).map(|_| rng.next_u32()).collect();
+        assert_eq!(
+            outputs,
+            [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+        );
+    }
+
+    #[test]
+    fn reseeding_repeats_the_stream() {
+        seed_thread(7, 3);
+        let first: Vec<f32> = (0..4).map(|_| next_f32()).collect();
+        seed_thread(7, 3);
+        let again: Vec<f32> = (0..4).map(|_| next_f32()).collect();
+        seed_thread(7, 4);
+        let other: Vec<f32> = (0..4).map(|_| next_f32()).collect();
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert!(first.iter().all(|x| (0.0..1.0).contains(x)));
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::OnceLock;
+
+use super::{sobol::*, *};
+
+/// Georgiev and Fajardo's blue noise dithered sampling: every pixel takes the same scrambled
+/// Sobol points, rotated (modulo 1) by the value of a blue noise mask at that pixel. Neighbouring
+/// pixels get offsets far apart, so their errors differ in sign and average out to the eye.
+/// The mask is moved by a different amount for every dimension.
+#[derive(Debug, Clone, Copy)]
+pub struct BlueNoiseSampler {
+    seed: u64,
+}
+
+impl BlueNoiseSampler {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    fn offset(&self, pixel: Pixel, dimension: u32) -> f32 {
+        let shift = rng::mix(self.seed ^ u64::from(dimension));
+        let x = (pixel[0] as usize + shift as usize) % MASK_SIZE;
+        let y = (pixel[1] as usize + (shift >> 32) as usize) % MASK_SIZE;
+        mask()[y * MASK_SIZE + x]
+    }
+}
+
+impl Sampler for BlueNoiseSampler {
+    fn get_1d(&self, pixel: Pixel, index: u32, dimension: u32) -> f32 {
+        let value = unit_float(sobol_1d(index, scramble(self.seed, [0, 0], dimension)));
+        rotate(value, self.offset(pixel, dimension))
+    }
+
+    fn get_2d(&self, pixel: Pixel, index: u32, dimension: u32) -> [f32; 2] {
+        let [x, y] = sobol_2d(index, scramble(self.seed, [0, 0], dimension)).map(unit_float);
+        [
+            rotate(x, self.offset(pixel, dimension)),
+            rotate(y, self.offset(pixel, dimension + 1)),
+        ]
+    }
+}
+
+fn rotate(value: f32, offset: f32) -> f32 {
+    let rotated = value + offset;
+    if rotated >= 1.0 {
+        // Rounding may land on 1 itself.
+        (rotated - 1.0).min(1.0 - f32::EPSILON / 2.0)
+    } else {
+        rotated
+    }
+}
+
+const MASK_SIZE: usize = 64;
+
+/// A tileable blue noise mask with every value in `[0, 1)` taken once, built on first use.
+fn mask() -> &'static [f32] {
+    static MASK: OnceLock<Vec<f32>> = OnceLock::new();
+    MASK.get_or_init(|| void_and_cluster(MASK_SIZE, 1.9))
+}
+
+/// Ulichney's void-and-cluster method, reduced to filling voids: pixels are ranked in the
+/// order they are added, each at the point farthest from those before it, i.e. where the sum
+/// of Gaussians of width `sigma` around them, wrapping around the edges, is the least.
+fn void_and_cluster(size: usize, sigma: f64) -> Vec<f32> {
+    let pixels = size * size;
+    let wrapped = |d: usize| d.min(size - d) as f64;
+    let kernel: Vec<f64> = (0..pixels)
+        .map(|p| {
+            let (dx, dy) = (wrapped(p % size), wrapped(p / size));
+            (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+
+    let mut energy = vec![0.0f64; pixels];
+    let mut rank = vec![None; pixels];
+    for r in 0..pixels {
+        let void = (0..pixels)
+            .filter(|&p| rank[p].is_none())
+            .min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap();
+        rank[void] = Some(r);
+
+        let (vx, vy) = (void % size, void / size);
+        for (p, e) in energy.iter_mut().enumerate() {
+            let dx = (p % size + size - vx) % size;
+            let dy = (p / size + size - vy) % size;
+            *e += kernel[dy * size + dx];
+        }
+    }
+
+    rank.into_iter()
+        .map(|r| (r.unwrap() as f32 + 0.5) / pixels as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_has_no_low_frequencies() {
+        let mask = mask();
+        let mut sorted = mask.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        for (r, value) in sorted.iter().enumerate() {
+            assert_eq!(*value, (r as f32 + 0.5) / (MASK_SIZE * MASK_SIZE) as f32);
+        }
+
+        // White noise averages over 4×4 blocks would stray from 0.5 by 0.07 on average.
+        let mut squares = 0.0;
+        let blocks = MASK_SIZE / 4;
+        for by in 0..blocks {
+            for bx in 0..blocks {
+                let mut sum = 0.0;
+                for y in 0..4 {
+                    for x in 0..4 {
+                        sum += mask[(4 * by + y) * MASK_SIZE + 4 * bx + x];
+                    }
+                }
+                squares += (sum / 16.0 - 0.5).powi(2);
+            }
+        }
+        let deviation = (squares / (blocks * blocks) as f32).sqrt();
+        assert!(deviation < 0.03, "{}", deviation);
+    }
+}
@@ -0,0 +1,71 @@
+use super::*;
+
+/// The Halton sequence: dimension `d` of sample `i` is the radical inverse of `i` in the `d`-th
+/// prime base. Its digits are Owen scrambled, so every pixel sees a different randomisation of
+/// the same well spread points. Dimensions past the last prime are drawn at random.
+#[derive(Debug, Clone, Copy)]
+pub struct HaltonSampler {
+    seed: u64,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn get_1d(&self, pixel: Pixel, index: u32, dimension: u32) -> f32 {
+        match PRIMES.get(dimension as usize) {
+            Some(&base) => {
+                scrambled_radical_inverse(base, index, scramble(self.seed, pixel, dimension))
+            }
+            None => rng::next_f32(),
+        }
+    }
+
+    fn get_2d(&self, pixel: Pixel, index: u32, dimension: u32) -> [f32; 2] {
+        [
+            self.get_1d(pixel, index, dimension),
+            self.get_1d(pixel, index, dimension + 1),
+        ]
+    }
+}
+
+const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+/// The digits of `index` in `base` mirrored around the point, each permuted according to the
+/// digits before it.
+fn scrambled_radical_inverse(base: u32, mut index: u32, hash: u32) -> f32 {
+    let base = u64::from(base);
+    let inv_base = 1.0 / base as f64;
+
+    let mut reversed = 0u64;
+    let mut scale = 1.0;
+    let mut level = 0u64;
+    // Past the digits of the index, the zeros are permuted too, down to the f32 precision.
+    while scale > f64::from(f32::EPSILON) / 4.0 {
+        let digit = u64::from(index) % base;
+        index /= base as u32;
+
+        let prefix = (u64::from(hash) << 32 | level) ^ reversed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let digit_hash = rng::mix(prefix) as u32;
+        level += 1;
+        reversed =
+            reversed * base + u64::from(permute_digit(digit as u32, base as u32, digit_hash));
+        scale *= inv_base;
+    }
+    ((reversed as f64 * scale) as f32).min(1.0 - f32::EPSILON / 2.0)
+}
+
+/// Digit `digit` of a random permutation of `0..base`.
+fn permute_digit(digit: u32, base: u32, hash: u32) -> u32 {
+    // A random rotation followed by a random multiplier coprime with a prime base.
+    let multiplier = 1 + (hash >> 16) % (base - 1).max(1);
+    ((u64::from(digit) * u64::from(multiplier) + u64::from(hash & 0xffff)) % u64::from(base)) as u32
+}
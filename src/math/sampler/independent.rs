@@ -0,0 +1,16 @@
+use super::*;
+
+/// Uncorrelated random numbers from the generator of the thread, which the camera restarts for
+/// every sample (see [`rng::seed_thread`]).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IndependentSampler;
+
+impl Sampler for IndependentSampler {
+    fn get_1d(&self, _pixel: Pixel, _index: u32, _dimension: u32) -> f32 {
+        rng::next_f32()
+    }
+
+    fn get_2d(&self, _pixel: Pixel, _index: u32, _dimension: u32) -> [f32; 2] {
+        [rng::next_f32(), rng::next_f32()]
+    }
+}
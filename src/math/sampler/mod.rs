@@ -0,0 +1,253 @@
+//! Samplers choose the numbers each camera sample draws, so that the samples of a pixel cover
+//! the space of pixel positions, lens positions, times, BSDF and light samples evenly rather
+//! than at random.
+//!
+//! While the camera takes a sample, [`random_float`](super::random_float) and
+//! [`random_float_2d`](super::random_float_2d) hand out the dimensions of its sampler one after
+//! the other: the pixel position comes first, then the lens, the time, and whatever the
+//! integrator draws along the path. Outside of samples, such as while building the scene or
+//! tracing photons, they draw from the generator of the thread (see [`rng`]).
+
+use std::{cell::RefCell, fmt, str::FromStr, sync::Arc};
+
+use super::rng;
+
+pub use self::{
+    blue_noise::BlueNoiseSampler, halton::HaltonSampler, independent::IndependentSampler,
+    sobol::SobolSampler, stratified::StratifiedSampler,
+};
+
+mod blue_noise;
+mod halton;
+mod independent;
+mod sobol;
+mod stratified;
+
+/// Column and row of a pixel.
+pub type Pixel = [u32; 2];
+
+/// The values of the samples of every pixel, in `[0, 1)`.
+///
+/// A sample is a point with as many dimensions as it draws, identified by its pixel and its
+/// `index` among that pixel's samples. Dimensions drawn together with [`Sampler::get_2d`] are
+/// spread evenly over the square, those drawn apart are not correlated with each other.
+pub trait Sampler: Send + Sync {
+    fn get_1d(&self, pixel: Pixel, index: u32, dimension: u32) -> f32;
+
+    /// Dimensions `dimension` and `dimension + 1`.
+    fn get_2d(&self, pixel: Pixel, index: u32, dimension: u32) -> [f32; 2];
+}
+
+/// The samplers selectable by name, e.g. from the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SamplerKind {
+    /// Uncorrelated random numbers.
+    Independent,
+    /// Correlated multi-jittered samples: every dimension split into as many strata as there
+    /// are samples.
+    Stratified,
+    /// The Halton sequence, Owen scrambled per pixel.
+    Halton,
+    /// Pairs of dimensions of the Sobol sequence, Owen scrambled and shuffled per pixel.
+    #[default]
+    Sobol,
+    /// The same Sobol points in every pixel, shifted by a blue noise mask, so that the error
+    /// left at low sample counts looks like fine grain rather than blotches.
+    BlueNoise,
+}
+
+impl SamplerKind {
+    /// The sampler for `samples_per_pixel` samples per pixel, scrambled according to `seed`.
+    pub fn build(self, samples_per_pixel: u32, seed: u64) -> Arc<dyn Sampler> {
+        match self {
+            SamplerKind::Independent => Arc::new(IndependentSampler),
+            SamplerKind::Stratified => Arc::new(StratifiedSampler::new(samples_per_pixel, seed)),
+            SamplerKind::Halton => Arc::new(HaltonSampler::new(seed)),
+            SamplerKind::Sobol => Arc::new(SobolSampler::new(seed)),
+            SamplerKind::BlueNoise => Arc::new(BlueNoiseSampler::new(seed)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SamplerKind::Independent => "independent",
+            SamplerKind::Stratified => "stratified",
+            SamplerKind::Halton => "halton",
+            SamplerKind::Sobol => "sobol",
+            SamplerKind::BlueNoise => "blue-noise",
+        }
+    }
+}
+
+impl FromStr for SamplerKind {
+    type Err = String;
+
+    /// Accepts `independent`, `stratified`, `halton`, `sobol` and `blue-noise`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            SamplerKind::Independent,
+            SamplerKind::Stratified,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+            SamplerKind::BlueNoise,
+        ]
+        .into_iter()
+        .find(|kind| kind.name() == s.trim())
+        .ok_or_else(|| {
+            format!(
+                "Unknown sampler: '{}' (expected independent, stratified, halton, sobol or \
+                 blue-noise)",
+                s
+            )
+        })
+    }
+}
+
+impl fmt::Display for SamplerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The sample being taken on this thread and the next of its dimensions to hand out.
+struct Sample {
+    sampler: Arc<dyn Sampler>,
+    pixel: Pixel,
+    index: u32,
+    dimension: u32,
+}
+
+thread_local! {
+    static SAMPLE: RefCell<Option<Sample>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, in which the random numbers drawn on this thread are the dimensions of sample
+/// `index` of `pixel`, in order.
+pub fn with_sample<R>(
+    sampler: &Arc<dyn Sampler>,
+    pixel: Pixel,
+    index: u32,
+    f: impl FnOnce() -> R,
+) -> R {
+    let previous = SAMPLE.replace(Some(Sample {
+        sampler: Arc::clone(sampler),
+        pixel,
+        index,
+        dimension: 0,
+    }));
+    let result = f();
+    SAMPLE.set(previous);
+    result
+}
+
+pub(super) fn next_1d() -> f32 {
+    SAMPLE.with_borrow_mut(|sample| match sample {
+        Some(sample) => {
+            let value = sample
+                .sampler
+                .get_1d(sample.pixel, sample.index, sample.dimension);
+            sample.dimension += 1;
+            value
+        }
+        None => rng::next_f32(),
+    })
+}
+
+pub(super) fn next_2d() -> [f32; 2] {
+    SAMPLE.with_borrow_mut(|sample| match sample {
+        Some(sample) => {
+            let value = sample
+                .sampler
+                .get_2d(sample.pixel, sample.index, sample.dimension);
+            sample.dimension += 2;
+            value
+        }
+        None => [rng::next_f32(), rng::next_f32()],
+    })
+}
+
+/// A 32-bit hash of a dimension of a pixel, from which samplers derive their scrambles.
+fn scramble(seed: u64, pixel: Pixel, dimension: u32) -> u32 {
+    let key = (u64::from(pixel[1]) << 32 | u64::from(pixel[0])) ^ u64::from(dimension) << 48;
+    rng::mix(seed ^ rng::mix(key)) as u32
+}
+
+/// The fraction `bits / 2^32`, rounded down to the precision of an `f32`.
+fn unit_float(bits: u32) -> f32 {
+    (bits >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sorts the values and checks each falls into its own one of `values.len()` strata.
+    fn assert_stratified(mut values: Vec<f32>) {
+        let n = values.len() as f32;
+        values.sort_by(f32::total_cmp);
+        for (stratum, value) in values.into_iter().enumerate() {
+            assert_eq!((value * n) as usize, stratum, "{}", value);
+        }
+    }
+
+    #[test]
+    fn stratifies_every_dimension() {
+        // Halton dimensions are stratified by powers of their base, 2 and 3 for the first two.
+        for (kind, samples, dimensions) in [
+            (SamplerKind::Stratified, 10, &[0, 1, 7, 40][..]),
+            (SamplerKind::Halton, 16, &[0]),
+            (SamplerKind::Halton, 9, &[1]),
+            (SamplerKind::Sobol, 16, &[0, 1, 7, 40]),
+        ] {
+            let sampler = kind.build(samples, 3);
+            for &dimension in dimensions {
+                let values = (0..samples)
+                    .map(|i| sampler.get_1d([5, 2], i, dimension))
+                    .collect();
+                assert_stratified(values);
+            }
+        }
+    }
+
+    #[test]
+    fn spreads_pairs_over_the_square() {
+        // 16 points in a 4×4 grid of cells, one in each.
+        for kind in [SamplerKind::Stratified, SamplerKind::Sobol] {
+            let sampler = kind.build(16, 3);
+            for dimension in [0, 2, 11] {
+                let mut cells: Vec<usize> = (0..16)
+                    .map(|i| {
+                        let [x, y] = sampler.get_2d([1, 4], i, dimension);
+                        assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+                        4 * (y * 4.0) as usize + (x * 4.0) as usize
+                    })
+                    .collect();
+                cells.sort();
+                assert_eq!(cells, (0..16).collect::<Vec<_>>(), "{}", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn hands_out_dimensions_in_order() {
+        let sampler = SamplerKind::Halton.build(8, 0);
+        let drawn = with_sample(&sampler, [3, 4], 5, || {
+            [next_1d(), next_2d()[0], next_2d()[1], next_1d()]
+        });
+        let expected = [
+            sampler.get_1d([3, 4], 5, 0),
+            sampler.get_2d([3, 4], 5, 1)[0],
+            sampler.get_2d([3, 4], 5, 3)[1],
+            sampler.get_1d([3, 4], 5, 5),
+        ];
+        assert_eq!(drawn, expected);
+    }
+
+    #[test]
+    fn parses_names() {
+        for kind in ["independent", "stratified", "halton", "sobol", "blue-noise"] {
+            assert_eq!(kind.parse::<SamplerKind>().unwrap().to_string(), kind);
+        }
+        assert!("random".parse::<SamplerKind>().is_err());
+    }
+}
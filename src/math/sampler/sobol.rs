@@ -0,0 +1,75 @@
+use super::*;
+
+/// Burley's shuffled and Owen scrambled Sobol points, padded: every 1D or 2D draw takes the
+/// first one or two dimensions of the Sobol sequence, with its own scramble and its own order
+/// of the samples. Each pair is stratified like the best 2D point sets, for any number of
+/// dimensions, and any power of two samples are stratified in every dimension.
+#[derive(Debug, Clone, Copy)]
+pub struct SobolSampler {
+    seed: u64,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn get_1d(&self, pixel: Pixel, index: u32, dimension: u32) -> f32 {
+        unit_float(sobol_1d(index, scramble(self.seed, pixel, dimension)))
+    }
+
+    fn get_2d(&self, pixel: Pixel, index: u32, dimension: u32) -> [f32; 2] {
+        sobol_2d(index, scramble(self.seed, pixel, dimension)).map(unit_float)
+    }
+}
+
+/// Scrambled sample `index` of the first Sobol dimension, as a 32-bit fraction.
+pub(super) fn sobol_1d(index: u32, hash: u32) -> u32 {
+    let index = nested_uniform_scramble(index, hash);
+    nested_uniform_scramble(index.reverse_bits(), mix32(hash, 1))
+}
+
+/// Scrambled sample `index` of the first two Sobol dimensions, as 32-bit fractions.
+pub(super) fn sobol_2d(index: u32, hash: u32) -> [u32; 2] {
+    let index = nested_uniform_scramble(index, hash);
+    [
+        nested_uniform_scramble(index.reverse_bits(), mix32(hash, 1)),
+        nested_uniform_scramble(second_dimension(index), mix32(hash, 2)),
+    ]
+}
+
+/// The second Sobol dimension, whose direction numbers are the rows of Pascal's triangle mod 2.
+fn second_dimension(mut index: u32) -> u32 {
+    let mut direction = 1u32 << 31;
+    let mut value = 0;
+    while index != 0 {
+        if index & 1 != 0 {
+            value ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    value
+}
+
+/// Owen scrambling of the bits of `x`, most significant first: each bit is flipped or not
+/// according to the bits above it.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+/// Burley's improved hash in which every bit depends only on the bits below it.
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x ^= x.wrapping_mul(0x3d20adea);
+    x = x.wrapping_add(seed);
+    x = x.wrapping_mul((seed >> 16) | 1);
+    x ^= x.wrapping_mul(0x05526c56);
+    x ^= x.wrapping_mul(0x53a22864);
+    x
+}
+
+fn mix32(hash: u32, salt: u64) -> u32 {
+    rng::mix(u64::from(hash) | salt << 32) as u32
+}
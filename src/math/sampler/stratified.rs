@@ -0,0 +1,117 @@
+use super::*;
+
+/// Kensler's correlated multi-jittered sampling, for any number of samples.
+///
+/// A single dimension is split into one stratum per sample. A pair of dimensions is split into
+/// a grid of about `√n × √n` cells, with one sample per cell and, within them, one per row and
+/// column of the finer `n × n` grid. The strata are shuffled independently for every pixel and
+/// dimension.
+#[derive(Debug, Clone, Copy)]
+pub struct StratifiedSampler {
+    samples: u32,
+    seed: u64,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Self {
+        Self {
+            samples: samples_per_pixel.max(1),
+            seed,
+        }
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn get_1d(&self, pixel: Pixel, index: u32, dimension: u32) -> f32 {
+        let pattern = scramble(self.seed, pixel, dimension);
+        let stratum = permute(index % self.samples, self.samples, pattern);
+        let jitter = random_float(index, pattern.wrapping_mul(0xa399d265));
+        ((stratum as f32 + jitter) / self.samples as f32).min(ONE_MINUS_EPSILON)
+    }
+
+    fn get_2d(&self, pixel: Pixel, index: u32, dimension: u32) -> [f32; 2] {
+        let n = self.samples;
+        let pattern = scramble(self.seed, pixel, dimension);
+
+        let columns = (n as f32).sqrt() as u32;
+        let rows = n.div_ceil(columns);
+        let s = permute(index % n, n, pattern.wrapping_mul(0x51633e2d));
+
+        let (column, row) = (s % columns, s / columns);
+        let sx = permute(column, columns, pattern.wrapping_mul(0x68bc21eb));
+        let sy = permute(row, rows, pattern.wrapping_mul(0x02e5be93));
+        let jx = random_float(s, pattern.wrapping_mul(0x967a889b));
+        let jy = random_float(s, pattern.wrapping_mul(0x368cc8b7));
+
+        let x = (column as f32 + (sy as f32 + jx) / rows as f32) / columns as f32;
+        let y = (row as f32 + (sx as f32 + jy) / columns as f32) / rows as f32;
+        [x.min(ONE_MINUS_EPSILON), y.min(ONE_MINUS_EPSILON)]
+    }
+}
+
+const ONE_MINUS_EPSILON: f32 = 1.0 - f32::EPSILON / 2.0;
+
+/// Element `i` of a random permutation of `0..len` chosen by `pattern`.
+fn permute(mut i: u32, len: u32, pattern: u32) -> u32 {
+    let mut w = len - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    // Permute within the next power of two and walk the cycle until back in range.
+    loop {
+        i ^= pattern;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= pattern >> 16;
+        i ^= (i & w) >> 4;
+        i ^= pattern >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= pattern >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | pattern >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < len {
+            break;
+        }
+    }
+    ((u64::from(i) + u64::from(pattern)) % u64::from(len)) as u32
+}
+
+/// A hash of `i` and `pattern` in `[0, 1)`.
+fn random_float(mut i: u32, pattern: u32) -> f32 {
+    i ^= pattern;
+    i ^= i >> 17;
+    i ^= i >> 10;
+    i = i.wrapping_mul(0xb36534e5);
+    i ^= i >> 12;
+    i ^= i >> 21;
+    i = i.wrapping_mul(0x93fc4795);
+    i ^= 0xdf6e307f;
+    i ^= i >> 17;
+    i = i.wrapping_mul(1 | pattern >> 18);
+    unit_float(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutes_any_length() {
+        for len in [1, 2, 7, 10, 64, 100] {
+            let mut image: Vec<u32> = (0..len).map(|i| permute(i, len, 0x1234_5678)).collect();
+            image.sort();
+            assert_eq!(image, (0..len).collect::<Vec<_>>());
+        }
+    }
+}
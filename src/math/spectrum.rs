@@ -0,0 +1,327 @@
+//! Spectral quantities sampled at a handful of wavelengths, and their conversion to and from
+//! the linear sRGB [`Color`] used everywhere else.
+
+use std::{
+    array,
+    ops::{Add, AddAssign, Mul},
+    sync::OnceLock,
+};
+
+use super::{Color, Dimensional};
+
+/// Shortest wavelength rendered, in nanometres.
+pub const LAMBDA_MIN: f32 = 380.0;
+/// Longest wavelength rendered, in nanometres.
+pub const LAMBDA_MAX: f32 = 720.0;
+
+/// Wavelengths traced together along a path: one hero and its evenly spaced rotations.
+pub const SPECTRUM_SAMPLES: usize = 4;
+
+/// Wavelengths carried by a path, following the hero wavelength scheme of Wilkie et al.
+///
+/// The hero wavelength is drawn uniformly over the visible range and the others are spread
+/// evenly from it, wrapping around the range, so one path estimates several wavelengths at
+/// once. When the path meets something that only the hero can follow, such as refraction
+/// through a dispersive medium, [`terminate_secondary`](Self::terminate_secondary) drops the
+/// rest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampledWavelengths {
+    lambda: [f32; SPECTRUM_SAMPLES],
+    pdf: [f32; SPECTRUM_SAMPLES],
+}
+
+impl SampledWavelengths {
+    /// Wavelengths for a uniform random number `u` in `[0, 1)`.
+    pub fn sample(u: f32) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let lambda = array::from_fn(|i| {
+            let offset = (u + i as f32 / SPECTRUM_SAMPLES as f32).fract();
+            LAMBDA_MIN + offset * range
+        });
+
+        Self {
+            lambda,
+            pdf: [1.0 / range; SPECTRUM_SAMPLES],
+        }
+    }
+
+    /// The wavelength whose direction the path follows.
+    pub fn hero(&self) -> f32 {
+        self.lambda[0]
+    }
+
+    pub fn lambda(&self) -> &[f32; SPECTRUM_SAMPLES] {
+        &self.lambda
+    }
+
+    /// Keeps only the hero wavelength, which then stands for all of them.
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+        for pdf in self.pdf.iter_mut().skip(1) {
+            *pdf = 0.0;
+        }
+        self.pdf[0] /= SPECTRUM_SAMPLES as f32;
+    }
+
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.0)
+    }
+
+    /// Converts radiance estimated at these wavelengths to linear sRGB.
+    ///
+    /// The result is white balanced so that a flat spectrum of 1 comes out as `(1, 1, 1)`,
+    /// which keeps grey materials and white lights neutral as in RGB rendering.
+    pub fn to_rgb(&self, radiance: &SampledSpectrum) -> Color {
+        let mut xyz = [0.0; 3];
+        for i in 0..SPECTRUM_SAMPLES {
+            if self.pdf[i] == 0.0 {
+                continue;
+            }
+            let weight = radiance.0[i] / (self.pdf[i] * SPECTRUM_SAMPLES as f32);
+            for (sum, matching) in xyz.iter_mut().zip(cie_xyz(self.lambda[i])) {
+                *sum += weight * matching;
+            }
+        }
+
+        let rgb = xyz_to_linear_srgb(xyz);
+        let white = flat_spectrum_rgb();
+        Color::new(rgb[0] / white[0], rgb[1] / white[1], rgb[2] / white[2])
+    }
+}
+
+/// A spectral quantity (radiance, reflectance, throughput) at the wavelengths of a
+/// [`SampledWavelengths`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampledSpectrum([f32; SPECTRUM_SAMPLES]);
+
+impl SampledSpectrum {
+    pub const ZERO: Self = Self([0.0; SPECTRUM_SAMPLES]);
+    pub const ONE: Self = Self([1.0; SPECTRUM_SAMPLES]);
+
+    pub fn new(values: [f32; SPECTRUM_SAMPLES]) -> Self {
+        Self(values)
+    }
+
+    /// Upsamples an RGB colour to a smooth spectrum and evaluates it at `wavelengths`.
+    ///
+    /// Uses the method of Smits, which builds the spectrum from white plus at most one of
+    /// cyan, magenta or yellow and one of red, green or blue. Reflectances in `[0, 1]` stay
+    /// within `[0, 1]`, and scaling the colour scales the spectrum, so emission above 1 works
+    /// too.
+    pub fn from_rgb(rgb: &Color, wavelengths: &SampledWavelengths) -> Self {
+        Self(
+            wavelengths
+                .lambda
+                .map(|lambda| rgb_to_spectrum(rgb, lambda)),
+        )
+    }
+
+    pub fn values(&self) -> &[f32; SPECTRUM_SAMPLES] {
+        &self.0
+    }
+
+    pub fn max_value(&self) -> f32 {
+        self.0.iter().copied().fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    pub fn is_black(&self) -> bool {
+        self.0.iter().all(|&x| x == 0.0)
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.0.iter().all(|x| x.is_finite())
+    }
+}
+
+impl Add for SampledSpectrum {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+}
+
+impl AddAssign for SampledSpectrum {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self(array::from_fn(|i| self.0[i] * other.0[i]))
+    }
+}
+
+impl Mul<SampledSpectrum> for f32 {
+    type Output = SampledSpectrum;
+
+    fn mul(self, spectrum: SampledSpectrum) -> SampledSpectrum {
+        SampledSpectrum(spectrum.0.map(|x| self * x))
+    }
+}
+
+// ─────────────────────────────
+// RGB to spectrum
+
+/// Number of equal bins the basis spectra of [`rgb_to_spectrum`] are tabulated in, spanning
+/// [`LAMBDA_MIN`] to [`LAMBDA_MAX`].
+const SMITS_BINS: usize = 10;
+
+#[rustfmt::skip]
+mod smits {
+    use super::SMITS_BINS;
+
+    pub const WHITE: [f32; SMITS_BINS] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+    pub const CYAN: [f32; SMITS_BINS] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+    pub const MAGENTA: [f32; SMITS_BINS] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+    pub const YELLOW: [f32; SMITS_BINS] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+    pub const RED: [f32; SMITS_BINS] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+    pub const GREEN: [f32; SMITS_BINS] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+    pub const BLUE: [f32; SMITS_BINS] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+}
+
+/// Value at `lambda` of the spectrum Smits' method builds for `rgb`.
+fn rgb_to_spectrum(rgb: &Color, lambda: f32) -> f32 {
+    let bin = ((lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN) * SMITS_BINS as f32) as usize;
+    let bin = bin.min(SMITS_BINS - 1);
+    let (r, g, b) = (rgb.x(), rgb.y(), rgb.z());
+
+    use smits::*;
+    if r <= g && r <= b {
+        let base = r * WHITE[bin];
+        if g <= b {
+            base + (g - r) * CYAN[bin] + (b - g) * BLUE[bin]
+        } else {
+            base + (b - r) * CYAN[bin] + (g - b) * GREEN[bin]
+        }
+    } else if g <= r && g <= b {
+        let base = g * WHITE[bin];
+        if r <= b {
+            base + (r - g) * MAGENTA[bin] + (b - r) * BLUE[bin]
+        } else {
+            base + (b - g) * MAGENTA[bin] + (r - b) * RED[bin]
+        }
+    } else {
+        let base = b * WHITE[bin];
+        if r <= g {
+            base + (r - b) * YELLOW[bin] + (g - r) * GREEN[bin]
+        } else {
+            base + (g - b) * YELLOW[bin] + (r - g) * RED[bin]
+        }
+    }
+}
+
+// ─────────────────────────────
+// Spectrum to RGB
+
+/// CIE 1931 colour matching functions, from the multi-lobe fit of Wyman, Sloan and Shirley.
+fn cie_xyz(lambda: f32) -> [f32; 3] {
+    let lobe = |mu: f32, below: f32, above: f32| {
+        let sigma = if lambda < mu { below } else { above };
+        (-0.5 * ((lambda - mu) / sigma).powi(2)).exp()
+    };
+
+    [
+        1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+            - 0.065 * lobe(501.1, 20.4, 26.2),
+        0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+        1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+    ]
+}
+
+fn xyz_to_linear_srgb([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    ]
+}
+
+/// Linear sRGB of a spectrum equal to 1 over the rendered range, before white balancing.
+fn flat_spectrum_rgb() -> &'static [f32; 3] {
+    static WHITE: OnceLock<[f32; 3]> = OnceLock::new();
+    WHITE.get_or_init(|| {
+        const STEPS: usize = 3400;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / STEPS as f32;
+
+        let mut xyz = [0.0; 3];
+        for i in 0..STEPS {
+            let lambda = LAMBDA_MIN + (i as f32 + 0.5) * step;
+            for (sum, matching) in xyz.iter_mut().zip(cie_xyz(lambda)) {
+                *sum += step * matching;
+            }
+        }
+        xyz_to_linear_srgb(xyz)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::random_float;
+
+    /// Averages the RGB estimate of a spectrum over many wavelength samples.
+    fn project(rgb: &Color, samples: usize) -> Color {
+        let mut sum = Color::new(0.0, 0.0, 0.0);
+        for i in 0..samples {
+            let wavelengths =
+                SampledWavelengths::sample((i as f32 + random_float()) / samples as f32);
+            sum += wavelengths.to_rgb(&SampledSpectrum::from_rgb(rgb, &wavelengths));
+        }
+        (1.0 / samples as f32) * sum
+    }
+
+    #[test]
+    fn rgb_round_trips_through_spectrum() {
+        for rgb in [
+            Color::new(1.0, 1.0, 1.0),
+            Color::new(1.0, 0.0, 0.0),
+            Color::new(0.12, 0.45, 0.15),
+            Color::new(0.2, 0.5, 0.8),
+            Color::new(15.0, 15.0, 15.0),
+        ] {
+            let back = project(&rgb, 20_000);
+            let scale = rgb.x().max(rgb.y()).max(rgb.z());
+            for (a, b) in [
+                (back.x(), rgb.x()),
+                (back.y(), rgb.y()),
+                (back.z(), rgb.z()),
+            ] {
+                assert!(
+                    (a - b).abs() < 0.03 * scale,
+                    "{:?} came back as {:?}",
+                    rgb,
+                    back
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hero_wavelengths_cover_the_range() {
+        let wavelengths = SampledWavelengths::sample(0.9);
+        let lambda = wavelengths.lambda();
+        assert_eq!(wavelengths.hero(), lambda[0]);
+        for l in lambda {
+            assert!((LAMBDA_MIN..LAMBDA_MAX).contains(l));
+        }
+        assert!((lambda[1] - (LAMBDA_MIN + 0.15 * (LAMBDA_MAX - LAMBDA_MIN))).abs() < 1e-3);
+    }
+
+    #[test]
+    fn terminating_secondary_wavelengths_keeps_the_estimate() {
+        let mut wavelengths = SampledWavelengths::sample(0.3);
+        let radiance = SampledSpectrum::new([2.0, 0.0, 0.0, 0.0]);
+        let before = wavelengths.to_rgb(&radiance);
+
+        wavelengths.terminate_secondary();
+        assert!(wavelengths.secondary_terminated());
+        let after = wavelengths.to_rgb(&radiance);
+        assert!((after.y() - SPECTRUM_SAMPLES as f32 * before.y()).abs() < 1e-4);
+    }
+}
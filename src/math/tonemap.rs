@@ -0,0 +1,241 @@
+use std::{fmt, str::FromStr};
+
+use super::{Color, Dimensional};
+
+/// Curve mapping linear HDR radiance into the displayable `[0, 1]` range.
+///
+/// The result is still linear; gamma encoding is left to the writer (see
+/// [`color_to_bytes`](super::color::color_to_bytes)).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMapper {
+    /// Plain clamp, the historical behaviour.
+    #[default]
+    Clamp,
+    /// `c / (1 + c)`.
+    Reinhard,
+    /// Reinhard that reaches 1.0 at `white` instead of at infinity.
+    ExtendedReinhard { white: f32 },
+    /// Narkowicz's fit of the ACES filmic reference transform.
+    Aces,
+    /// John Hable's Uncharted 2 filmic curve.
+    Hable,
+    /// Troy Sobotka's AgX with the default contrast look.
+    Agx,
+}
+
+/// A [`ToneMapper`] with an exposure adjustment in stops, applied before the curve.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ToneMap {
+    operator: ToneMapper,
+    exposure: f32,
+}
+
+impl ToneMap {
+    pub fn new(operator: ToneMapper, exposure: f32) -> Self {
+        Self { operator, exposure }
+    }
+
+    pub fn operator(&self) -> ToneMapper {
+        self.operator
+    }
+
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    pub fn map(&self, color: &Color) -> Color {
+        let scale = self.exposure.exp2();
+        let [r, g, b] =
+            self.operator
+                .map([scale * color.x(), scale * color.y(), scale * color.z()]);
+        Color::new(r, g, b)
+    }
+
+    /// Tone maps an interleaved RGB buffer, as returned by `Camera::render`, in place.
+    pub fn apply(&self, pixels: &mut [f32]) {
+        let scale = self.exposure.exp2();
+
+        for rgb in pixels.chunks_exact_mut(3) {
+            let mapped = self
+                .operator
+                .map([scale * rgb[0], scale * rgb[1], scale * rgb[2]]);
+            rgb.copy_from_slice(&mapped);
+        }
+    }
+}
+
+impl ToneMapper {
+    pub fn map(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let rgb = rgb.map(|c| if c.is_finite() { c.max(0.0) } else { 0.0 });
+
+        match *self {
+            ToneMapper::Clamp => rgb.map(|c| c.min(1.0)),
+            ToneMapper::Reinhard => rgb.map(|c| c / (1.0 + c)),
+            ToneMapper::ExtendedReinhard { white } => {
+                let white_sq = (white * white).max(f32::EPSILON);
+                rgb.map(|c| (c * (1.0 + c / white_sq) / (1.0 + c)).min(1.0))
+            }
+            ToneMapper::Aces => rgb.map(|c| {
+                let mapped = (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14);
+                mapped.clamp(0.0, 1.0)
+            }),
+            ToneMapper::Hable => {
+                const EXPOSURE_BIAS: f32 = 2.0;
+                const WHITE: f32 = 11.2;
+                let white_scale = 1.0 / hable(WHITE);
+                rgb.map(|c| (hable(EXPOSURE_BIAS * c) * white_scale).min(1.0))
+            }
+            ToneMapper::Agx => agx(rgb),
+        }
+    }
+}
+
+fn hable(x: f32) -> f32 {
+    const A: f32 = 0.15;
+    const B: f32 = 0.50;
+    const C: f32 = 0.10;
+    const D: f32 = 0.20;
+    const E: f32 = 0.02;
+    const F: f32 = 0.30;
+
+    ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F
+}
+
+fn agx(rgb: [f32; 3]) -> [f32; 3] {
+    // Rows of the inset and outset matrices for linear sRGB input.
+    const INSET: [[f32; 3]; 3] = [
+        [0.842_479_1, 0.078_433_6, 0.079_223_75],
+        [0.042_328_24, 0.878_468_6, 0.079_166_13],
+        [0.042_375_65, 0.078_433_6, 0.879_143],
+    ];
+    const OUTSET: [[f32; 3]; 3] = [
+        [1.196_879, -0.098_020_88, -0.099_029_74],
+        [-0.052_896_85, 1.151_903_1, -0.098_961_18],
+        [-0.052_971_64, -0.098_043_45, 1.151_073_7],
+    ];
+    const MIN_EV: f32 = -12.473_93;
+    const MAX_EV: f32 = 4.026_069;
+
+    let apply = |m: &[[f32; 3]; 3], v: [f32; 3]| -> [f32; 3] {
+        m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+    };
+
+    let encoded = apply(&INSET, rgb).map(|c| {
+        let ev = c.max(f32::MIN_POSITIVE).log2().clamp(MIN_EV, MAX_EV);
+        agx_contrast((ev - MIN_EV) / (MAX_EV - MIN_EV))
+    });
+
+    // The look is defined on a 2.2 power encoded signal; return to linear.
+    apply(&OUTSET, encoded).map(|c| c.max(0.0).powf(2.2).min(1.0))
+}
+
+/// Polynomial approximation of the AgX default contrast sigmoid.
+fn agx_contrast(x: f32) -> f32 {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+
+    15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+        - 0.00232
+}
+
+impl FromStr for ToneMapper {
+    type Err = String;
+
+    /// Accepts `clamp`, `reinhard`, `reinhard-extended[:white]`, `aces`, `hable` and `agx`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = match s.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (s, None),
+        };
+
+        let operator = match name.trim().to_ascii_lowercase().as_str() {
+            "clamp" | "none" => ToneMapper::Clamp,
+            "reinhard" => ToneMapper::Reinhard,
+            "reinhard-extended" | "extended-reinhard" => {
+                let white = match argument {
+                    Some(white) => white
+                        .trim()
+                        .parse::<f32>()
+                        .map_err(|_| format!("Invalid white point: '{}'", white))?,
+                    None => 4.0,
+                };
+                return Ok(ToneMapper::ExtendedReinhard { white });
+            }
+            "aces" => ToneMapper::Aces,
+            "hable" | "uncharted2" => ToneMapper::Hable,
+            "agx" => ToneMapper::Agx,
+            _ => return Err(format!("Unknown tone mapper: '{}'", s)),
+        };
+
+        match argument {
+            Some(_) => Err(format!("'{}' takes no argument", name)),
+            None => Ok(operator),
+        }
+    }
+}
+
+impl fmt::Display for ToneMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToneMapper::Clamp => write!(f, "clamp"),
+            ToneMapper::Reinhard => write!(f, "reinhard"),
+            ToneMapper::ExtendedReinhard { white } => write!(f, "reinhard-extended:{}", white),
+            ToneMapper::Aces => write!(f, "aces"),
+            ToneMapper::Hable => write!(f, "hable"),
+            ToneMapper::Agx => write!(f, "agx"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERATORS: [ToneMapper; 6] = [
+        ToneMapper::Clamp,
+        ToneMapper::Reinhard,
+        ToneMapper::ExtendedReinhard { white: 4.0 },
+        ToneMapper::Aces,
+        ToneMapper::Hable,
+        ToneMapper::Agx,
+    ];
+
+    #[test]
+    fn maps_into_display_range_monotonically() {
+        for operator in OPERATORS {
+            let mut previous = -1.0;
+            for step in 0..200 {
+                let x = step as f32 * 0.25;
+                let [r, g, b] = operator.map([x, x, x]);
+
+                assert!((0.0..=1.0).contains(&r), "{}: {} -> {}", operator, x, r);
+                assert!(r >= previous - 1e-4, "{}: not monotonic at {}", operator, x);
+                assert!((r - g).abs() < 1e-3 && (g - b).abs() < 1e-3);
+                previous = r;
+            }
+        }
+    }
+
+    #[test]
+    fn extended_reinhard_reaches_white() {
+        let [r, _, _] = ToneMapper::ExtendedReinhard { white: 7.0 }.map([7.0; 3]);
+        assert!((r - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn parses_names() {
+        for operator in OPERATORS {
+            assert_eq!(operator.to_string().parse::<ToneMapper>(), Ok(operator));
+        }
+        assert!("filmic".parse::<ToneMapper>().is_err());
+        assert!("aces:2".parse::<ToneMapper>().is_err());
+    }
+
+    #[test]
+    fn exposure_is_in_stops() {
+        let tonemap = ToneMap::new(ToneMapper::Clamp, -1.0);
+        let mut pixels = [1.0, 0.5, 0.25];
+        tonemap.apply(&mut pixels);
+        assert_eq!(pixels, [0.5, 0.25, 0.125]);
+    }
+}
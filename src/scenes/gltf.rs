@@ -0,0 +1,1203 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    f32::consts::PI,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::*;
+use crate::{
+    math::Aabb,
+    util::{Json, RtwImage},
+};
+
+/// Column-major 4x4 matrix, as stored by glTF.
+type Mat4 = [[f32; 4]; 4];
+
+const IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+
+/// Punctual lights become small emissive spheres; their radius relative to the scene size.
+const POINT_LIGHT_RADIUS: f32 = 0.005;
+
+/// A scene imported from a glTF 2.0 asset (`.gltf` with embedded or external buffers, or `.glb`).
+///
+/// - Nodes are flattened: each mesh primitive becomes a [`TriangleMesh`] with the node's world
+///   transform baked into its vertices.
+/// - `pbrMetallicRoughness` materials map onto [`DiffuseLight`] (emissive), [`Dielectric`]
+///   (`KHR_materials_transmission`, dispersive with `KHR_materials_dispersion`), [`Metal`]
+///   (metallic ≥ 0.5, fuzz from roughness) or [`Lambertian`], both tinted by the base colour
+///   texture times its factor.
+/// - The first perspective camera in the scene drives the [`CameraBuilder`]; without one the
+///   camera frames the scene bounds.
+/// - `KHR_lights_punctual` point and spot lights become small emissive spheres of matching
+///   intensity, and are also added to the importance sampling lights with emissive meshes.
+///   Spot lights lose their cone and shine in every direction. Directional lights have no
+///   equivalent and are skipped.
+///
+/// What is skipped or approximated is listed in [`warnings`](Self::warnings).
+pub struct GltfScene {
+    camera: CameraBuilder,
+    world: Vec<Arc<dyn Primitive>>,
+    lights: Vec<Arc<dyn Primitive>>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum GltfError {
+    Io { file: PathBuf, error: io::Error },
+    Invalid { file: PathBuf, message: String },
+}
+
+impl GltfScene {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GltfError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|error| GltfError::Io {
+            file: path.to_path_buf(),
+            error,
+        })?;
+
+        Self::parse(&bytes, path)
+    }
+
+    /// Imports `bytes`, either glTF JSON or a GLB container. External files are resolved
+    /// relative to `file`.
+    pub fn parse(bytes: &[u8], file: &Path) -> Result<Self, GltfError> {
+        let invalid = |message: String| GltfError::Invalid {
+            file: file.to_path_buf(),
+            message,
+        };
+
+        let (json, bin) = match bytes.get(..4) {
+            Some(magic) if u32::from_le_bytes(magic.try_into().unwrap()) == GLB_MAGIC => {
+                split_glb(bytes).map_err(invalid)?
+            }
+            _ => (bytes, None),
+        };
+
+        let json = std::str::from_utf8(json).map_err(|_| invalid("not UTF-8 JSON".into()))?;
+        let doc = Json::parse(json).map_err(|err| invalid(err.to_string()))?;
+
+        let dir = file.parent().unwrap_or(Path::new(""));
+        let mut importer = Importer::new(&doc, dir, bin).map_err(invalid)?;
+        importer.import().map_err(invalid)?;
+
+        Ok(importer.finish())
+    }
+
+    /// The parts of the asset that were skipped or only approximated, for the caller to report.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+impl Scene for GltfScene {
+    fn default_camera(&self) -> CameraBuilder {
+        self.camera.clone()
+    }
+
+    fn build(&self) -> (HittableList, HittableList) {
+        let mut world = HittableList::with_capacity(self.world.len().max(1));
+        for object in &self.world {
+            world.push(object.clone());
+        }
+
+        let mut lights = HittableList::with_capacity(self.lights.len().max(1));
+        for light in &self.lights {
+            lights.push(light.clone());
+        }
+
+        (world, lights)
+    }
+}
+
+// ─────────────────────────────
+
+struct Importer<'a> {
+    doc: &'a Json,
+    dir: &'a Path,
+    buffers: Vec<Vec<u8>>,
+    /// Per glTF material: the mapped material and whether it emits light.
+    materials: Vec<(Arc<dyn Material>, bool)>,
+    default_material: Arc<dyn Material>,
+    world: Vec<Arc<dyn Primitive>>,
+    lights: Vec<Arc<dyn Primitive>>,
+    point_lights: Vec<(Point3, Color)>,
+    camera: Option<CameraBuilder>,
+    bbox: Aabb,
+    warnings: Vec<String>,
+}
+
+impl<'a> Importer<'a> {
+    fn new(doc: &'a Json, dir: &'a Path, bin: Option<&[u8]>) -> Result<Self, String> {
+        let version = doc
+            .get("asset")
+            .and_then(|asset| asset.get("version"))
+            .and_then(Json::as_str)
+            .ok_or("missing `asset.version`")?;
+        if !version.starts_with("2.") {
+            return Err(format!("unsupported glTF version {}", version));
+        }
+
+        let mut importer = Self {
+            doc,
+            dir,
+            buffers: Vec::new(),
+            materials: Vec::new(),
+            // The glTF default material: white, fully metallic and rough.
+            default_material: Arc::new(Metal::new(Color::splat(1.0), 1.0)),
+            world: Vec::new(),
+            lights: Vec::new(),
+            point_lights: Vec::new(),
+            camera: None,
+            bbox: Aabb::EMPTY,
+            warnings: Vec::new(),
+        };
+
+        for (i, buffer) in importer.list("buffers").iter().enumerate() {
+            let data = match buffer.get("uri").and_then(Json::as_str) {
+                Some(uri) => importer.read_uri(uri)?,
+                None if i == 0 => bin
+                    .ok_or("buffer 0 has no uri and there is no GLB chunk")?
+                    .to_vec(),
+                None => return Err(format!("`buffers[{}]` has no uri", i)),
+            };
+            importer.buffers.push(data);
+        }
+
+        let mut textures = HashMap::new();
+        for i in 0..importer.list("materials").len() {
+            let material = importer.material(i, &mut textures)?;
+            importer.materials.push(material);
+        }
+
+        Ok(importer)
+    }
+
+    fn import(&mut self) -> Result<(), String> {
+        let scene = self.doc.get("scene").and_then(Json::as_usize).unwrap_or(0);
+
+        let roots: Vec<usize> = match self.list("scenes").get(scene) {
+            Some(scene) => indices(scene.get("nodes")),
+            // No scenes: every node that is nobody's child is a root.
+            None => {
+                let children: Vec<usize> = self
+                    .list("nodes")
+                    .iter()
+                    .flat_map(|node| indices(node.get("children")))
+                    .collect();
+                (0..self.list("nodes").len())
+                    .filter(|i| !children.contains(i))
+                    .collect()
+            }
+        };
+
+        for root in roots {
+            self.visit(root, &IDENTITY, 0)?;
+        }
+
+        self.add_point_lights();
+        Ok(())
+    }
+
+    fn finish(self) -> GltfScene {
+        let camera = self.camera.unwrap_or_else(|| frame(&self.bbox));
+
+        let mut camera = camera
+            .image_width(600)
+            .samples_per_pixel(100)
+            .max_depth(50)
+            .defocus_angle(0.0);
+
+        // Without any light source the render would be black, so fall back to a sky colour.
+        if self.lights.is_empty() {
+            camera = camera.background(Color::new(0.70, 0.80, 1.00));
+        }
+
+        GltfScene {
+            camera,
+            world: self.world,
+            lights: self.lights,
+            warnings: self.warnings,
+        }
+    }
+
+    // ─────────────────────────────
+    // Document access
+
+    /// Top-level array `name`, empty when absent.
+    fn list(&self, name: &str) -> &'a [Json] {
+        self.doc.get(name).and_then(Json::as_array).unwrap_or(&[])
+    }
+
+    fn item(&self, name: &str, index: usize) -> Result<&'a Json, String> {
+        self.list(name)
+            .get(index)
+            .ok_or_else(|| format!("`{}[{}]` does not exist", name, index))
+    }
+
+    fn read_uri(&self, uri: &str) -> Result<Vec<u8>, String> {
+        if let Some(data) = uri.strip_prefix("data:") {
+            let (header, payload) = data.split_once(',').ok_or("malformed data uri")?;
+            if !header.ends_with(";base64") {
+                return Err("only base64 data uris are supported".to_string());
+            }
+            return decode_base64(payload);
+        }
+
+        let path = self.dir.join(percent_decode(uri));
+        fs::read(&path).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    fn buffer_view(&self, index: usize) -> Result<(&[u8], Option<usize>), String> {
+        let view = self.item("bufferViews", index)?;
+        let name = format!("bufferViews[{}]", index);
+
+        let buffer = view
+            .get("buffer")
+            .and_then(Json::as_usize)
+            .ok_or_else(|| format!("`{}.buffer` is missing", name))?;
+        let offset = view.get("byteOffset").and_then(Json::as_usize).unwrap_or(0);
+        let length = view
+            .get("byteLength")
+            .and_then(Json::as_usize)
+            .ok_or_else(|| format!("`{}.byteLength` is missing", name))?;
+        let stride = view.get("byteStride").and_then(Json::as_usize);
+
+        let data = self
+            .buffers
+            .get(buffer)
+            .and_then(|data| data.get(offset..offset + length))
+            .ok_or_else(|| format!("`{}` is out of the bounds of its buffer", name))?;
+
+        Ok((data, stride))
+    }
+
+    fn accessor(&self, index: usize) -> Result<Accessor<'_>, String> {
+        let accessor = self.item("accessors", index)?;
+        let name = format!("accessors[{}]", index);
+
+        if accessor.get("sparse").is_some() {
+            return Err(format!("`{}`: sparse accessors are not supported", name));
+        }
+
+        let count = accessor
+            .get("count")
+            .and_then(Json::as_usize)
+            .ok_or_else(|| format!("`{}.count` is missing", name))?;
+        let component = accessor
+            .get("componentType")
+            .and_then(Json::as_usize)
+            .ok_or_else(|| format!("`{}.componentType` is missing", name))?;
+        let component_size = match component {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => return Err(format!("`{}`: unknown component type {}", name, other)),
+        };
+        let components = match accessor.get("type").and_then(Json::as_str) {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") | Some("MAT2") => 4,
+            Some("MAT3") => 9,
+            Some("MAT4") => 16,
+            _ => return Err(format!("`{}.type` is missing or unknown", name)),
+        };
+
+        let view = accessor
+            .get("bufferView")
+            .and_then(Json::as_usize)
+            .ok_or_else(|| format!("`{}` has no bufferView", name))?;
+        let (data, stride) = self.buffer_view(view)?;
+
+        let offset = accessor
+            .get("byteOffset")
+            .and_then(Json::as_usize)
+            .unwrap_or(0);
+        let element_size = component_size * components;
+        let stride = stride.unwrap_or(element_size);
+
+        let end = match count {
+            0 => offset,
+            _ => offset + stride * (count - 1) + element_size,
+        };
+        if end > data.len() {
+            return Err(format!("`{}` reads past the end of its bufferView", name));
+        }
+
+        Ok(Accessor {
+            data: &data[offset..],
+            stride,
+            count,
+            component,
+            components,
+            normalized: accessor
+                .get("normalized")
+                .and_then(Json::as_bool)
+                .unwrap_or(false),
+        })
+    }
+
+    fn read_floats<const N: usize>(&self, index: usize) -> Result<Vec<[f32; N]>, String> {
+        let accessor = self.accessor(index)?;
+        if accessor.components != N {
+            return Err(format!(
+                "`accessors[{}]` should have {} components",
+                index, N
+            ));
+        }
+
+        Ok((0..accessor.count)
+            .map(|i| std::array::from_fn(|c| accessor.float(i, c)))
+            .collect())
+    }
+
+    fn read_indices(&self, index: usize) -> Result<Vec<u32>, String> {
+        let accessor = self.accessor(index)?;
+        if accessor.components != 1 || !matches!(accessor.component, 5121 | 5123 | 5125) {
+            return Err(format!(
+                "`accessors[{}]` is not a list of unsigned indices",
+                index
+            ));
+        }
+
+        Ok((0..accessor.count)
+            .map(|i| accessor.raw(i, 0) as u32)
+            .collect())
+    }
+
+    // ─────────────────────────────
+    // Materials
+
+    fn texture(
+        &self,
+        index: usize,
+        cache: &mut HashMap<usize, Arc<dyn Texture>>,
+    ) -> Result<Arc<dyn Texture>, String> {
+        let image = self
+            .item("textures", index)?
+            .get("source")
+            .and_then(Json::as_usize)
+            .ok_or_else(|| format!("`textures[{}]` has no image source", index))?;
+
+        if let Some(texture) = cache.get(&image) {
+            return Ok(texture.clone());
+        }
+
+        let source = self.item("images", image)?;
+        let bytes = match (
+            source.get("uri").and_then(Json::as_str),
+            source.get("bufferView"),
+        ) {
+            (Some(uri), _) => self.read_uri(uri)?,
+            (None, Some(view)) => {
+                let view = view.as_usize().ok_or("invalid image bufferView")?;
+                self.buffer_view(view)?.0.to_vec()
+            }
+            (None, None) => return Err(format!("`images[{}]` has no data", image)),
+        };
+
+        let decoded = RtwImage::try_from_memory(&bytes)
+            .map_err(|err| format!("`images[{}]`: {}", image, err))?;
+        let texture: Arc<dyn Texture> = Arc::new(ImageTexture::from_rtw_image(decoded));
+
+        cache.insert(image, texture.clone());
+        Ok(texture)
+    }
+
+    fn material(
+        &self,
+        index: usize,
+        textures: &mut HashMap<usize, Arc<dyn Texture>>,
+    ) -> Result<(Arc<dyn Material>, bool), String> {
+        let material = self.item("materials", index)?;
+        let pbr = material.get("pbrMetallicRoughness");
+        let extension = |name: &str, key: &str| {
+            material
+                .get("extensions")
+                .and_then(|e| e.get(name))
+                .and_then(|e| e.get(key))
+        };
+        let texture_index = |info: Option<&Json>| {
+            info.and_then(|info| info.get("index"))
+                .and_then(Json::as_usize)
+        };
+
+        let [r, g, b, _] = pbr
+            .and_then(|pbr| pbr.get("baseColorFactor"))
+            .and_then(Json::as_f32_array::<4>)
+            .unwrap_or([1.0; 4]);
+        let base_color = Color::new(r, g, b);
+        let metallic = pbr
+            .and_then(|pbr| pbr.get("metallicFactor"))
+            .and_then(Json::as_f32)
+            .unwrap_or(1.0);
+        let roughness = pbr
+            .and_then(|pbr| pbr.get("roughnessFactor"))
+            .and_then(Json::as_f32)
+            .unwrap_or(1.0);
+
+        let emissive = material
+            .get("emissiveFactor")
+            .and_then(Json::as_f32_array::<3>)
+            .unwrap_or([0.0; 3]);
+        let strength = extension("KHR_materials_emissive_strength", "emissiveStrength")
+            .and_then(Json::as_f32)
+            .unwrap_or(1.0);
+        let transmission = extension("KHR_materials_transmission", "transmissionFactor")
+            .and_then(Json::as_f32)
+            .unwrap_or(0.0);
+        let ior = extension("KHR_materials_ior", "ior")
+            .and_then(Json::as_f32)
+            .unwrap_or(1.5);
+        // The extension stores 20 / Abbe number; 0 means no dispersion.
+        let dispersion = extension("KHR_materials_dispersion", "dispersion")
+            .and_then(Json::as_f32)
+            .unwrap_or(0.0);
+
+        if emissive.iter().any(|&e| e > 0.0) {
+            let emit = strength * Color::from_array(emissive);
+            let light = match texture_index(material.get("emissiveTexture")) {
+                Some(i) => DiffuseLight::from_texture(Arc::new(ScaledTexture::new(
+                    self.texture(i, textures)?,
+                    emit,
+                ))),
+                None => DiffuseLight::from_color(emit),
+            };
+            return Ok((Arc::new(light), true));
+        }
+
+        let mapped: Arc<dyn Material> = if transmission > 0.0 {
+            Arc::new(if dispersion > 0.0 {
+                Dielectric::with_index(RefractiveIndex::from_abbe(ior, 20.0 / dispersion))
+            } else {
+                Dielectric::new(ior)
+            })
+        } else {
+            // glTF multiplies the base colour texture by the factor.
+            let base_texture: Option<Arc<dyn Texture>> =
+                match texture_index(pbr.and_then(|pbr| pbr.get("baseColorTexture"))) {
+                    Some(i) => Some(Arc::new(ScaledTexture::new(
+                        self.texture(i, textures)?,
+                        base_color.clone(),
+                    ))),
+                    None => None,
+                };
+            let fuzz = roughness.clamp(0.0, 1.0);
+            match (metallic >= 0.5, base_texture) {
+                (true, Some(texture)) => Arc::new(Metal::from_texture(texture, fuzz)),
+                (true, None) => Arc::new(Metal::new(base_color, fuzz)),
+                (false, Some(texture)) => Arc::new(Lambertian::from_texture(texture, 1.0)),
+                (false, None) => Arc::new(Lambertian::from_color(base_color, 1.0)),
+            }
+        };
+
+        Ok((mapped, false))
+    }
+
+    // ─────────────────────────────
+    // Nodes
+
+    fn visit(&mut self, index: usize, parent: &Mat4, depth: usize) -> Result<(), String> {
+        if depth > self.list("nodes").len() {
+            return Err("the node hierarchy contains a cycle".to_string());
+        }
+
+        let node = self.item("nodes", index)?;
+        let transform = mat_mul(parent, &local_transform(node));
+
+        if let Some(mesh) = node.get("mesh").and_then(Json::as_usize) {
+            self.add_mesh(mesh, &transform)?;
+        }
+
+        if let Some(camera) = node.get("camera").and_then(Json::as_usize) {
+            if self.camera.is_none() {
+                self.add_camera(camera, &transform)?;
+            }
+        }
+
+        let light = node
+            .get("extensions")
+            .and_then(|e| e.get("KHR_lights_punctual"))
+            .and_then(|e| e.get("light"))
+            .and_then(Json::as_usize);
+        if let Some(light) = light {
+            self.add_light(light, &transform)?;
+        }
+
+        for child in indices(node.get("children")) {
+            self.visit(child, &transform, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_mesh(&mut self, index: usize, transform: &Mat4) -> Result<(), String> {
+        let mesh = self.item("meshes", index)?;
+        let primitives = mesh
+            .get("primitives")
+            .and_then(Json::as_array)
+            .unwrap_or(&[]);
+
+        let normal_matrix = cofactor(transform);
+        // Mirroring transforms flip the winding, and with it the geometric normal.
+        let mirrored = determinant(transform) < 0.0;
+
+        for (p, primitive) in primitives.iter().enumerate() {
+            let name = format!("meshes[{}].primitives[{}]", index, p);
+
+            let mode = primitive.get("mode").and_then(Json::as_usize).unwrap_or(4);
+            if !matches!(mode, 4..=6) {
+                // Points and lines have no surface to render.
+                continue;
+            }
+
+            let attribute = |key: &str| {
+                primitive
+                    .get("attributes")
+                    .and_then(|a| a.get(key))
+                    .and_then(Json::as_usize)
+            };
+
+            let position = attribute("POSITION")
+                .ok_or_else(|| format!("`{}` has no POSITION attribute", name))?;
+            let positions = self.read_floats::<3>(position)?;
+            let normals = attribute("NORMAL")
+                .map(|i| self.read_floats::<3>(i))
+                .transpose()?
+                .filter(|n| n.len() == positions.len());
+            let uvs = attribute("TEXCOORD_0")
+                .map(|i| self.read_floats::<2>(i))
+                .transpose()?
+                .filter(|uv| uv.len() == positions.len());
+
+            let (material, emissive) = match primitive.get("material").and_then(Json::as_usize) {
+                Some(i) => self
+                    .materials
+                    .get(i)
+                    .cloned()
+                    .ok_or_else(|| format!("`materials[{}]` does not exist", i))?,
+                None => (self.default_material.clone(), false),
+            };
+
+            let indices = match primitive.get("indices").and_then(Json::as_usize) {
+                Some(i) => self.read_indices(i)?,
+                None => (0..positions.len() as u32).collect(),
+            };
+            if let Some(bad) = indices.iter().find(|&&i| i as usize >= positions.len()) {
+                return Err(format!("`{}` references missing vertex {}", name, bad));
+            }
+
+            let mut data = MeshData {
+                positions: positions
+                    .iter()
+                    .map(|p| transform_point(transform, p))
+                    .collect(),
+                normals: normals
+                    .iter()
+                    .flatten()
+                    .map(|n| {
+                        let n = mat3_apply(&normal_matrix, n);
+                        UnitVec3::try_from_array(n).unwrap_or(UnitVec3::J)
+                    })
+                    .collect(),
+                // glTF puts the texture origin at the top left, `ImageTexture` at the bottom.
+                uvs: uvs.iter().flatten().map(|[u, v]| (*u, 1.0 - v)).collect(),
+                materials: vec![material],
+                ..MeshData::default()
+            };
+
+            for mut triangle in triangles(&indices, mode) {
+                if mirrored {
+                    triangle.swap(1, 2);
+                }
+                data.faces.push(MeshFace {
+                    positions: triangle,
+                    normals: normals.is_some().then_some(triangle),
+                    uvs: uvs.is_some().then_some(triangle),
+                    colors: None,
+                    material: 0,
+                });
+            }
+
+            let mesh = TriangleMesh::new(data);
+            if mesh.is_empty() {
+                continue;
+            }
+
+            self.bbox.merge_inplace(mesh.bounding_box());
+
+            let mesh: Arc<dyn Primitive> = Arc::new(mesh);
+            if emissive {
+                self.lights.push(mesh.clone());
+            }
+            self.world.push(mesh);
+        }
+
+        Ok(())
+    }
+
+    fn add_camera(&mut self, index: usize, transform: &Mat4) -> Result<(), String> {
+        let camera = self.item("cameras", index)?;
+
+        let Some(perspective) = camera.get("perspective") else {
+            self.warnings.push(format!(
+                "skipped camera {}: only perspective cameras are supported",
+                index
+            ));
+            return Ok(());
+        };
+
+        let yfov = perspective
+            .get("yfov")
+            .and_then(Json::as_f32)
+            .ok_or_else(|| format!("`cameras[{}].perspective.yfov` is missing", index))?;
+
+        // glTF cameras look down their local -Z axis with +Y up.
+        let look_from = transform_point(transform, &[0.0, 0.0, 0.0]);
+        let forward = Vec3::from_array(mat3_apply(&upper_3x3(transform), &[0.0, 0.0, -1.0]));
+        let up = Vec3::from_array(mat3_apply(&upper_3x3(transform), &[0.0, 1.0, 0.0]));
+
+        let mut builder = CameraBuilder::uninitialized()
+            .look_at(&look_from + &forward)
+            .look_from(look_from)
+            .vup(up)
+            .vfov(yfov.to_degrees());
+
+        if let Some(aspect) = perspective.get("aspectRatio").and_then(Json::as_f32) {
+            builder = builder.aspect_ratio(aspect);
+        }
+
+        self.camera = Some(builder);
+        Ok(())
+    }
+
+    fn add_light(&mut self, index: usize, transform: &Mat4) -> Result<(), String> {
+        let light = self
+            .doc
+            .get("extensions")
+            .and_then(|e| e.get("KHR_lights_punctual"))
+            .and_then(|e| e.get("lights"))
+            .and_then(Json::as_array)
+            .and_then(|lights| lights.get(index))
+            .ok_or_else(|| format!("punctual light {} does not exist", index))?;
+
+        let color = light
+            .get("color")
+            .and_then(Json::as_f32_array::<3>)
+            .unwrap_or([1.0; 3]);
+        let intensity = light.get("intensity").and_then(Json::as_f32).unwrap_or(1.0);
+
+        match light.get("type").and_then(Json::as_str) {
+            Some(kind @ ("point" | "spot")) => {
+                if kind == "spot" {
+                    self.warnings.push(format!(
+                        "spot light {} shines in every direction: cones are not supported",
+                        index
+                    ));
+                }
+                let position = transform_point(transform, &[0.0, 0.0, 0.0]);
+                self.point_lights
+                    .push((position, intensity * Color::from_array(color)));
+            }
+            _ => self.warnings.push(format!(
+                "skipped light {}: only point and spot lights are supported",
+                index
+            )),
+        }
+
+        Ok(())
+    }
+
+    /// Point lights become spheres sized relative to the scene, emitting the radiance that
+    /// gives the light's intensity (`I = L π r²`).
+    fn add_point_lights(&mut self) {
+        let diagonal = [self.bbox.x(), self.bbox.y(), self.bbox.z()]
+            .iter()
+            .map(|axis| axis.size().max(0.0).powi(2))
+            .sum::<f32>()
+            .sqrt();
+        let radius = match diagonal.is_finite() && diagonal > 0.0 {
+            true => POINT_LIGHT_RADIUS * diagonal,
+            false => POINT_LIGHT_RADIUS,
+        };
+
+        for (position, intensity) in std::mem::take(&mut self.point_lights) {
+            let radiance = (1.0 / (PI * radius * radius)) * intensity;
+            let sphere: Arc<dyn Primitive> = Arc::new(Sphere::new(
+                position,
+                radius,
+                Arc::new(DiffuseLight::from_color(radiance)),
+            ));
+
+            self.lights.push(sphere.clone());
+            self.world.push(sphere);
+        }
+    }
+}
+
+/// A typed view over the elements of a glTF accessor.
+struct Accessor<'a> {
+    data: &'a [u8],
+    stride: usize,
+    count: usize,
+    component: usize,
+    components: usize,
+    normalized: bool,
+}
+
+impl Accessor<'_> {
+    fn raw(&self, index: usize, component: usize) -> f64 {
+        let size = match self.component {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            _ => 4,
+        };
+        let at = index * self.stride + component * size;
+        let bytes = &self.data[at..at + size];
+
+        match self.component {
+            5120 => bytes[0] as i8 as f64,
+            5121 => bytes[0] as f64,
+            5122 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            5123 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            5125 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            _ => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+        }
+    }
+
+    /// Component value, mapping normalized integers to `[0, 1]` or `[-1, 1]`.
+    fn float(&self, index: usize, component: usize) -> f32 {
+        let value = self.raw(index, component);
+
+        // Signed integers have one more negative value than positive ones; it maps to -1 too.
+        let value = match (self.normalized, self.component) {
+            (true, 5120) => (value / i8::MAX as f64).max(-1.0),
+            (true, 5121) => value / u8::MAX as f64,
+            (true, 5122) => (value / i16::MAX as f64).max(-1.0),
+            (true, 5123) => value / u16::MAX as f64,
+            _ => value,
+        };
+
+        value as f32
+    }
+}
+
+// ─────────────────────────────
+
+fn indices(list: Option<&Json>) -> Vec<usize> {
+    list.and_then(Json::as_array)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(Json::as_usize)
+        .collect()
+}
+
+/// Triangles of a primitive in `TRIANGLES` (4), `TRIANGLE_STRIP` (5) or `TRIANGLE_FAN` (6) mode.
+fn triangles(indices: &[u32], mode: usize) -> Vec<[u32; 3]> {
+    match mode {
+        5 => indices
+            .windows(3)
+            .enumerate()
+            .map(|(i, w)| match i % 2 {
+                0 => [w[0], w[1], w[2]],
+                _ => [w[1], w[0], w[2]],
+            })
+            .collect(),
+        6 if !indices.is_empty() => indices[1..]
+            .windows(2)
+            .map(|w| [indices[0], w[0], w[1]])
+            .collect(),
+        _ => indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect(),
+    }
+}
+
+/// Camera looking down -Z at the whole scene.
+fn frame(bbox: &Aabb) -> CameraBuilder {
+    let [x, y, z] = bbox.center();
+    let extent = [bbox.x(), bbox.y(), bbox.z()]
+        .iter()
+        .map(|axis| axis.size())
+        .fold(0.0f32, f32::max);
+    let extent = if extent.is_finite() && extent > 0.0 {
+        extent
+    } else {
+        1.0
+    };
+    let (x, y, z) = if [x, y, z].iter().all(|c| c.is_finite()) {
+        (x, y, z)
+    } else {
+        (0.0, 0.0, 0.0)
+    };
+
+    CameraBuilder::uninitialized()
+        .look_from(Point3::new(x, y, z + 1.5 * extent))
+        .look_at(Point3::new(x, y, z))
+        .vup(Vec3::new(0.0, 1.0, 0.0))
+        .vfov(45.0)
+}
+
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    let word = |at: usize| -> Result<u32, String> {
+        bytes
+            .get(at..at + 4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+            .ok_or_else(|| "truncated GLB file".to_string())
+    };
+
+    if word(4)? != 2 {
+        return Err(format!("unsupported GLB version {}", word(4)?));
+    }
+    let total = (word(8)? as usize).min(bytes.len());
+
+    let mut json = None;
+    let mut bin = None;
+    let mut at = 12;
+
+    while at + 8 <= total {
+        let length = word(at)? as usize;
+        let kind = word(at + 4)?;
+        let chunk = bytes
+            .get(at + 8..at + 8 + length)
+            .ok_or("truncated GLB chunk")?;
+
+        match kind {
+            GLB_JSON_CHUNK if json.is_none() => json = Some(chunk),
+            GLB_BIN_CHUNK if bin.is_none() => bin = Some(chunk),
+            _ => {}
+        }
+        at += 8 + length;
+    }
+
+    Ok((json.ok_or("GLB file has no JSON chunk")?, bin))
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+
+    for byte in text.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b'\n' | b'\r' | b' ' => continue,
+            _ => return Err("invalid base64 data".to_string()),
+        };
+
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+
+    Ok(out)
+}
+
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(decoded)) => {
+                out.push(decoded);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// ─────────────────────────────
+// Matrices
+
+fn local_transform(node: &Json) -> Mat4 {
+    if let Some(m) = node.get("matrix").and_then(Json::as_f32_array::<16>) {
+        return std::array::from_fn(|col| std::array::from_fn(|row| m[col * 4 + row]));
+    }
+
+    let [tx, ty, tz] = node
+        .get("translation")
+        .and_then(Json::as_f32_array::<3>)
+        .unwrap_or([0.0; 3]);
+    let [x, y, z, w] = node
+        .get("rotation")
+        .and_then(Json::as_f32_array::<4>)
+        .unwrap_or([0.0, 0.0, 0.0, 1.0]);
+    let [sx, sy, sz] = node
+        .get("scale")
+        .and_then(Json::as_f32_array::<3>)
+        .unwrap_or([1.0; 3]);
+
+    // Columns of R·S, followed by the translation.
+    [
+        [
+            sx * (1.0 - 2.0 * (y * y + z * z)),
+            sx * (2.0 * (x * y + z * w)),
+            sx * (2.0 * (x * z - y * w)),
+            0.0,
+        ],
+        [
+            sy * (2.0 * (x * y - z * w)),
+            sy * (1.0 - 2.0 * (x * x + z * z)),
+            sy * (2.0 * (y * z + x * w)),
+            0.0,
+        ],
+        [
+            sz * (2.0 * (x * z + y * w)),
+            sz * (2.0 * (y * z - x * w)),
+            sz * (1.0 - 2.0 * (x * x + y * y)),
+            0.0,
+        ],
+        [tx, ty, tz, 1.0],
+    ]
+}
+
+fn mat_mul(a: &Mat4, b: &Mat4) -> Mat4 {
+    std::array::from_fn(|col| {
+        std::array::from_fn(|row| (0..4).map(|k| a[k][row] * b[col][k]).sum())
+    })
+}
+
+fn transform_point(m: &Mat4, p: &[f32; 3]) -> Point3 {
+    let [x, y, z] = mat3_apply(&upper_3x3(m), p);
+    Point3::new(x + m[3][0], y + m[3][1], z + m[3][2])
+}
+
+/// Columns of the upper-left 3x3 block.
+fn upper_3x3(m: &Mat4) -> [[f32; 3]; 3] {
+    std::array::from_fn(|col| [m[col][0], m[col][1], m[col][2]])
+}
+
+fn mat3_apply(m: &[[f32; 3]; 3], v: &[f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|row| m[0][row] * v[0] + m[1][row] * v[1] + m[2][row] * v[2])
+}
+
+fn cross(a: &[f32; 3], b: &[f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn determinant(m: &Mat4) -> f32 {
+    let [a, b, c] = upper_3x3(m);
+    let bc = cross(&b, &c);
+    a[0] * bc[0] + a[1] * bc[1] + a[2] * bc[2]
+}
+
+/// Normal matrix up to a positive scale: the cofactor matrix `det(M)·(M⁻¹)ᵀ`, with the sign of
+/// the determinant divided back out so normals keep their side.
+fn cofactor(m: &Mat4) -> [[f32; 3]; 3] {
+    let [a, b, c] = upper_3x3(m);
+    let sign = determinant(m).signum();
+    [cross(&b, &c), cross(&c, &a), cross(&a, &b)].map(|col| col.map(|x| sign * x))
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Io { file, error } => write!(f, "{}: {}", file.display(), error),
+            GltfError::Invalid { file, message } => write!(f, "{}: {}", file.display(), message),
+        }
+    }
+}
+
+impl Error for GltfError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{Interval, Ray};
+
+    /// One triangle (0,0,0), (1,0,0), (0,1,0) in an embedded buffer, placed at z = -2 by its
+    /// node, seen by a camera at the origin and lit by a point light.
+    const TRIANGLE: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0, 1, 2] }],
+        "nodes": [
+            { "mesh": 0, "translation": [0, 0, -2] },
+            { "camera": 0 },
+            { "translation": [0, 0, 1], "extensions": { "KHR_lights_punctual": { "light": 0 } } }
+        ],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "material": 0 }] }],
+        "materials": [{ "pbrMetallicRoughness": { "baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0 } }],
+        "cameras": [{ "type": "perspective", "perspective": { "yfov": 0.8, "aspectRatio": 1.5, "znear": 0.1 } }],
+        "extensions": { "KHR_lights_punctual": { "lights": [{ "type": "point", "intensity": 10 }] } },
+        "accessors": [{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }],
+        "bufferViews": [{ "buffer": 0, "byteLength": 36 }],
+        "buffers": [{ "byteLength": 36, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA" }]
+    }"#;
+
+    fn hit(scene: &GltfScene) -> Option<HitRecord> {
+        let (world, _) = scene.build();
+        let ray = Ray::new(Point3::new(0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        world.hit(&ray, Interval::CAMERA_VIEW)
+    }
+
+    #[test]
+    fn imports_embedded_gltf() {
+        let scene = GltfScene::parse(TRIANGLE.as_bytes(), Path::new("test.gltf")).unwrap();
+        let rec = hit(&scene).expect("the triangle should be in front of the camera");
+
+        assert!((rec.t() - 2.0).abs() < 1e-5);
+        assert!(rec.front_face());
+
+        // The triangle and the point light sphere; the light is also sampled.
+        let (world, lights) = scene.build();
+        assert_eq!(world.objects().len(), 2);
+        assert_eq!(lights.objects().len(), 1);
+        assert!(scene.warnings().is_empty());
+    }
+
+    #[test]
+    fn reports_what_it_skips_or_approximates() {
+        let json = TRIANGLE
+            .replace(r#""type": "point""#, r#""type": "spot""#)
+            .replace(r#""perspective": {"#, r#""orthographic": {"#);
+        let scene = GltfScene::parse(json.as_bytes(), Path::new("test.gltf")).unwrap();
+
+        assert_eq!(scene.warnings().len(), 2);
+        assert!(scene.warnings().iter().any(|w| w.contains("camera 0")));
+        assert!(scene.warnings().iter().any(|w| w.contains("spot light 0")));
+        // The spot light still lights the scene.
+        assert_eq!(scene.build().1.objects().len(), 1);
+    }
+
+    #[test]
+    fn keeps_coordinates_outside_the_unit_range() {
+        // The first vertex moved to (-5, 0, 0): the triangle now reaches x = -3 at y = 0.25.
+        let json = TRIANGLE.replace(
+            "AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA",
+            "AACgwAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA",
+        );
+        let scene = GltfScene::parse(json.as_bytes(), Path::new("test.gltf")).unwrap();
+        let (world, _) = scene.build();
+        let ray = Ray::new(Point3::new(-3.0, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(world.hit(&ray, Interval::CAMERA_VIEW).is_some());
+    }
+
+    #[test]
+    fn imports_glb() {
+        let mut json = TRIANGLE.replace(
+            r#", "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA""#,
+            "",
+        );
+        while !json.len().is_multiple_of(4) {
+            json.push(' ');
+        }
+        let bin: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+
+        let mut glb = Vec::new();
+        let total = 12 + 8 + json.len() + 8 + bin.len();
+        for word in [
+            GLB_MAGIC,
+            2,
+            total as u32,
+            json.len() as u32,
+            GLB_JSON_CHUNK,
+        ] {
+            glb.extend_from_slice(&word.to_le_bytes());
+        }
+        glb.extend_from_slice(json.as_bytes());
+        for word in [bin.len() as u32, GLB_BIN_CHUNK] {
+            glb.extend_from_slice(&word.to_le_bytes());
+        }
+        glb.extend_from_slice(&bin);
+
+        let scene = GltfScene::parse(&glb, Path::new("test.glb")).unwrap();
+        assert!(hit(&scene).is_some());
+    }
+
+    #[test]
+    fn multiplies_textures_by_their_factors() {
+        let dir = std::env::temp_dir().join(format!("gltf_factor_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        image::RgbImage::from_pixel(1, 1, image::Rgb([255, 255, 255]))
+            .save(dir.join("white.png"))
+            .unwrap();
+        let textured = |material: &str| {
+            let source = TRIANGLE
+                .replace(
+                    r#"{ "pbrMetallicRoughness": { "baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0 } }"#,
+                    material,
+                )
+                .replace(
+                    r#""cameras""#,
+                    r#""textures": [{ "source": 0 }], "images": [{ "uri": "white.png" }], "cameras""#,
+                );
+            let scene = GltfScene::parse(source.as_bytes(), &dir.join("test.gltf")).unwrap();
+            hit(&scene).unwrap()
+        };
+
+        let rec = textured(
+            r#"{ "emissiveFactor": [0.5, 0.25, 0], "emissiveTexture": { "index": 0 },
+                 "extensions": { "KHR_materials_emissive_strength": { "emissiveStrength": 4 } } }"#,
+        );
+        let emitted = rec.material().emitted(&rec);
+        assert!((emitted.x() - 2.0).abs() < 1e-3 && (emitted.y() - 1.0).abs() < 1e-3);
+
+        for metallic in [0, 1] {
+            let rec = textured(&format!(
+                r#"{{ "pbrMetallicRoughness": {{ "baseColorFactor": [1, 0, 0, 1],
+                     "baseColorTexture": {{ "index": 0 }}, "metallicFactor": {} }} }}"#,
+                metallic
+            ));
+            let albedo = rec.material().albedo(&rec);
+            assert!(
+                albedo.x() > 0.9 && albedo.y() == 0.0,
+                "metallic {}",
+                metallic
+            );
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reports_missing_accessor() {
+        let source = TRIANGLE.replace(r#""POSITION": 0"#, r#""POSITION": 7"#);
+
+        match GltfScene::parse(source.as_bytes(), Path::new("test.gltf")) {
+            Err(GltfError::Invalid { message, .. }) => {
+                assert_eq!(message, "`accessors[7]` does not exist")
+            }
+            _ => panic!("expected an invalid file error"),
+        }
+    }
+}
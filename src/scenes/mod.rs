@@ -1,19 +1,28 @@
 use std::sync::Arc;
 
 use crate::{
-    camera::{Camera, CameraBuilder},
+    camera::{Camera, CameraBuilder, FrameBuffer, RenderState},
     geometry::*,
     materials::*,
-    math::{color, Color, Point3, Vec3},
+    math::{color, rng, Color, Point3, Vec3},
     textures::*,
 };
 
-pub use {cornell_box::CornellBox, earth::Earth, office::Svrnc, rtiow::Rtiow};
+pub use {
+    cornell_box::CornellBox,
+    earth::Earth,
+    gltf::{GltfError, GltfScene},
+    office::Svrnc,
+    rtiow::Rtiow,
+    scene_file::{SceneFile, SceneFileError},
+};
 
 mod cornell_box;
 mod earth;
+mod gltf;
 mod office;
 mod rtiow;
+mod scene_file;
 
 #[allow(dead_code)]
 struct SceneData {
@@ -64,12 +73,25 @@ pub trait Scene {
 
     fn default_camera(&self) -> CameraBuilder;
 
-    fn render(&self, cam: &Camera) -> Vec<f32> {
+    /// Builds the scene, with random content drawn from the seed of `cam`, and renders it.
+    fn render(&self, cam: &Camera) -> FrameBuffer {
+        cam.frame(&self.render_progressive(cam, None, &mut |_| {}))
+    }
+
+    /// Builds the scene and renders it pass by pass, as [`Camera::render_progressive`] does,
+    /// and panics in the same case.
+    fn render_progressive(
+        &self,
+        cam: &Camera,
+        resume: Option<RenderState>,
+        on_pass: &mut dyn FnMut(&RenderState),
+    ) -> RenderState {
+        rng::seed_thread(cam.seed(), rng::SCENE_STREAM);
         let (world, lights) = self.build();
 
         let world = Bvh::build(world);
 
-        cam.render(&world, &lights)
+        cam.render_progressive(&world, &lights, resume, on_pass)
     }
 }
 
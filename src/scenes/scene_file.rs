@@ -0,0 +1,1328 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+use super::*;
+use crate::{
+    math::{sampler::SamplerKind, ColorSpace},
+    util::{load_obj, load_ply},
+};
+
+type Span = Option<Range<usize>>;
+
+/// A scene described declaratively in a TOML file.
+///
+/// The file is parsed and validated once by [`SceneFile::load`]; `build` then hands out the
+/// same `(world, lights)` pair a built-in scene would.
+///
+/// ```toml
+/// [camera]
+/// image_width = 400
+/// look_from = [278, 278, -800]
+/// look_at = [278, 278, 0]
+///
+/// [materials.white]
+/// type = "lambertian"
+/// albedo = [0.73, 0.73, 0.73]
+///
+/// [materials.light]
+/// type = "diffuse_light"
+/// emit = [15, 15, 15]
+///
+/// [[objects]]
+/// type = "cuboid"
+/// p0 = [0, 0, 0]
+/// p1 = [165, 330, 165]
+/// material = "white"
+/// transform = [{ rotation_y = 15 }, { translation = [265, 0, 295] }]
+///
+/// [[lights]]
+/// type = "quad"
+/// q = [343, 554, 332]
+/// u = [-130, 0, 0]
+/// v = [0, 0, -105]
+/// ```
+pub struct SceneFile {
+    camera: CameraBuilder,
+    world: Vec<Arc<dyn Primitive>>,
+    lights: Vec<Arc<dyn Primitive>>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io {
+        file: PathBuf,
+        error: io::Error,
+    },
+    Parse {
+        file: PathBuf,
+        line: Option<usize>,
+        key: String,
+        message: String,
+    },
+}
+
+impl SceneFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneFileError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|error| SceneFileError::Io {
+            file: path.to_path_buf(),
+            error,
+        })?;
+
+        Self::parse(&source, path)
+    }
+
+    /// Parses a scene from `source`; `file` is only used to label errors.
+    pub fn parse(source: &str, file: &Path) -> Result<Self, SceneFileError> {
+        let mut parser = Parser::new(source, file);
+
+        let doc = ImDocument::parse(source).map_err(|error| {
+            parser.error(error.span(), "", error.message().trim_end().to_string())
+        })?;
+
+        parser.parse_document(doc.as_table())
+    }
+
+    /// What the meshes the scene loads skipped, such as missing OBJ material libraries, for
+    /// the caller to report.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+impl Scene for SceneFile {
+    fn default_camera(&self) -> CameraBuilder {
+        self.camera.clone()
+    }
+
+    fn build(&self) -> (HittableList, HittableList) {
+        let mut world = HittableList::with_capacity(self.world.len().max(1));
+        for object in &self.world {
+            world.push(object.clone());
+        }
+
+        let mut lights = HittableList::with_capacity(self.lights.len().max(1));
+        for light in &self.lights {
+            lights.push(light.clone());
+        }
+
+        (world, lights)
+    }
+}
+
+// ─────────────────────────────
+
+/// A table (regular or inline) together with where it lives in the file.
+struct Node<'doc> {
+    table: &'doc dyn TableLike,
+    span: Span,
+    path: String,
+}
+
+impl<'doc> Node<'doc> {
+    fn key_path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        }
+    }
+
+    fn key_span(&self, key: &str) -> Span {
+        self.table
+            .get_key_value(key)
+            .and_then(|(k, item)| k.span().or_else(|| item.span()))
+            .or_else(|| self.span.clone())
+    }
+
+    fn item(&self, key: &str) -> Option<&'doc Item> {
+        self.table.get(key).filter(|item| !item.is_none())
+    }
+}
+
+struct Parser<'src> {
+    source: &'src str,
+    file: &'src Path,
+    textures: HashMap<String, Arc<dyn Texture>>,
+    materials: HashMap<String, Arc<dyn Material>>,
+    /// Objects are parsed through shared references, so the warnings of mesh loaders are kept
+    /// in a cell.
+    warnings: RefCell<Vec<String>>,
+}
+
+impl<'src> Parser<'src> {
+    const TOP_LEVEL_KEYS: [&'static str; 5] =
+        ["camera", "textures", "materials", "objects", "lights"];
+
+    const CAMERA_KEYS: [&'static str; 15] = [
+        "look_from",
+        "look_at",
+        "vup",
+        "vfov",
+        "viewport_height",
+        "defocus_angle",
+        "focus_dist",
+        "background",
+        "aspect_ratio",
+        "image_width",
+        "samples_per_pixel",
+        "adaptive_threshold",
+        "max_samples_per_pixel",
+        "sampler",
+        "max_depth",
+    ];
+
+    fn new(source: &'src str, file: &'src Path) -> Self {
+        Self {
+            source,
+            file,
+            textures: HashMap::new(),
+            materials: HashMap::new(),
+            warnings: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn parse_document(&mut self, root: &toml_edit::Table) -> Result<SceneFile, SceneFileError> {
+        let root = Node {
+            table: root,
+            span: None,
+            path: String::new(),
+        };
+        self.check_keys(&root, &Self::TOP_LEVEL_KEYS)?;
+
+        let camera = match root.item("camera") {
+            Some(item) => self.parse_camera(&self.table(item, &root, "camera")?)?,
+            None => CameraBuilder::uninitialized(),
+        };
+
+        if let Some(item) = root.item("textures") {
+            let textures = self.table(item, &root, "textures")?;
+            for (name, _) in textures.table.iter() {
+                let node = self.table(textures.table.get(name).unwrap(), &textures, name)?;
+                let texture = self.parse_texture(&node)?;
+                self.textures.insert(name.to_string(), texture);
+            }
+        }
+
+        if let Some(item) = root.item("materials") {
+            let materials = self.table(item, &root, "materials")?;
+            for (name, _) in materials.table.iter() {
+                let node = self.table(materials.table.get(name).unwrap(), &materials, name)?;
+                let material = self.parse_material(&node)?;
+                self.materials.insert(name.to_string(), material);
+            }
+        }
+
+        let world = match root.item("objects") {
+            Some(item) => self
+                .tables(item, &root, "objects")?
+                .iter()
+                .map(|node| self.parse_object(node, false))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+
+        let lights = match root.item("lights") {
+            Some(item) => self
+                .tables(item, &root, "lights")?
+                .iter()
+                .map(|node| self.parse_object(node, true))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(SceneFile {
+            camera,
+            world,
+            lights,
+            warnings: self.warnings.take(),
+        })
+    }
+
+    fn parse_camera(&self, node: &Node) -> Result<CameraBuilder, SceneFileError> {
+        self.check_keys(node, &Self::CAMERA_KEYS)?;
+
+        let mut builder = CameraBuilder::uninitialized();
+
+        if let Some(p) = self.opt_vec3(node, "look_from")? {
+            builder = builder.look_from(Point3::from_array(p));
+        }
+        if let Some(p) = self.opt_vec3(node, "look_at")? {
+            builder = builder.look_at(Point3::from_array(p));
+        }
+        if let Some(v) = self.opt_vec3(node, "vup")? {
+            builder = builder.vup(Vec3::from_array(v));
+        }
+        if let Some(c) = self.opt_vec3(node, "background")? {
+            builder = builder.background(Color::from_array(c));
+        }
+        if let Some(x) = self.opt_f32(node, "vfov")? {
+            builder = builder.vfov(x);
+        }
+        if let Some(x) = self.opt_f32(node, "viewport_height")? {
+            builder = builder.viewport_height(x);
+        }
+        if let Some(x) = self.opt_f32(node, "defocus_angle")? {
+            builder = builder.defocus_angle(x);
+        }
+        if let Some(x) = self.opt_f32(node, "focus_dist")? {
+            builder = builder.focus_dist(x);
+        }
+        if let Some(x) = self.opt_f32(node, "aspect_ratio")? {
+            builder = builder.aspect_ratio(x);
+        }
+        if let Some(x) = self.opt_uint(node, "image_width", u32::MAX as i64)? {
+            builder = builder.image_width(x as u32);
+        }
+        if let Some(x) = self.opt_uint(node, "samples_per_pixel", u16::MAX as i64)? {
+            builder = builder.samples_per_pixel(x as u16);
+        }
+        if let Some(x) = self.opt_uint(node, "max_depth", u16::MAX as i64)? {
+            builder = builder.max_depth(x as u16);
+        }
+        if let Some(x) = self.opt_f32(node, "adaptive_threshold")? {
+            builder = builder.adaptive_threshold(x);
+        }
+        if let Some(x) = self.opt_uint(node, "max_samples_per_pixel", u16::MAX as i64)? {
+            builder = builder.max_samples_per_pixel(x as u16);
+        }
+        if node.item("sampler").is_some() {
+            builder = builder.sampler(self.req_sampler(node, "sampler")?);
+        }
+
+        Ok(builder)
+    }
+
+    fn parse_texture(&self, node: &Node) -> Result<Arc<dyn Texture>, SceneFileError> {
+        let kind = self.req_str(node, "type")?;
+
+        let texture: Arc<dyn Texture> = match kind {
+            "solid" => {
+                self.check_keys(node, &["type", "color"])?;
+                Arc::new(SolidColor::from_color(self.req_color(node, "color")?))
+            }
+            "checker" => {
+                self.check_keys(node, &["type", "even", "odd", "scale"])?;
+                Arc::new(CheckerTexture::from_textures(
+                    self.req_texture(node, "even")?,
+                    self.req_texture(node, "odd")?,
+                    self.req_f32(node, "scale")?,
+                ))
+            }
+            "image" => {
+                self.check_keys(node, &["type", "file", "color_space"])?;
+                let file = self.req_str(node, "file")?;
+                // Images not found next to the scene file are looked for in the image
+                // directories, as for the built-in scenes.
+                let path = self.resolve_path(file);
+                let file = if path.exists() {
+                    path.to_string_lossy()
+                } else {
+                    file.into()
+                };
+                match node.item("color_space") {
+                    Some(_) => Arc::new(ImageTexture::from_image_with_color_space(
+                        &file,
+                        self.req_color_space(node, "color_space")?,
+                    )),
+                    None => Arc::new(ImageTexture::from_image(&file)),
+                }
+            }
+            "noise" => {
+                self.check_keys(node, &["type", "scale"])?;
+                Arc::new(NoiseTexture::new(self.req_f32(node, "scale")?))
+            }
+            "marble" => {
+                self.check_keys(node, &["type", "scale", "color"])?;
+                Arc::new(MarbleTexture::new(
+                    self.req_f32(node, "scale")?,
+                    self.req_color(node, "color")?,
+                ))
+            }
+            "wood" => {
+                self.check_keys(node, &["type", "scale"])?;
+                Arc::new(WoodTexture::new(self.req_f32(node, "scale")?))
+            }
+            "melamine" => {
+                self.check_keys(node, &["type", "color", "scale"])?;
+                Arc::new(Melamine::new(
+                    self.req_color(node, "color")?,
+                    self.req_f32(node, "scale")?,
+                ))
+            }
+            "vertex_color" => {
+                self.check_keys(node, &["type", "fallback"])?;
+                match node.item("fallback") {
+                    Some(_) => Arc::new(VertexColor::with_fallback(
+                        self.req_color(node, "fallback")?,
+                    )),
+                    None => Arc::new(VertexColor::new()),
+                }
+            }
+            unknown => {
+                return Err(self.error(
+                    node.key_span("type"),
+                    &node.key_path("type"),
+                    format!("unknown texture type `{}`", unknown),
+                ))
+            }
+        };
+
+        Ok(texture)
+    }
+
+    fn parse_density(&self, node: &Node) -> Result<Arc<dyn DensityField>, SceneFileError> {
+        let kind = self.req_str(node, "type")?;
+
+        let field: Arc<dyn DensityField> = match kind {
+            "noise" => {
+                self.check_keys(node, &["type", "scale", "octaves"])?;
+                let noise = NoiseDensity::new(self.req_f32(node, "scale")?);
+                match self.opt_uint(node, "octaves", 16)? {
+                    Some(octaves) => Arc::new(noise.with_octaves(octaves as usize)),
+                    None => Arc::new(noise),
+                }
+            }
+            "voxels" => {
+                self.check_keys(node, &["type", "min", "max", "resolution", "values"])?;
+                let resolution = self.req_resolution(node, "resolution")?;
+                Arc::new(VoxelGrid::new(
+                    Point3::from_array(self.req_vec3(node, "min")?),
+                    Point3::from_array(self.req_vec3(node, "max")?),
+                    resolution,
+                    self.req_values(node, "values", resolution.iter().product())?,
+                ))
+            }
+            "texture" => {
+                self.check_keys(node, &["type", "texture"])?;
+                Arc::new(TextureDensity::new(self.req_texture(node, "texture")?))
+            }
+            unknown => {
+                return Err(self.error(
+                    node.key_span("type"),
+                    &node.key_path("type"),
+                    format!("unknown density field type `{}`", unknown),
+                ))
+            }
+        };
+
+        Ok(field)
+    }
+
+    fn parse_material(&self, node: &Node) -> Result<Arc<dyn Material>, SceneFileError> {
+        let kind = self.req_str(node, "type")?;
+
+        let material: Arc<dyn Material> = match kind {
+            "lambertian" => {
+                self.check_keys(node, &["type", "albedo", "scatter_prob"])?;
+                let scatter_prob = self.opt_f32(node, "scatter_prob")?.unwrap_or(1.0);
+                Arc::new(Lambertian::from_texture(
+                    self.req_texture(node, "albedo")?,
+                    scatter_prob,
+                ))
+            }
+            "metal" => {
+                self.check_keys(node, &["type", "albedo", "fuzz"])?;
+                let fuzz = self.opt_f32(node, "fuzz")?.unwrap_or(0.0);
+                Arc::new(Metal::new(self.req_color(node, "albedo")?, fuzz))
+            }
+            "dielectric" => {
+                self.check_keys(node, &["type", "refraction_index"])?;
+                Arc::new(Dielectric::with_index(
+                    self.req_refractive_index(node, "refraction_index")?,
+                ))
+            }
+            "diffuse_light" => {
+                self.check_keys(node, &["type", "emit"])?;
+                Arc::new(DiffuseLight::from_texture(self.req_texture(node, "emit")?))
+            }
+            "isotropic" => {
+                self.check_keys(node, &["type", "albedo"])?;
+                Arc::new(Isotropic::from_texture(self.req_texture(node, "albedo")?))
+            }
+            "empty" => {
+                self.check_keys(node, &["type"])?;
+                Arc::new(Empty)
+            }
+            unknown => {
+                return Err(self.error(
+                    node.key_span("type"),
+                    &node.key_path("type"),
+                    format!("unknown material type `{}`", unknown),
+                ))
+            }
+        };
+
+        Ok(material)
+    }
+
+    /// Parses a primitive, wraps it in its `transform` chain and returns it.
+    ///
+    /// Light entries only need geometry, so their material defaults to `Empty`.
+    fn parse_object(
+        &self,
+        node: &Node,
+        is_light: bool,
+    ) -> Result<Arc<dyn Primitive>, SceneFileError> {
+        let kind = self.req_str(node, "type")?;
+
+        let material = |parser: &Self| -> Result<Arc<dyn Material>, SceneFileError> {
+            match (node.item("material"), is_light) {
+                (None, true) => Ok(Arc::new(Empty)),
+                _ => parser.req_material(node, "material"),
+            }
+        };
+
+        let object: Arc<dyn Primitive> = match kind {
+            "sphere" => {
+                self.check_keys(
+                    node,
+                    &[
+                        "type",
+                        "center",
+                        "center1",
+                        "radius",
+                        "material",
+                        "transform",
+                    ],
+                )?;
+                let center = Point3::from_array(self.req_vec3(node, "center")?);
+                let radius = self.req_f32(node, "radius")?;
+                match self.opt_vec3(node, "center1")? {
+                    Some(center1) => Arc::new(Sphere::new_moving(
+                        center,
+                        Point3::from_array(center1),
+                        radius,
+                        material(self)?,
+                    )),
+                    None => Arc::new(Sphere::new(center, radius, material(self)?)),
+                }
+            }
+            "quad" | "triangle" | "disk" => {
+                self.check_keys(node, &["type", "q", "u", "v", "material", "transform"])?;
+                let q = Point3::from_array(self.req_vec3(node, "q")?);
+                let u = Vec3::from_array(self.req_vec3(node, "u")?);
+                let v = Vec3::from_array(self.req_vec3(node, "v")?);
+                match kind {
+                    "quad" => Arc::new(Quad::new(q, u, v, material(self)?)),
+                    "triangle" => Arc::new(Triangle::new(q, u, v, material(self)?)),
+                    _ => Arc::new(Disk::new(q, u, v, material(self)?)),
+                }
+            }
+            "trapezoid" => {
+                self.check_keys(
+                    node,
+                    &["type", "q", "u", "v", "ratio", "material", "transform"],
+                )?;
+                Arc::new(Trapezoid::new(
+                    Point3::from_array(self.req_vec3(node, "q")?),
+                    Vec3::from_array(self.req_vec3(node, "u")?),
+                    Vec3::from_array(self.req_vec3(node, "v")?),
+                    self.req_f32(node, "ratio")?,
+                    material(self)?,
+                ))
+            }
+            "cuboid" => {
+                self.check_keys(node, &["type", "p0", "p1", "material", "transform"])?;
+                Arc::new(Cuboid::new(
+                    Point3::from_array(self.req_vec3(node, "p0")?),
+                    Point3::from_array(self.req_vec3(node, "p1")?),
+                    material(self)?,
+                ))
+            }
+            "cylinder" => {
+                self.check_keys(
+                    node,
+                    &[
+                        "type",
+                        "center",
+                        "radius",
+                        "height",
+                        "material",
+                        "transform",
+                    ],
+                )?;
+                Arc::new(Cylinder::new(
+                    Point3::from_array(self.req_vec3(node, "center")?),
+                    self.req_positive(node, "radius")?,
+                    self.req_positive(node, "height")?,
+                    material(self)?,
+                ))
+            }
+            "pyramid" => {
+                self.check_keys(
+                    node,
+                    &["type", "p0", "p1", "height", "material", "transform"],
+                )?;
+                Arc::new(Pyramid::new(
+                    Point3::from_array(self.req_vec3(node, "p0")?),
+                    Point3::from_array(self.req_vec3(node, "p1")?),
+                    self.req_f32(node, "height")?,
+                    material(self)?,
+                ))
+            }
+            "group" => {
+                self.check_keys(node, &["type", "children", "transform"])?;
+                let children = self.required(node, "children")?;
+                let mut group = HittableList::new();
+                for child in self.tables(children, node, "children")? {
+                    group.push(self.parse_object(&child, is_light)?);
+                }
+                Arc::new(group)
+            }
+            "medium" => {
+                self.check_keys(
+                    node,
+                    &[
+                        "type",
+                        "boundary",
+                        "density",
+                        "albedo",
+                        "field",
+                        "transform",
+                    ],
+                )?;
+                let boundary = self.table(self.required(node, "boundary")?, node, "boundary")?;
+                if boundary.item("material").is_some() {
+                    return Err(self.error(
+                        boundary.key_span("material"),
+                        &boundary.key_path("material"),
+                        "a medium boundary has no material; the medium scatters with its `albedo`"
+                            .to_string(),
+                    ));
+                }
+                let boundary = self.parse_object(&boundary, true)?;
+                let density = self.req_positive(node, "density")?;
+                let albedo = self.req_texture(node, "albedo")?;
+                match node.item("field") {
+                    Some(field) => Arc::new(HeterogeneousMedium::from_texture(
+                        boundary,
+                        density,
+                        self.parse_density(&self.table(field, node, "field")?)?,
+                        albedo,
+                    )),
+                    None => Arc::new(ConstantMedium::from_texture(boundary, density, albedo)),
+                }
+            }
+            "obj" | "ply" => {
+                self.check_keys(node, &["type", "file", "material", "transform"])?;
+                let material: Option<Arc<dyn Material>> = match (node.item("material"), is_light) {
+                    (Some(_), _) => Some(self.req_material(node, "material")?),
+                    (None, true) => Some(Arc::new(Empty)),
+                    (None, false) => None,
+                };
+                let path = self.resolve_path(self.req_str(node, "file")?);
+                let mesh = match kind {
+                    "obj" => load_obj(
+                        &path,
+                        material.unwrap_or_else(|| {
+                            Arc::new(Lambertian::from_color(Color::splat(0.73), 1.0))
+                        }),
+                        &mut self.warnings.borrow_mut(),
+                    ),
+                    _ => load_ply(&path, material),
+                };
+                let mesh = mesh.map_err(|err| {
+                    self.error(
+                        node.key_span("file"),
+                        &node.key_path("file"),
+                        err.to_string(),
+                    )
+                })?;
+                Arc::new(mesh)
+            }
+            unknown => {
+                return Err(self.error(
+                    node.key_span("type"),
+                    &node.key_path("type"),
+                    format!("unknown object type `{}`", unknown),
+                ))
+            }
+        };
+
+        self.apply_transforms(node, object)
+    }
+
+    fn apply_transforms(
+        &self,
+        node: &Node,
+        mut object: Arc<dyn Primitive>,
+    ) -> Result<Arc<dyn Primitive>, SceneFileError> {
+        let Some(item) = node.item("transform") else {
+            return Ok(object);
+        };
+
+        let path = node.key_path("transform");
+        let steps = item.as_array().ok_or_else(|| {
+            self.error(
+                item.span(),
+                &path,
+                "expected an array of transforms".to_string(),
+            )
+        })?;
+
+        for (i, step) in steps.iter().enumerate() {
+            let step_path = format!("{}[{}]", path, i);
+
+            if step.as_str() == Some("mirror_yz") {
+                object = Arc::new(MirrorYZ::new(object));
+                continue;
+            }
+
+            let table = step.as_inline_table().ok_or_else(|| {
+                self.error(
+                    step.span(),
+                    &step_path,
+                    "expected `\"mirror_yz\"` or a table such as `{ rotation_y = 15 }`".to_string(),
+                )
+            })?;
+            let step = Node {
+                table,
+                span: step.span(),
+                path: step_path,
+            };
+
+            let mut keys = step.table.iter().map(|(key, _)| key);
+            let (Some(kind), None) = (keys.next(), keys.next()) else {
+                return Err(self.error(
+                    step.span.clone(),
+                    &step.path,
+                    "a transform must have exactly one key".to_string(),
+                ));
+            };
+
+            object = match kind {
+                "rotation_x" => Arc::new(RotationX::new(object, self.req_f32(&step, kind)?)),
+                "rotation_y" => Arc::new(RotationY::new(object, self.req_f32(&step, kind)?)),
+                "rotation_z" => Arc::new(RotationZ::new(object, self.req_f32(&step, kind)?)),
+                "translation" => Arc::new(Translation::new(
+                    object,
+                    Vec3::from_array(self.req_vec3(&step, kind)?),
+                )),
+                unknown => {
+                    return Err(self.error(
+                        step.key_span(unknown),
+                        &step.key_path(unknown),
+                        "unknown transform, expected one of `rotation_x`, `rotation_y`, \
+                         `rotation_z`, `translation`, `mirror_yz`"
+                            .to_string(),
+                    ))
+                }
+            };
+        }
+
+        Ok(object)
+    }
+
+    /// Relative asset paths are taken from the directory of the scene file.
+    fn resolve_path(&self, file: &str) -> PathBuf {
+        let path = Path::new(file);
+        match self.file.parent() {
+            Some(dir) if path.is_relative() => dir.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+
+    // ─────────────────────────────
+
+    fn error(&self, span: Span, key: &str, message: String) -> SceneFileError {
+        let line = span.map(|span| {
+            let end = span.start.min(self.source.len());
+            self.source[..end].matches('\n').count() + 1
+        });
+
+        SceneFileError::Parse {
+            file: self.file.to_path_buf(),
+            line,
+            key: key.to_string(),
+            message,
+        }
+    }
+
+    fn check_keys(&self, node: &Node, allowed: &[&str]) -> Result<(), SceneFileError> {
+        for (key, _) in node.table.iter() {
+            if !allowed.contains(&key) {
+                return Err(self.error(
+                    node.key_span(key),
+                    &node.key_path(key),
+                    format!("unknown key, expected one of: {}", allowed.join(", ")),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn table<'doc>(
+        &self,
+        item: &'doc Item,
+        parent: &Node,
+        key: &str,
+    ) -> Result<Node<'doc>, SceneFileError> {
+        let path = parent.key_path(key);
+        let table = item
+            .as_table_like()
+            .ok_or_else(|| self.error(item.span(), &path, "expected a table".to_string()))?;
+
+        Ok(Node {
+            table,
+            span: item.span().or_else(|| parent.key_span(key)),
+            path,
+        })
+    }
+
+    /// Accepts both `[[key]]` arrays of tables and inline `key = [{ .. }, { .. }]` arrays.
+    fn tables<'doc>(
+        &self,
+        item: &'doc Item,
+        parent: &Node,
+        key: &str,
+    ) -> Result<Vec<Node<'doc>>, SceneFileError> {
+        let path = parent.key_path(key);
+
+        if let Some(array) = item.as_array_of_tables() {
+            return Ok(array
+                .iter()
+                .enumerate()
+                .map(|(i, table)| Node {
+                    table,
+                    span: table.span(),
+                    path: format!("{}[{}]", path, i),
+                })
+                .collect());
+        }
+
+        let array = item.as_array().ok_or_else(|| {
+            self.error(
+                item.span(),
+                &path,
+                "expected an array of tables".to_string(),
+            )
+        })?;
+
+        array
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let path = format!("{}[{}]", path, i);
+                match value.as_inline_table() {
+                    Some(table) => Ok(Node {
+                        table,
+                        span: value.span(),
+                        path,
+                    }),
+                    None => Err(self.error(value.span(), &path, "expected a table".to_string())),
+                }
+            })
+            .collect()
+    }
+
+    fn required<'doc>(&self, node: &Node<'doc>, key: &str) -> Result<&'doc Item, SceneFileError> {
+        node.item(key).ok_or_else(|| {
+            self.error(
+                node.span.clone(),
+                &node.key_path(key),
+                "missing required key".to_string(),
+            )
+        })
+    }
+
+    fn value<'doc>(&self, node: &Node<'doc>, key: &str) -> Result<&'doc Value, SceneFileError> {
+        let item = self.required(node, key)?;
+        item.as_value().ok_or_else(|| {
+            self.error(
+                item.span(),
+                &node.key_path(key),
+                "expected a value".to_string(),
+            )
+        })
+    }
+
+    fn req_str<'doc>(&self, node: &Node<'doc>, key: &str) -> Result<&'doc str, SceneFileError> {
+        let value = self.value(node, key)?;
+        value.as_str().ok_or_else(|| {
+            self.error(
+                value.span(),
+                &node.key_path(key),
+                "expected a string".to_string(),
+            )
+        })
+    }
+
+    fn req_color_space(&self, node: &Node, key: &str) -> Result<ColorSpace, SceneFileError> {
+        let value = self.value(node, key)?;
+        match value.as_str() {
+            Some("srgb") => Ok(ColorSpace::Srgb),
+            Some("linear") => Ok(ColorSpace::Linear),
+            _ => Err(self.error(
+                value.span(),
+                &node.key_path(key),
+                "expected `srgb` or `linear`".to_string(),
+            )),
+        }
+    }
+
+    fn req_sampler(&self, node: &Node, key: &str) -> Result<SamplerKind, SceneFileError> {
+        let value = self.value(node, key)?;
+        match value.as_str().map(str::parse) {
+            Some(Ok(sampler)) => Ok(sampler),
+            _ => Err(self.error(
+                value.span(),
+                &node.key_path(key),
+                "expected `independent`, `stratified`, `halton`, `sobol` or `blue-noise`"
+                    .to_string(),
+            )),
+        }
+    }
+
+    fn req_f32(&self, node: &Node, key: &str) -> Result<f32, SceneFileError> {
+        let value = self.value(node, key)?;
+        as_f32(value).ok_or_else(|| {
+            self.error(
+                value.span(),
+                &node.key_path(key),
+                "expected a number".to_string(),
+            )
+        })
+    }
+
+    fn req_positive(&self, node: &Node, key: &str) -> Result<f32, SceneFileError> {
+        let x = self.req_f32(node, key)?;
+        if x > 0.0 {
+            Ok(x)
+        } else {
+            Err(self.error(
+                node.key_span(key),
+                &node.key_path(key),
+                "expected a positive number".to_string(),
+            ))
+        }
+    }
+
+    fn opt_f32(&self, node: &Node, key: &str) -> Result<Option<f32>, SceneFileError> {
+        match node.item(key) {
+            Some(_) => self.req_f32(node, key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn opt_uint(&self, node: &Node, key: &str, max: i64) -> Result<Option<i64>, SceneFileError> {
+        if node.item(key).is_none() {
+            return Ok(None);
+        }
+
+        let value = self.value(node, key)?;
+        match value.as_integer() {
+            Some(x) if (1..=max).contains(&x) => Ok(Some(x)),
+            _ => Err(self.error(
+                value.span(),
+                &node.key_path(key),
+                format!("expected an integer between 1 and {}", max),
+            )),
+        }
+    }
+
+    fn req_vec3(&self, node: &Node, key: &str) -> Result<[f32; 3], SceneFileError> {
+        let value = self.value(node, key)?;
+        as_vec3(value).ok_or_else(|| {
+            self.error(
+                value.span(),
+                &node.key_path(key),
+                "expected an array of three numbers".to_string(),
+            )
+        })
+    }
+
+    fn opt_vec3(&self, node: &Node, key: &str) -> Result<Option<[f32; 3]>, SceneFileError> {
+        match node.item(key) {
+            Some(_) => self.req_vec3(node, key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn req_resolution(&self, node: &Node, key: &str) -> Result<[usize; 3], SceneFileError> {
+        let value = self.value(node, key)?;
+        let resolution = value
+            .as_array()
+            .filter(|array| array.len() == 3)
+            .and_then(|array| {
+                let mut out = [0; 3];
+                for (slot, value) in out.iter_mut().zip(array.iter()) {
+                    *slot = usize::try_from(value.as_integer()?)
+                        .ok()
+                        .filter(|&n| n > 0)?;
+                }
+                Some(out)
+            });
+        resolution.ok_or_else(|| {
+            self.error(
+                value.span(),
+                &node.key_path(key),
+                "expected an array of three positive integers".to_string(),
+            )
+        })
+    }
+
+    fn req_values(&self, node: &Node, key: &str, len: usize) -> Result<Vec<f32>, SceneFileError> {
+        let value = self.value(node, key)?;
+        let values = value
+            .as_array()
+            .filter(|array| array.len() == len)
+            .and_then(|array| array.iter().map(as_f32).collect::<Option<Vec<_>>>());
+        values.ok_or_else(|| {
+            self.error(
+                value.span(),
+                &node.key_path(key),
+                format!("expected an array of {} numbers", len),
+            )
+        })
+    }
+
+    fn req_color(&self, node: &Node, key: &str) -> Result<Color, SceneFileError> {
+        self.req_vec3(node, key).map(Color::from_array)
+    }
+
+    /// A texture slot accepts a colour array, the name of a `[textures]` entry or an inline
+    /// texture table.
+    fn req_texture(&self, node: &Node, key: &str) -> Result<Arc<dyn Texture>, SceneFileError> {
+        let item = self.required(node, key)?;
+        let path = node.key_path(key);
+
+        if let Some(color) = item.as_value().and_then(as_vec3) {
+            return Ok(Arc::new(SolidColor::from_color(Color::from_array(color))));
+        }
+
+        if let Some(name) = item.as_str() {
+            return self.textures.get(name).cloned().ok_or_else(|| {
+                self.error(item.span(), &path, format!("undefined texture `{}`", name))
+            });
+        }
+
+        if item.is_table_like() {
+            return self.parse_texture(&self.table(item, node, key)?);
+        }
+
+        Err(self.error(
+            item.span(),
+            &path,
+            "expected a colour, a texture name or a texture table".to_string(),
+        ))
+    }
+
+    /// A refractive index is a number, the name of a preset glass (`bk7` or `diamond`), or a
+    /// `cauchy` or `sellmeier` table of coefficients for wavelengths in micrometres.
+    fn req_refractive_index(
+        &self,
+        node: &Node,
+        key: &str,
+    ) -> Result<RefractiveIndex, SceneFileError> {
+        let item = self.required(node, key)?;
+        let path = node.key_path(key);
+
+        if let Some(index) = item.as_value().and_then(as_f32) {
+            return Ok(RefractiveIndex::Constant(index));
+        }
+
+        if let Some(name) = item.as_str() {
+            return match name {
+                "bk7" => Ok(RefractiveIndex::BK7),
+                "diamond" => Ok(RefractiveIndex::DIAMOND),
+                _ => Err(self.error(
+                    item.span(),
+                    &path,
+                    format!("unknown glass `{}`, expected `bk7` or `diamond`", name),
+                )),
+            };
+        }
+
+        if item.is_table_like() {
+            let table = self.table(item, node, key)?;
+            return match self.req_str(&table, "type")? {
+                "cauchy" => {
+                    self.check_keys(&table, &["type", "a", "b"])?;
+                    Ok(RefractiveIndex::Cauchy {
+                        a: self.req_f32(&table, "a")?,
+                        b: self.req_f32(&table, "b")?,
+                    })
+                }
+                "sellmeier" => {
+                    self.check_keys(&table, &["type", "b", "c"])?;
+                    Ok(RefractiveIndex::Sellmeier {
+                        b: self.req_vec3(&table, "b")?,
+                        c: self.req_vec3(&table, "c")?,
+                    })
+                }
+                unknown => Err(self.error(
+                    table.key_span("type"),
+                    &table.key_path("type"),
+                    format!("unknown dispersion model `{}`", unknown),
+                )),
+            };
+        }
+
+        Err(self.error(
+            item.span(),
+            &path,
+            "expected a number, a glass name or a dispersion table".to_string(),
+        ))
+    }
+
+    /// A material slot accepts the name of a `[materials]` entry or an inline material table.
+    fn req_material(&self, node: &Node, key: &str) -> Result<Arc<dyn Material>, SceneFileError> {
+        let item = self.required(node, key)?;
+        let path = node.key_path(key);
+
+        if let Some(name) = item.as_str() {
+            return self.materials.get(name).cloned().ok_or_else(|| {
+                self.error(item.span(), &path, format!("undefined material `{}`", name))
+            });
+        }
+
+        if item.is_table_like() {
+            return self.parse_material(&self.table(item, node, key)?);
+        }
+
+        Err(self.error(
+            item.span(),
+            &path,
+            "expected a material name or a material table".to_string(),
+        ))
+    }
+}
+
+fn as_f32(value: &Value) -> Option<f32> {
+    value
+        .as_float()
+        .or_else(|| value.as_integer().map(|x| x as f64))
+        .map(|x| x as f32)
+}
+
+fn as_vec3(value: &Value) -> Option<[f32; 3]> {
+    let array = value.as_array()?;
+    if array.len() != 3 {
+        return None;
+    }
+
+    let mut out = [0.0; 3];
+    for (slot, value) in out.iter_mut().zip(array.iter()) {
+        *slot = as_f32(value)?;
+    }
+    Some(out)
+}
+
+// ─────────────────────────────
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFileError::Io { file, error } => {
+                write!(f, "{}: {}", file.display(), error)
+            }
+            SceneFileError::Parse {
+                file,
+                line,
+                key,
+                message,
+            } => {
+                write!(f, "{}", file.display())?;
+                if let Some(line) = line {
+                    write!(f, ":{}", line)?;
+                }
+                if !key.is_empty() {
+                    write!(f, ": `{}`", key)?;
+                }
+                write!(f, ": {}", message)
+            }
+        }
+    }
+}
+
+impl Error for SceneFileError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<SceneFile, SceneFileError> {
+        SceneFile::parse(source, Path::new("test.toml"))
+    }
+
+    fn parse_err(source: &str) -> (Option<usize>, String) {
+        match parse(source) {
+            Err(SceneFileError::Parse { line, key, .. }) => (line, key),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn bundled_cornell_box() {
+        let scene = parse(include_str!("../../assets/scenes/cornell_box.toml")).unwrap();
+        let (world, lights) = scene.build();
+
+        assert_eq!(world.objects().len(), 8);
+        assert_eq!(lights.objects().len(), 2);
+    }
+
+    #[test]
+    fn references_and_transforms() {
+        let scene = parse(
+            r#"
+            [textures.floor]
+            type = "checker"
+            even = [0.2, 0.3, 0.1]
+            odd = [0.9, 0.9, 0.9]
+            scale = 0.5
+
+            [materials.ground]
+            type = "lambertian"
+            albedo = "floor"
+
+            [[objects]]
+            type = "group"
+            transform = [{ rotation_y = 15 }, { translation = [1, 2, 3] }, "mirror_yz"]
+            children = [
+                { type = "sphere", center = [0, 0, 0], radius = 1, material = "ground" },
+                { type = "cuboid", p0 = [0, 0, 0], p1 = [1, 1, 1], material = { type = "metal", albedo = [1, 1, 1] } },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let (world, lights) = scene.build();
+        assert_eq!(world.objects().len(), 1);
+        assert!(lights.is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_key() {
+        let (line, key) = parse_err("[camera]\nvfov = 40\nfov = 30\n");
+        assert_eq!(line, Some(3));
+        assert_eq!(key, "camera.fov");
+    }
+
+    #[test]
+    fn reports_undefined_material() {
+        let (line, key) = parse_err(
+            "[[objects]]\ntype = \"sphere\"\ncenter = [0, 0, 0]\nradius = 1\nmaterial = \"nope\"\n",
+        );
+        assert_eq!(line, Some(5));
+        assert_eq!(key, "objects[0].material");
+    }
+
+    #[test]
+    fn reports_missing_key() {
+        let (line, key) = parse_err("[materials.glass]\ntype = \"dielectric\"\n");
+        assert_eq!(line, Some(1));
+        assert_eq!(key, "materials.glass.refraction_index");
+    }
+
+    #[test]
+    fn dispersive_dielectrics() {
+        let scene = parse(
+            r#"
+            [materials.crown]
+            type = "dielectric"
+            refraction_index = "bk7"
+
+            [materials.flint]
+            type = "dielectric"
+            refraction_index = { type = "cauchy", a = 1.67, b = 0.0074 }
+
+            [materials.custom]
+            type = "dielectric"
+            refraction_index = { type = "sellmeier", b = [1.0, 0.2, 1.0], c = [0.006, 0.02, 100] }
+
+            [[objects]]
+            type = "sphere"
+            center = [0, 0, 0]
+            radius = 1
+            material = "flint"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(scene.build().0.objects().len(), 1);
+
+        let (line, key) = parse_err(
+            "[materials.glass]\ntype = \"dielectric\"\nrefraction_index = { type = \"abbe\" }\n",
+        );
+        assert_eq!(line, Some(3));
+        assert_eq!(key, "materials.glass.refraction_index.type");
+    }
+
+    #[test]
+    fn heterogeneous_medium() {
+        let scene = parse(
+            r#"
+            [[objects]]
+            type = "medium"
+            boundary = { type = "sphere", center = [0, 0, 0], radius = 1 }
+            density = 2
+            albedo = [1, 1, 1]
+            field = { type = "voxels", min = [-1, -1, -1], max = [1, 1, 1], resolution = [2, 1, 1], values = [0, 1] }
+
+            [[objects]]
+            type = "medium"
+            boundary = { type = "cuboid", p0 = [2, 0, 0], p1 = [3, 1, 1] }
+            density = 1
+            albedo = [1, 1, 1]
+            field = { type = "noise", scale = 4, octaves = 3 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(scene.build().0.objects().len(), 2);
+
+        let (line, key) = parse_err(
+            "[[objects]]\ntype = \"medium\"\nboundary = { type = \"sphere\", center = [0, 0, 0], radius = 1 }\ndensity = 1\nalbedo = [1, 1, 1]\nfield = { type = \"voxels\", min = [0, 0, 0], max = [1, 1, 1], resolution = [2, 2, 2], values = [1] }\n",
+        );
+        assert_eq!(line, Some(6));
+        assert_eq!(key, "objects[0].field.values");
+    }
+
+    #[test]
+    fn reports_medium_boundary_material() {
+        let (line, key) = parse_err(
+            "[materials.white]\ntype = \"lambertian\"\nalbedo = [1, 1, 1]\n\n[[objects]]\ntype = \"medium\"\nboundary = { type = \"sphere\", center = [0, 0, 0], radius = 1, material = \"white\" }\ndensity = 1\nalbedo = [1, 1, 1]\n",
+        );
+        assert_eq!(line, Some(7));
+        assert_eq!(key, "objects[0].boundary.material");
+    }
+
+    #[test]
+    fn finds_images_next_to_the_scene_file() {
+        let dir =
+            std::env::temp_dir().join(format!("scene_file_image_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        image::RgbImage::from_pixel(1, 1, image::Rgb([255, 0, 0]))
+            .save(dir.join("red.png"))
+            .unwrap();
+        let source = r#"
+            [[objects]]
+            type = "sphere"
+            center = [0, 0, -2]
+            radius = 1
+            material = { type = "lambertian", albedo = { type = "image", file = "red.png" } }
+            "#;
+        let scene = SceneFile::parse(source, &dir.join("scene.toml")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = scene.build().0.hit(&ray, Interval::CAMERA_VIEW).unwrap();
+        let albedo = rec.material().albedo(&rec);
+        assert!(albedo.x() > 0.9 && albedo.y() == 0.0);
+    }
+
+    #[test]
+    fn reports_syntax_error() {
+        let (line, _) = parse_err("[camera]\nvfov = = 40\n");
+        assert_eq!(line, Some(2));
+    }
+}
@@ -0,0 +1,198 @@
+use super::*;
+
+/// Default number of noise octaves summed by [`NoiseDensity`].
+const DEFAULT_OCTAVES: usize = 7;
+
+/// A scalar field over space, such as the density of a participating medium.
+pub trait DensityField: Send + Sync {
+    /// Value of the field at `p`, between 0 and [`max_density`](Self::max_density).
+    fn density(&self, p: &Point3) -> f32;
+
+    /// Upper bound of the field, used as the majorant when tracking through it.
+    fn max_density(&self) -> f32;
+}
+
+/// Turbulent Perlin noise clamped to `[0, 1]`, for smoke and clouds.
+pub struct NoiseDensity {
+    perlin: Perlin,
+    scale: f32,
+    octaves: usize,
+}
+
+impl NoiseDensity {
+    pub fn new(scale: f32) -> Self {
+        Self {
+            perlin: Perlin::new(),
+            scale,
+            octaves: DEFAULT_OCTAVES,
+        }
+    }
+
+    /// Number of noise octaves summed; more give finer wisps.
+    pub fn with_octaves(mut self, octaves: usize) -> Self {
+        self.octaves = octaves.max(1);
+        self
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, p: &Point3) -> f32 {
+        self.perlin
+            .turbulence(self.scale * p.clone(), self.octaves)
+            .clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f32 {
+        1.0
+    }
+}
+
+/// Densities stored on a regular grid of voxels spanning an axis-aligned box, interpolated
+/// trilinearly between voxel centres. The field is zero outside the box.
+pub struct VoxelGrid {
+    min: Point3,
+    max: Point3,
+    resolution: [usize; 3],
+    values: Vec<f32>,
+    max_density: f32,
+}
+
+impl VoxelGrid {
+    /// `values` holds one density per voxel, with x varying fastest and z slowest. Negative
+    /// values are clamped to zero.
+    ///
+    /// # Panics
+    ///
+    /// If a dimension of `resolution` is zero or `values` does not hold one value per voxel.
+    pub fn new(min: Point3, max: Point3, resolution: [usize; 3], mut values: Vec<f32>) -> Self {
+        assert!(
+            resolution.iter().all(|&n| n > 0),
+            "voxel grid resolution must be positive"
+        );
+        assert_eq!(
+            values.len(),
+            resolution.iter().product::<usize>(),
+            "voxel grid needs one value per voxel"
+        );
+
+        for value in values.iter_mut() {
+            *value = value.max(0.0);
+        }
+        let max_density = values.iter().copied().fold(0.0, f32::max);
+
+        Self {
+            min,
+            max,
+            resolution,
+            values,
+            max_density,
+        }
+    }
+
+    fn voxel(&self, [i, j, k]: [usize; 3]) -> f32 {
+        let [nx, ny, _] = self.resolution;
+        self.values[i + nx * (j + ny * k)]
+    }
+}
+
+impl DensityField for VoxelGrid {
+    fn density(&self, p: &Point3) -> f32 {
+        let mut lower = [0; 3];
+        let mut upper = [0; 3];
+        let mut weight = [0.0; 3];
+
+        for axis in 0..3 {
+            let extent = self.max[axis] - self.min[axis];
+            let relative = (p[axis] - self.min[axis]) / extent;
+            if !(0.0..=1.0).contains(&relative) {
+                return 0.0;
+            }
+
+            // Continuous voxel coordinate, with voxel centres at whole numbers.
+            let last = self.resolution[axis] - 1;
+            let x = (relative * self.resolution[axis] as f32 - 0.5).clamp(0.0, last as f32);
+            lower[axis] = (x.floor() as usize).min(last);
+            upper[axis] = (lower[axis] + 1).min(last);
+            weight[axis] = x - lower[axis] as f32;
+        }
+
+        let mut density = 0.0;
+        for corner in 0..8 {
+            let mut index = [0; 3];
+            let mut w = 1.0;
+            for axis in 0..3 {
+                if corner & (1 << axis) == 0 {
+                    index[axis] = lower[axis];
+                    w *= 1.0 - weight[axis];
+                } else {
+                    index[axis] = upper[axis];
+                    w *= weight[axis];
+                }
+            }
+            density += w * self.voxel(index);
+        }
+        density
+    }
+
+    fn max_density(&self) -> f32 {
+        self.max_density
+    }
+}
+
+/// Uses any [`Texture`] as a density field: the mean of its channels at each point, clamped to
+/// `[0, 1]`. Surface coordinates are not available inside a volume, so `u` and `v` are zero.
+pub struct TextureDensity {
+    texture: Arc<dyn Texture>,
+}
+
+impl TextureDensity {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self { texture }
+    }
+}
+
+impl DensityField for TextureDensity {
+    fn density(&self, p: &Point3) -> f32 {
+        let color = self.texture.value(0.0, 0.0, p);
+        ((color.x() + color.y() + color.z()) / 3.0).clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f32 {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn voxel_grid_interpolates_between_centres() {
+        let grid = VoxelGrid::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 1.0),
+            [2, 1, 1],
+            vec![0.0, 1.0],
+        );
+
+        assert_eq!(grid.max_density(), 1.0);
+        assert_eq!(grid.density(&Point3::new(0.25, 0.5, 0.5)), 0.0);
+        assert_eq!(grid.density(&Point3::new(1.0, 0.5, 0.5)), 0.5);
+        assert_eq!(grid.density(&Point3::new(1.5, 0.9, 0.1)), 1.0);
+        assert_eq!(grid.density(&Point3::new(2.5, 0.5, 0.5)), 0.0);
+    }
+
+    #[test]
+    fn noise_stays_within_bounds() {
+        let noise = NoiseDensity::new(4.0);
+        for _ in 0..1000 {
+            let p = Point3::new(
+                math::random_float(),
+                math::random_float(),
+                math::random_float(),
+            );
+            let density = noise.density(&p);
+            assert!((0.0..=noise.max_density()).contains(&density));
+        }
+    }
+}
@@ -1,42 +1,61 @@
 use super::*;
 
+/// A texture sampled from an image file.
+///
+/// The pixels are decoded to linear RGB once, on load, following the colour space of the
+/// image: colour maps are sRGB, while non-colour data can opt out with
+/// [`ImageTexture::from_image_with_color_space`] and [`ColorSpace::Linear`].
 pub struct ImageTexture {
-    image: Option<RtwImage>,
+    width: u32,
+    height: u32,
+    texels: Vec<[f32; 3]>,
 }
 
 impl ImageTexture {
     pub fn from_image(filename: &str) -> Self {
-        let image = RtwImage::try_new(filename);
+        match RtwImage::try_new(filename) {
+            Ok(image) => Self::from_rtw_image(image),
+            Err(_) => Self::missing(),
+        }
+    }
+
+    pub fn from_image_with_color_space(filename: &str, color_space: ColorSpace) -> Self {
+        match RtwImage::try_new(filename) {
+            Ok(image) => Self::from_rtw_image(image.with_color_space(color_space)),
+            Err(_) => Self::missing(),
+        }
+    }
+
+    pub(crate) fn from_rtw_image(image: RtwImage) -> Self {
         Self {
-            image: if let Ok(image) = image {
-                Some(image)
-            } else {
-                None
-            },
+            width: image.width(),
+            height: image.height(),
+            texels: image.to_linear(),
+        }
+    }
+
+    fn missing() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            texels: Vec::new(),
         }
     }
 }
 
 impl Texture for ImageTexture {
     fn value(&self, u: f32, v: f32, _: &Point3) -> Color {
-        let image = if let Some(image) = &self.image {
-            image
-        } else {
+        if self.texels.is_empty() {
             return WHITE;
-        };
+        }
 
         let u = f32::clamp(u, 0.0, 1.0);
         let v = 1.0 - f32::clamp(v, 0.0, 1.0);
 
-        let i = (u * image.width() as f32) as u32;
-        let j = (v * image.height() as f32) as u32;
-        let pixel = image.pixel_data(i, j);
+        let i = ((u * self.width as f32) as u32).min(self.width - 1);
+        let j = ((v * self.height as f32) as u32).min(self.height - 1);
+        let [r, g, b] = self.texels[(j * self.width + i) as usize];
 
-        let color_scale = 1.0 / 255.0;
-        Color::new(
-            pixel[0] as f32 * color_scale,
-            pixel[1] as f32 * color_scale,
-            pixel[1] as f32 * color_scale,
-        )
+        Color::new(r, g, b)
     }
 }
@@ -1,23 +1,36 @@
 mod checker;
+mod density;
 mod image_texture;
 mod noise;
 mod perlin;
+mod scaled;
 mod solid;
+mod vertex_color;
 
 use std::sync::Arc;
 
 pub(crate) use crate::{
-    math::{self, Color, Dimensional, Point3, WHITE},
+    geometry::HitRecord,
+    math::{self, Color, ColorSpace, Dimensional, Point3, WHITE},
     util::RtwImage,
 };
 
 pub(crate) use perlin::Perlin;
 
 pub use checker::CheckerTexture;
+pub use density::{DensityField, NoiseDensity, TextureDensity, VoxelGrid};
 pub use image_texture::ImageTexture;
 pub use noise::{MarbleTexture, Melamine, NoiseTexture, WoodTexture};
+pub use scaled::ScaledTexture;
 pub use solid::SolidColor;
+pub use vertex_color::VertexColor;
 
 pub trait Texture: Send + Sync {
     fn value(&self, u: f32, v: f32, p: &Point3) -> Color;
+
+    /// Texture value at a hit. Textures that need more than the surface coordinates and point
+    /// (such as [`VertexColor`]) override this; materials should call it rather than `value`.
+    fn value_at(&self, rec: &HitRecord) -> Color {
+        self.value(rec.u(), rec.v(), rec.point())
+    }
 }
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use super::{Color, HitRecord, Point3, Texture};
+
+/// Another texture multiplied by a colour, as glTF multiplies its textures by their factors.
+pub struct ScaledTexture {
+    texture: Arc<dyn Texture>,
+    scale: Color,
+}
+
+impl ScaledTexture {
+    pub fn new(texture: Arc<dyn Texture>, scale: Color) -> Self {
+        Self { texture, scale }
+    }
+}
+
+impl Texture for ScaledTexture {
+    fn value(&self, u: f32, v: f32, p: &Point3) -> Color {
+        self.scale.mul(&self.texture.value(u, v, p))
+    }
+
+    fn value_at(&self, rec: &HitRecord) -> Color {
+        self.scale.mul(&self.texture.value_at(rec))
+    }
+}
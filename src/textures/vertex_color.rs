@@ -0,0 +1,37 @@
+use super::{Color, HitRecord, Point3, Texture, WHITE};
+
+/// Colour interpolated from the vertices of a mesh (e.g. a scanned PLY).
+///
+/// Surfaces without vertex colours get `fallback`.
+pub struct VertexColor {
+    fallback: Color,
+}
+
+impl VertexColor {
+    pub fn new() -> Self {
+        Self::with_fallback(WHITE)
+    }
+
+    pub fn with_fallback(fallback: Color) -> Self {
+        Self { fallback }
+    }
+}
+
+impl Default for VertexColor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Texture for VertexColor {
+    fn value(&self, _u: f32, _v: f32, _p: &Point3) -> Color {
+        self.fallback.clone()
+    }
+
+    fn value_at(&self, rec: &HitRecord) -> Color {
+        match rec.vertex_color() {
+            Some(color) => color.clone(),
+            None => self.fallback.clone(),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::{error::Error, fmt, io, path::Path};
+
+pub use {obj::load_obj, ply::load_ply};
+
+mod obj;
+mod ply;
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io {
+        file: String,
+        error: io::Error,
+    },
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    /// Malformed data that has no line to point at, such as a truncated binary body.
+    Invalid {
+        file: String,
+        message: String,
+    },
+    Empty(String),
+}
+
+impl MeshError {
+    fn io(file: &Path, error: io::Error) -> Self {
+        MeshError::Io {
+            file: file.display().to_string(),
+            error,
+        }
+    }
+
+    fn parse(file: &Path, line: usize, message: impl Into<String>) -> Self {
+        MeshError::Parse {
+            file: file.display().to_string(),
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn invalid(file: &Path, message: impl Into<String>) -> Self {
+        MeshError::Invalid {
+            file: file.display().to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io { file, error } => write!(f, "{}: {}", file, error),
+            MeshError::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+            MeshError::Invalid { file, message } => write!(f, "{}: {}", file, message),
+            MeshError::Empty(file) => write!(f, "{}: mesh has no faces", file),
+        }
+    }
+}
+
+impl Error for MeshError {}
@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::SplitWhitespace,
+    sync::Arc,
+};
+
+use super::MeshError;
+use crate::{
+    geometry::{MeshData, MeshFace, TriangleMesh},
+    materials::{Dielectric, DiffuseLight, Lambertian, Material, Metal},
+    math::{Color, Dimensional, Point3, UnitVec3, BLACK},
+    textures::{ImageTexture, SolidColor, Texture},
+};
+
+/// Loads a Wavefront OBJ file as a single [`TriangleMesh`].
+///
+/// Polygons are triangulated as fans. Materials referenced through `mtllib`/`usemtl` are mapped
+/// onto `Lambertian`, `Metal`, `Dielectric` or `DiffuseLight`; faces without one use
+/// `default_material`. Material libraries that cannot be read are skipped, and a warning for
+/// the caller to report is pushed onto `warnings`.
+pub fn load_obj(
+    path: impl AsRef<Path>,
+    default_material: Arc<dyn Material>,
+    warnings: &mut Vec<String>,
+) -> Result<TriangleMesh, MeshError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|error| MeshError::io(path, error))?;
+
+    parse_obj(&source, path, default_material, warnings)
+}
+
+fn parse_obj(
+    source: &str,
+    file: &Path,
+    default_material: Arc<dyn Material>,
+    warnings: &mut Vec<String>,
+) -> Result<TriangleMesh, MeshError> {
+    let dir = file.parent().unwrap_or(Path::new(""));
+
+    let mut data = MeshData {
+        materials: vec![default_material],
+        ..MeshData::default()
+    };
+
+    // Materials from `mtllib`, and the slot each one got in `data.materials` once used.
+    let mut library: HashMap<String, Arc<dyn Material>> = HashMap::new();
+    let mut slots: HashMap<String, u32> = HashMap::new();
+    let mut material = 0;
+
+    for (i, line) in source.lines().enumerate() {
+        let line_no = i + 1;
+        let err = |message: String| MeshError::parse(file, line_no, message);
+
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                let p = parse_floats(&mut tokens).map_err(err)?;
+                data.positions.push(Point3::from_array(p));
+            }
+            Some("vn") => {
+                let n = parse_floats::<3>(&mut tokens).map_err(err)?;
+                let n = UnitVec3::try_from_array(n).unwrap_or(UnitVec3::J);
+                data.normals.push(n);
+            }
+            Some("vt") => {
+                let [u, v] = parse_floats(&mut tokens).map_err(err)?;
+                data.uvs.push((u, v));
+            }
+            Some("f") => {
+                let mut vertices = Vec::with_capacity(4);
+                for token in tokens {
+                    vertices.push(parse_vertex(token, &data).map_err(err)?);
+                }
+
+                if vertices.len() < 3 {
+                    return Err(err("a face needs at least three vertices".to_string()));
+                }
+
+                for k in 1..vertices.len() - 1 {
+                    let [a, b, c] = [&vertices[0], &vertices[k], &vertices[k + 1]];
+                    data.faces.push(MeshFace {
+                        positions: [a.0, b.0, c.0],
+                        uvs: a.1.zip(b.1).zip(c.1).map(|((a, b), c)| [a, b, c]),
+                        normals: a.2.zip(b.2).zip(c.2).map(|((a, b), c)| [a, b, c]),
+                        colors: None,
+                        material,
+                    });
+                }
+            }
+            Some("mtllib") => {
+                for name in tokens {
+                    let mtl_path = dir.join(name);
+                    match fs::read_to_string(&mtl_path) {
+                        Ok(mtl) => library.extend(parse_mtl(&mtl, &mtl_path)?),
+                        Err(error) => warnings.push(format!(
+                            "skipped material library {}: {}",
+                            mtl_path.display(),
+                            error
+                        )),
+                    }
+                }
+            }
+            Some("usemtl") => {
+                let name = tokens.next().unwrap_or("");
+                material = match (slots.get(name), library.get(name)) {
+                    (Some(&slot), _) => slot,
+                    (None, Some(found)) => {
+                        let slot = data.materials.len() as u32;
+                        data.materials.push(found.clone());
+                        slots.insert(name.to_string(), slot);
+                        slot
+                    }
+                    (None, None) => 0,
+                };
+            }
+            // Groups, objects, smoothing groups, lines and points carry nothing we render.
+            _ => {}
+        }
+    }
+
+    let mesh = TriangleMesh::new(data);
+    if mesh.is_empty() {
+        return Err(MeshError::Empty(file.display().to_string()));
+    }
+
+    Ok(mesh)
+}
+
+fn parse_floats<const N: usize>(tokens: &mut SplitWhitespace) -> Result<[f32; N], String> {
+    let mut out = [0.0; N];
+    for slot in out.iter_mut() {
+        let token = tokens
+            .next()
+            .ok_or_else(|| format!("expected {} numbers", N))?;
+        *slot = token
+            .parse()
+            .map_err(|_| format!("invalid number `{}`", token))?;
+    }
+    Ok(out)
+}
+
+/// Resolves a 1-based (or negative, relative) OBJ index into `0..len`.
+fn resolve_index(token: &str, len: usize, what: &str) -> Result<u32, String> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| format!("invalid {} index `{}`", what, token))?;
+
+    let resolved = match index {
+        i if i > 0 => i - 1,
+        i if i < 0 => len as i64 + i,
+        _ => return Err(format!("{} index cannot be 0", what)),
+    };
+
+    if (0..len as i64).contains(&resolved) {
+        Ok(resolved as u32)
+    } else {
+        Err(format!("{} index {} out of range", what, index))
+    }
+}
+
+/// Parses `v`, `v/vt`, `v//vn` and `v/vt/vn` face vertices into position, uv and normal indices.
+fn parse_vertex(token: &str, data: &MeshData) -> Result<(u32, Option<u32>, Option<u32>), String> {
+    let mut parts = token.split('/');
+
+    let position = resolve_index(parts.next().unwrap_or(""), data.positions.len(), "vertex")?;
+
+    let uv = match parts.next() {
+        Some("") | None => None,
+        Some(t) => Some(resolve_index(t, data.uvs.len(), "texture")?),
+    };
+
+    let normal = match parts.next() {
+        Some("") | None => None,
+        Some(n) => Some(resolve_index(n, data.normals.len(), "normal")?),
+    };
+
+    Ok((position, uv, normal))
+}
+
+// ─────────────────────────────
+
+/// The subset of an MTL `newmtl` block that maps onto our materials.
+struct MtlEntry {
+    kd: Color,
+    ks: Color,
+    ke: Color,
+    ns: f32,
+    ni: f32,
+    dissolve: f32,
+    illum: u32,
+    map_kd: Option<PathBuf>,
+    map_ke: Option<PathBuf>,
+}
+
+impl Default for MtlEntry {
+    fn default() -> Self {
+        Self {
+            kd: Color::splat(0.8),
+            ks: BLACK,
+            ke: BLACK,
+            ns: 0.0,
+            ni: 1.5,
+            dissolve: 1.0,
+            illum: 2,
+            map_kd: None,
+            map_ke: None,
+        }
+    }
+}
+
+impl MtlEntry {
+    /// - Emissive (`Ke`/`map_Ke`) → `DiffuseLight`
+    /// - Transparent (`d`/`Tr` < 1, or `illum` 4, 6, 7) → `Dielectric` with `Ni`
+    /// - Mirror-like (`illum` 3 or 5) → `Metal` with `Ks`, fuzz derived from `Ns`
+    /// - Anything else → `Lambertian` with `Kd`/`map_Kd`
+    fn into_material(self) -> Arc<dyn Material> {
+        let texture = |map: Option<PathBuf>, color: Color| -> Arc<dyn Texture> {
+            match map {
+                Some(map) => Arc::new(ImageTexture::from_image(&map.to_string_lossy())),
+                None => Arc::new(SolidColor::from_color(color)),
+            }
+        };
+
+        let is_emissive =
+            self.map_ke.is_some() || self.ke.x().max(self.ke.y()).max(self.ke.z()) > 0.0;
+
+        if is_emissive {
+            Arc::new(DiffuseLight::from_texture(texture(self.map_ke, self.ke)))
+        } else if self.dissolve < 1.0 || matches!(self.illum, 4 | 6 | 7) {
+            Arc::new(Dielectric::new(self.ni))
+        } else if matches!(self.illum, 3 | 5) {
+            let fuzz = (2.0 / (self.ns + 2.0)).sqrt();
+            Arc::new(Metal::new(self.ks, fuzz))
+        } else {
+            Arc::new(Lambertian::from_texture(texture(self.map_kd, self.kd), 1.0))
+        }
+    }
+}
+
+fn parse_mtl(source: &str, file: &Path) -> Result<HashMap<String, Arc<dyn Material>>, MeshError> {
+    let dir = file.parent().unwrap_or(Path::new(""));
+
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlEntry)> = None;
+
+    for (i, line) in source.lines().enumerate() {
+        let err = |message: String| MeshError::parse(file, i + 1, message);
+
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+
+        if keyword == "newmtl" {
+            if let Some((name, entry)) = current.take() {
+                materials.insert(name, entry.into_material());
+            }
+            let name = tokens.next().unwrap_or("").to_string();
+            current = Some((name, MtlEntry::default()));
+            continue;
+        }
+
+        let Some((_, entry)) = current.as_mut() else {
+            continue;
+        };
+
+        match keyword {
+            "Kd" => entry.kd = Color::from_array(parse_floats(&mut tokens).map_err(err)?),
+            "Ks" => entry.ks = Color::from_array(parse_floats(&mut tokens).map_err(err)?),
+            "Ke" => entry.ke = Color::from_array(parse_floats(&mut tokens).map_err(err)?),
+            "Ns" => [entry.ns] = parse_floats(&mut tokens).map_err(err)?,
+            "Ni" => [entry.ni] = parse_floats(&mut tokens).map_err(err)?,
+            "d" => [entry.dissolve] = parse_floats(&mut tokens).map_err(err)?,
+            "Tr" => {
+                let [tr] = parse_floats(&mut tokens).map_err(err)?;
+                entry.dissolve = 1.0 - tr;
+            }
+            "illum" => {
+                let token = tokens.next().unwrap_or("");
+                entry.illum = token
+                    .parse()
+                    .map_err(|_| err(format!("invalid illumination model `{}`", token)))?;
+            }
+            // Texture options (`-s`, `-o`, ...) precede the file name, which comes last.
+            "map_Kd" => entry.map_kd = tokens.last().map(|name| dir.join(name)),
+            "map_Ke" => entry.map_ke = tokens.last().map(|name| dir.join(name)),
+            _ => {}
+        }
+    }
+
+    if let Some((name, entry)) = current {
+        materials.insert(name, entry.into_material());
+    }
+
+    Ok(materials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::Hittable,
+        materials::Empty,
+        math::{Interval, Ray, Vec3},
+    };
+
+    fn parse(source: &str) -> Result<TriangleMesh, MeshError> {
+        parse_obj(
+            source,
+            Path::new("test.obj"),
+            Arc::new(Empty),
+            &mut Vec::new(),
+        )
+    }
+
+    #[test]
+    fn warns_about_missing_material_libraries() {
+        let mut warnings = Vec::new();
+        let mesh = parse_obj(
+            "mtllib missing.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl red\nf 1 2 3\n",
+            Path::new("test.obj"),
+            Arc::new(Empty),
+            &mut warnings,
+        )
+        .unwrap();
+
+        assert_eq!(mesh.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("missing.mtl"), "{}", warnings[0]);
+    }
+
+    #[test]
+    fn triangulates_polygons() {
+        let mesh = parse(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+             vn 0 0 1\n\
+             f 1/1/1 2/2/1 3/3/1 4/4/1\n",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.len(), 2);
+    }
+
+    #[test]
+    fn interpolates_uvs() {
+        let mesh =
+            parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nf -3/1 -2/2 -1/3\n").unwrap();
+
+        let ray = Ray::new(Point3::new(0.25, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, Interval::CAMERA_VIEW).unwrap();
+        let (u, v) = rec.get_uv();
+
+        assert!((u - 0.25).abs() < 1e-5);
+        assert!((v - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn reports_bad_index_line() {
+        match parse("v 0 0 0\nv 1 0 0\n\nf 1 2 3\n") {
+            Err(MeshError::Parse { line, .. }) => assert_eq!(line, 4),
+            _ => panic!("expected a parse error"),
+        }
+    }
+}
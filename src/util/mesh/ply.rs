@@ -0,0 +1,647 @@
+use std::{fs, path::Path, str::Lines, str::SplitWhitespace, sync::Arc};
+
+use super::MeshError;
+use crate::{
+    geometry::{MeshData, MeshFace, TriangleMesh},
+    materials::{Lambertian, Material},
+    math::{Color, ColorSpace, Point3, UnitVec3},
+    textures::VertexColor,
+};
+
+/// Loads a Stanford PLY file (ASCII, binary little-endian or binary big-endian) as a
+/// [`TriangleMesh`].
+///
+/// Positions are required; normals (`nx ny nz`), texture coordinates (`u v`, `s t`,
+/// `texture_u texture_v`) and colours (`red green blue`) are read when present. Integer colours
+/// are taken to be sRGB, like 8-bit images, and floating point ones linear. Polygons are
+/// triangulated as fans.
+///
+/// Every face uses `material`. When it is `None`, meshes with vertex colours get a `Lambertian`
+/// with a [`VertexColor`] texture and the rest a light grey `Lambertian`.
+pub fn load_ply(
+    path: impl AsRef<Path>,
+    material: Option<Arc<dyn Material>>,
+) -> Result<TriangleMesh, MeshError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|error| MeshError::io(path, error))?;
+
+    parse_ply(&bytes, path, material)
+}
+
+fn parse_ply(
+    bytes: &[u8],
+    file: &Path,
+    material: Option<Arc<dyn Material>>,
+) -> Result<TriangleMesh, MeshError> {
+    let header = Header::parse(bytes, file)?;
+    let mut body = Body::new(
+        &bytes[header.body_offset..],
+        header.format,
+        header.body_line,
+    )
+    .map_err(|message| MeshError::invalid(file, message))?;
+
+    let mut data = MeshData::default();
+    let mut has_colors = false;
+
+    for element in &header.elements {
+        let result = match element.name.as_str() {
+            "vertex" => read_vertices(element, &mut body, &mut data).map(|colors| {
+                has_colors = colors;
+            }),
+            "face" => read_faces(element, &mut body, &mut data),
+            _ => skip_element(element, &mut body),
+        };
+
+        result.map_err(|message| body.error(file, message))?;
+    }
+
+    let material = material.unwrap_or_else(|| match has_colors {
+        true => Arc::new(Lambertian::from_texture(Arc::new(VertexColor::new()), 1.0)),
+        false => Arc::new(Lambertian::from_color(Color::splat(0.73), 1.0)),
+    });
+    data.materials.push(material);
+
+    let mesh = TriangleMesh::new(data);
+    if mesh.is_empty() {
+        return Err(MeshError::Empty(file.display().to_string()));
+    }
+
+    Ok(mesh)
+}
+
+/// Reads every vertex, returning whether they carry colours.
+fn read_vertices(element: &Element, body: &mut Body, data: &mut MeshData) -> Result<bool, String> {
+    let find = |names: &[&str]| -> Option<usize> {
+        element
+            .properties
+            .iter()
+            .position(|p| names.contains(&p.name.as_str()) && p.kind.scalar().is_some())
+    };
+    let find_all = |names: &[&[&str]]| -> Option<Vec<usize>> {
+        names
+            .iter()
+            .map(|alternatives| find(alternatives))
+            .collect()
+    };
+
+    let position = find_all(&[&["x"], &["y"], &["z"]])
+        .ok_or_else(|| "vertex element has no x, y, z properties".to_string())?;
+    let normal = find_all(&[&["nx"], &["ny"], &["nz"]]);
+    let uv = find_all(&[
+        &["u", "s", "texture_u", "texture_s"],
+        &["v", "t", "texture_v", "texture_t"],
+    ]);
+    let color = find_all(&[
+        &["red", "diffuse_red"],
+        &["green", "diffuse_green"],
+        &["blue", "diffuse_blue"],
+    ]);
+
+    // Integer colour channels are normalized by the range of their type, and decoded from sRGB.
+    let color_decode: Vec<(f32, ColorSpace)> = color
+        .iter()
+        .flatten()
+        .map(|&i| {
+            let scalar = element.properties[i].kind.scalar().unwrap();
+            let color_space = match scalar {
+                Scalar::F32 | Scalar::F64 => ColorSpace::Linear,
+                _ => ColorSpace::Srgb,
+            };
+            (scalar.normalization(), color_space)
+        })
+        .collect();
+
+    let mut values = vec![0.0; element.properties.len()];
+
+    for _ in 0..element.count {
+        for (slot, property) in values.iter_mut().zip(&element.properties) {
+            match property.kind {
+                Kind::Scalar(scalar) => *slot = body.read(scalar)? as f32,
+                Kind::List { count, item } => {
+                    for _ in 0..body.read_count(count)? {
+                        body.read(item)?;
+                    }
+                }
+            }
+        }
+
+        let pick = |indices: &[usize]| -> [f32; 3] {
+            [values[indices[0]], values[indices[1]], values[indices[2]]]
+        };
+
+        data.positions.push(Point3::from_array(pick(&position)));
+
+        if let Some(normal) = &normal {
+            let n = UnitVec3::try_from_array(pick(normal)).unwrap_or(UnitVec3::J);
+            data.normals.push(n);
+        }
+
+        if let Some(uv) = &uv {
+            data.uvs.push((values[uv[0]], values[uv[1]]));
+        }
+
+        if let Some(color) = &color {
+            let decode = |channel: usize, value: f32| {
+                let (scale, color_space) = color_decode[channel];
+                color_space.to_linear(value * scale)
+            };
+            let [r, g, b] = pick(color);
+            data.colors
+                .push(Color::new(decode(0, r), decode(1, g), decode(2, b)));
+        }
+    }
+
+    Ok(color.is_some())
+}
+
+fn read_faces(element: &Element, body: &mut Body, data: &mut MeshData) -> Result<(), String> {
+    let indices_property = element
+        .properties
+        .iter()
+        .position(|p| matches!(p.name.as_str(), "vertex_indices" | "vertex_index"))
+        .ok_or_else(|| "face element has no vertex_indices property".to_string())?;
+
+    let vertex_count = data.positions.len();
+    let per_vertex = |buffer_len: usize| (buffer_len == vertex_count).then_some(());
+
+    let mut polygon = Vec::with_capacity(4);
+
+    for _ in 0..element.count {
+        for (i, property) in element.properties.iter().enumerate() {
+            match property.kind {
+                Kind::Scalar(scalar) => {
+                    body.read(scalar)?;
+                }
+                Kind::List { count, item } => {
+                    let len = body.read_count(count)?;
+                    let keep = i == indices_property;
+                    if keep {
+                        polygon.clear();
+                    }
+                    for _ in 0..len {
+                        let value = body.read(item)?;
+                        if keep {
+                            polygon.push(vertex_index(value, vertex_count)?);
+                        }
+                    }
+                }
+            }
+        }
+
+        if polygon.len() < 3 {
+            return Err("a face needs at least three vertices".to_string());
+        }
+
+        for k in 1..polygon.len() - 1 {
+            let indices = [polygon[0], polygon[k], polygon[k + 1]];
+            data.faces.push(MeshFace {
+                positions: indices,
+                normals: per_vertex(data.normals.len()).map(|_| indices),
+                uvs: per_vertex(data.uvs.len()).map(|_| indices),
+                colors: per_vertex(data.colors.len()).map(|_| indices),
+                material: 0,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn vertex_index(value: f64, vertex_count: usize) -> Result<u32, String> {
+    if value >= 0.0 && (value as usize) < vertex_count {
+        Ok(value as u32)
+    } else {
+        Err(format!("vertex index {} out of range", value))
+    }
+}
+
+fn skip_element(element: &Element, body: &mut Body) -> Result<(), String> {
+    for _ in 0..element.count {
+        for property in &element.properties {
+            match property.kind {
+                Kind::Scalar(scalar) => {
+                    body.read(scalar)?;
+                }
+                Kind::List { count, item } => {
+                    for _ in 0..body.read_count(count)? {
+                        body.read(item)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// ─────────────────────────────
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    /// Factor mapping a colour channel of this type to `[0, 1]`.
+    fn normalization(self) -> f32 {
+        match self {
+            Scalar::U8 => 1.0 / u8::MAX as f32,
+            Scalar::U16 => 1.0 / u16::MAX as f32,
+            Scalar::U32 => 1.0 / u32::MAX as f32,
+            Scalar::I8 => 1.0 / i8::MAX as f32,
+            Scalar::I16 => 1.0 / i16::MAX as f32,
+            Scalar::I32 => 1.0 / i32::MAX as f32,
+            Scalar::F32 | Scalar::F64 => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    Scalar(Scalar),
+    List { count: Scalar, item: Scalar },
+}
+
+impl Kind {
+    fn scalar(&self) -> Option<Scalar> {
+        match self {
+            Kind::Scalar(scalar) => Some(*scalar),
+            Kind::List { .. } => None,
+        }
+    }
+}
+
+struct Property {
+    name: String,
+    kind: Kind,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+    body_offset: usize,
+    /// Line where the body starts, for ASCII error messages.
+    body_line: usize,
+}
+
+impl Header {
+    fn parse(bytes: &[u8], file: &Path) -> Result<Self, MeshError> {
+        let mut format = None;
+        let mut elements: Vec<Element> = Vec::new();
+        let mut offset = 0;
+        let mut line_no = 0;
+
+        loop {
+            line_no += 1;
+            let err = |message: &str| MeshError::parse(file, line_no, message);
+
+            let rest = &bytes[offset..];
+            let end = rest
+                .iter()
+                .position(|&b| b == b'\n')
+                .ok_or_else(|| err("header is missing `end_header`"))?;
+            offset += end + 1;
+
+            let line = std::str::from_utf8(&rest[..end])
+                .map_err(|_| err("header is not valid text"))?
+                .trim();
+            let mut tokens = line.split_whitespace();
+
+            match (line_no, tokens.next()) {
+                (1, Some("ply")) => {}
+                (1, _) => return Err(err("not a PLY file")),
+                (_, Some("format")) => {
+                    format = Some(match (tokens.next(), tokens.next()) {
+                        (Some("ascii"), _) => Format::Ascii,
+                        (Some("binary_little_endian"), _) => Format::BinaryLittleEndian,
+                        (Some("binary_big_endian"), _) => Format::BinaryBigEndian,
+                        _ => return Err(err("unknown format")),
+                    });
+                }
+                (_, Some("element")) => {
+                    let (Some(name), Some(count)) = (tokens.next(), tokens.next()) else {
+                        return Err(err("expected `element <name> <count>`"));
+                    };
+                    let count = count
+                        .parse()
+                        .map_err(|_| err(&format!("invalid element count `{}`", count)))?;
+                    elements.push(Element {
+                        name: name.to_string(),
+                        count,
+                        properties: Vec::new(),
+                    });
+                }
+                (_, Some("property")) => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| err("property outside of an element"))?;
+                    element
+                        .properties
+                        .push(parse_property(tokens).map_err(|m| err(&m))?);
+                }
+                (_, Some("end_header")) => break,
+                // `comment`, `obj_info` and blank lines.
+                _ => {}
+            }
+        }
+
+        let format = format.ok_or_else(|| MeshError::parse(file, 2, "missing `format` line"))?;
+
+        Ok(Self {
+            format,
+            elements,
+            body_offset: offset,
+            body_line: line_no + 1,
+        })
+    }
+}
+
+fn parse_property(mut tokens: SplitWhitespace) -> Result<Property, String> {
+    let scalar = |name: Option<&str>| -> Result<Scalar, String> {
+        let name = name.unwrap_or("");
+        Scalar::parse(name).ok_or_else(|| format!("unknown property type `{}`", name))
+    };
+
+    let kind = match tokens.next() {
+        Some("list") => Kind::List {
+            count: scalar(tokens.next())?,
+            item: scalar(tokens.next())?,
+        },
+        ty => Kind::Scalar(scalar(ty)?),
+    };
+
+    let name = tokens.next().ok_or("property has no name")?.to_string();
+
+    Ok(Property { name, kind })
+}
+
+// ─────────────────────────────
+
+/// Sequential reader over the element data that follows the header.
+enum Body<'a> {
+    Ascii {
+        lines: Lines<'a>,
+        tokens: SplitWhitespace<'a>,
+        line: usize,
+    },
+    Binary {
+        bytes: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl<'a> Body<'a> {
+    fn new(bytes: &'a [u8], format: Format, first_line: usize) -> Result<Self, String> {
+        Ok(match format {
+            Format::Ascii => {
+                let text = std::str::from_utf8(bytes).map_err(|_| "body is not valid text")?;
+                Body::Ascii {
+                    lines: text.lines(),
+                    tokens: "".split_whitespace(),
+                    line: first_line - 1,
+                }
+            }
+            Format::BinaryLittleEndian | Format::BinaryBigEndian => Body::Binary {
+                bytes,
+                offset: 0,
+                big_endian: format == Format::BinaryBigEndian,
+            },
+        })
+    }
+
+    fn read(&mut self, scalar: Scalar) -> Result<f64, String> {
+        match self {
+            Body::Ascii {
+                lines,
+                tokens,
+                line,
+            } => {
+                let token = loop {
+                    if let Some(token) = tokens.next() {
+                        break token;
+                    }
+                    *tokens = lines
+                        .next()
+                        .ok_or("unexpected end of file")?
+                        .split_whitespace();
+                    *line += 1;
+                };
+                token
+                    .parse()
+                    .map_err(|_| format!("invalid number `{}`", token))
+            }
+            Body::Binary {
+                bytes,
+                offset,
+                big_endian,
+            } => {
+                let size = scalar.size();
+                let chunk = bytes
+                    .get(*offset..*offset + size)
+                    .ok_or("unexpected end of file")?;
+                *offset += size;
+
+                Ok(decode(scalar, chunk, *big_endian))
+            }
+        }
+    }
+
+    fn read_count(&mut self, scalar: Scalar) -> Result<usize, String> {
+        let count = self.read(scalar)?;
+        if count < 0.0 {
+            return Err(format!("negative list length {}", count));
+        }
+        Ok(count as usize)
+    }
+
+    fn error(&self, file: &Path, message: String) -> MeshError {
+        match self {
+            Body::Ascii { line, .. } => MeshError::parse(file, *line, message),
+            Body::Binary { offset, .. } => {
+                MeshError::invalid(file, format!("byte {} of the body: {}", offset, message))
+            }
+        }
+    }
+}
+
+fn decode(scalar: Scalar, chunk: &[u8], big_endian: bool) -> f64 {
+    macro_rules! num {
+        ($ty:ty) => {{
+            let bytes = chunk.try_into().unwrap();
+            if big_endian {
+                <$ty>::from_be_bytes(bytes) as f64
+            } else {
+                <$ty>::from_le_bytes(bytes) as f64
+            }
+        }};
+    }
+
+    match scalar {
+        Scalar::I8 => num!(i8),
+        Scalar::U8 => num!(u8),
+        Scalar::I16 => num!(i16),
+        Scalar::U16 => num!(u16),
+        Scalar::I32 => num!(i32),
+        Scalar::U32 => num!(u32),
+        Scalar::F32 => num!(f32),
+        Scalar::F64 => num!(f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::Hittable,
+        math::{Dimensional, Interval, Ray, Vec3},
+    };
+
+    const ASCII: &str = "ply
+format ascii 1.0
+comment a quad with per-vertex colour
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 255 0 0
+1 1 0 0 0 255
+0 1 0 0 0 255
+4 0 1 2 3
+";
+
+    fn hit_center(mesh: &TriangleMesh) -> Color {
+        let ray = Ray::new(Point3::new(0.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, Interval::CAMERA_VIEW).unwrap();
+        rec.vertex_color().unwrap().clone()
+    }
+
+    #[test]
+    fn reads_ascii_vertex_colors() {
+        let mesh = parse_ply(ASCII.as_bytes(), Path::new("test.ply"), None).unwrap();
+        let color = hit_center(&mesh);
+
+        assert_eq!(mesh.len(), 2);
+        assert!((color.x() - 0.5).abs() < 1e-5);
+        assert!(color.y().abs() < 1e-5);
+        assert!((color.z() - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn decodes_integer_colors_from_srgb() {
+        let source = ASCII
+            .replace("255 0 0", "128 128 128")
+            .replace("0 0 255", "128 128 128");
+        let mesh = parse_ply(source.as_bytes(), Path::new("test.ply"), None).unwrap();
+        let color = hit_center(&mesh);
+
+        assert!((color.x() - 0.2158).abs() < 1e-3, "{}", color.x());
+        assert_eq!(color.x(), color.z());
+    }
+
+    #[test]
+    fn binary_matches_ascii() {
+        for big_endian in [false, true] {
+            let format = match big_endian {
+                true => "binary_big_endian",
+                false => "binary_little_endian",
+            };
+            let mut bytes = ASCII
+                .split("end_header\n")
+                .next()
+                .unwrap()
+                .replace("ascii", format)
+                .into_bytes();
+            bytes.extend_from_slice(b"end_header\n");
+
+            let vertices = [
+                ([0.0f32, 0.0, 0.0], [255u8, 0, 0]),
+                ([1.0, 0.0, 0.0], [255, 0, 0]),
+                ([1.0, 1.0, 0.0], [0, 0, 255]),
+                ([0.0, 1.0, 0.0], [0, 0, 255]),
+            ];
+            for (position, color) in vertices {
+                for value in position {
+                    match big_endian {
+                        true => bytes.extend_from_slice(&value.to_be_bytes()),
+                        false => bytes.extend_from_slice(&value.to_le_bytes()),
+                    }
+                }
+                bytes.extend_from_slice(&color);
+            }
+            bytes.push(4);
+            for index in 0i32..4 {
+                match big_endian {
+                    true => bytes.extend_from_slice(&index.to_be_bytes()),
+                    false => bytes.extend_from_slice(&index.to_le_bytes()),
+                }
+            }
+
+            let mesh = parse_ply(&bytes, Path::new("test.ply"), None).unwrap();
+            let expected = parse_ply(ASCII.as_bytes(), Path::new("test.ply"), None).unwrap();
+
+            assert_eq!(mesh.len(), expected.len());
+            assert_eq!(hit_center(&mesh), hit_center(&expected));
+        }
+    }
+
+    #[test]
+    fn reports_ascii_line() {
+        let source = ASCII.replace("4 0 1 2 3", "4 0 1 2 9");
+
+        match parse_ply(source.as_bytes(), Path::new("test.ply"), None) {
+            Err(MeshError::Parse { line, .. }) => assert_eq!(line, 18),
+            _ => panic!("expected a parse error"),
+        }
+    }
+}
@@ -1,13 +1,20 @@
 pub mod fmt;
 
 pub use fmt::UnitVecError;
-pub use parser::parse_config;
-pub use rtw_image::{save_as_png_from_floats, save_as_ppm_from_floats, ImageError};
+pub use mesh::{load_obj, load_ply, MeshError};
+pub use parser::{parse_config, Checkpoints};
+pub use rtw_image::{
+    is_hdr_output, layer_filename, save_as_exr, save_as_hdr, save_as_png_from_floats,
+    save_as_ppm_from_floats, save_image_from_floats, ImageError,
+};
 
+pub(crate) use json::Json;
 pub(crate) use progress::Progress;
 pub(crate) use rtw_image::RtwImage;
 
 mod convert;
+mod json;
+mod mesh;
 mod parser;
 mod progress;
 mod rtw_image;
@@ -1,6 +1,24 @@
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    time::Duration,
+};
+
 use clap::Parser;
 
-use crate::{camera::Camera, math::Point3, scenes::*};
+use crate::{
+    camera::{Aov, Camera, CheckpointError, Denoiser, Filter, Region, RenderState, TileOrder},
+    integrators::IntegratorKind,
+    math::{
+        rng,
+        sampler::SamplerKind,
+        tonemap::{ToneMap, ToneMapper},
+        Point3,
+    },
+    scenes::*,
+};
 
 macro_rules! apply_camera_settings {
     ($builder:expr, $config:expr, [ $( $field:ident ),* ]) => {{
@@ -48,6 +66,48 @@ pub struct CameraConfig {
     /// Example: --look-at 278,273,0 or -t 278,273,0
     #[arg(short = 't', long)]
     look_at: Option<Point3>,
+
+    /// Adaptive sampling: after --samples-per-pixel samples, pixels keep sampling until the
+    /// standard error of their luminance is below this fraction of it
+    /// Example: --adaptive-threshold 0.02
+    #[arg(long)]
+    adaptive_threshold: Option<f32>,
+
+    /// Most samples per pixel with adaptive sampling (8 times --samples-per-pixel by default)
+    /// Example: --max-spp 1024
+    #[arg(long = "max-spp")]
+    max_samples_per_pixel: Option<u16>,
+
+    /// How the samples of each pixel are spread:
+    /// independent, stratified, halton, sobol (default) or blue-noise
+    /// Example: --sampler blue-noise
+    #[arg(long)]
+    sampler: Option<SamplerKind>,
+
+    /// Reconstruction filter spreading each sample over the pixels around it, with an optional
+    /// radius in pixels: box (default, radius 0.5), tent, gaussian, mitchell, lanczos or
+    /// blackman-harris
+    /// Example: --filter mitchell or --filter gaussian:2
+    #[arg(long)]
+    filter: Option<Filter>,
+
+    /// Side in pixels of the square tiles the image is cut into for the threads (16 by default)
+    /// Example: --tile-size 32
+    #[arg(long)]
+    tile_size: Option<u32>,
+
+    /// Order in which the tiles are rendered: scanline, spiral or hilbert (default)
+    /// Example: --tile-order spiral
+    #[arg(long)]
+    tile_order: Option<TileOrder>,
+
+    /// Render only the region of the image starting at pixel x,y that is w pixels wide and
+    /// h pixels high; the output holds that region, with the same pixels as a full render
+    /// (except that with --adaptive-threshold, pixels near its edges may take other sample
+    /// counts)
+    /// Example: --crop 120,80,64,48
+    #[arg(long)]
+    crop: Option<Region>,
 }
 
 #[derive(Parser, Debug)]
@@ -75,22 +135,185 @@ pub struct Args {
     #[arg(short, long, default_value = "cornell_box")]
     scene: String,
 
-    /// Output file path
+    /// Scene description file (TOML, or glTF 2.0 as .gltf/.glb), takes precedence over --scene
+    /// Example: --scene-file assets/scenes/cornell_box.toml
+    #[arg(long)]
+    scene_file: Option<PathBuf>,
+
+    /// Output file path; the extension selects the format
+    /// (.exr/.hdr keep linear HDR radiance, .png/.ppm/... are 8-bit)
     #[arg(short, long, default_value = "output.png")]
     output: String,
 
+    /// Tone mapper applied to 8-bit outputs (ignored for .exr/.hdr):
+    /// clamp, reinhard, reinhard-extended[:white], aces, hable, agx
+    /// Example: --tonemap aces or --tonemap reinhard-extended:8
+    #[arg(long, default_value = "clamp")]
+    tonemap: ToneMapper,
+
+    /// Exposure adjustment in stops, applied before tone mapping
+    /// Example: --exposure -1.5
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    exposure: f32,
+
+    /// Light transport algorithm
+    /// Supported values: path, mixture, mis, spectral, each with an optional :roulette_depth,
+    /// bdpt, photon with optional :photons_per_pass and :radius, and the debug views normal,
+    /// geometric-normal, depth[:max_distance], uv, albedo, facing and ao[:radius]
+    /// Example: --integrator mis or --integrator path:5 or --integrator spectral
+    #[arg(long, default_value = "path")]
+    integrator: IntegratorKind,
+
+    /// Extra channels to write next to the image, each to a file named after the output
+    /// (output.albedo.png, ...): albedo, normal, depth, position, object-id, material-id,
+    /// emission, direct, indirect and sample-count
+    /// Example: --aov albedo,normal,depth
+    #[arg(long, value_delimiter = ',')]
+    aov: Vec<Aov>,
+
+    /// Filter the noise out of the image before tone mapping, guided by the albedo, normals
+    /// and depth of the first surface in each pixel; makes previews at 16-64 spp usable
+    #[arg(long)]
+    denoise: bool,
+
+    /// Seed of the random numbers; the same seed renders the same image, bit for bit
+    /// Example: --seed 7
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// File in which the render saves its progress, to be continued with --resume
+    /// (the output with a .ckpt extension by default)
+    /// Example: --checkpoint svrnc.ckpt
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// Save the image so far and a checkpoint every N passes; a pass takes one sample per
+    /// pixel, or a batch of --samples-per-pixel with adaptive sampling
+    /// Example: --checkpoint-passes 16
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    checkpoint_passes: Option<u32>,
+
+    /// Save the image so far and a checkpoint every S seconds
+    /// Example: --checkpoint-seconds 600
+    #[arg(long, value_parser = parse_seconds)]
+    checkpoint_seconds: Option<Duration>,
+
+    /// Continue the render saved in the checkpoint, taking only the samples it lacks;
+    /// raise --samples-per-pixel to add samples to a finished render. Edits to the scene
+    /// file are detected, but not to the meshes and images it loads
+    #[arg(long)]
+    resume: bool,
+
     #[command(flatten)]
     camera_config: CameraConfig,
 }
 
-pub fn parse_config() -> (Box<dyn Scene>, Camera, String) {
+/// Where and how often a render saves its progress, when any of `--checkpoint`,
+/// `--checkpoint-passes`, `--checkpoint-seconds` or `--resume` is given.
+pub struct Checkpoints {
+    pub path: PathBuf,
+    /// Identifies the scene, integrator and camera the checkpoint belongs to. Of a scene file,
+    /// only its own text is hashed, not the assets it loads.
+    pub key: u64,
+    every_passes: Option<u32>,
+    every: Option<Duration>,
+    /// The progress read back with `--resume`.
+    pub resume: Option<RenderState>,
+}
+
+impl Checkpoints {
+    /// Whether to save after pass `passes`, `elapsed` after the last save.
+    pub fn is_due(&self, passes: u32, elapsed: Duration) -> bool {
+        self.every_passes
+            .is_some_and(|every| passes.is_multiple_of(every))
+            || self.every.is_some_and(|every| elapsed >= every)
+    }
+}
+
+pub fn parse_config() -> (Box<dyn Scene>, Camera, String, ToneMap, Option<Checkpoints>) {
     let args = Args::parse();
 
-    let scene = build_scene(args.scene.as_str());
+    // Scene files create their random content, such as noise textures, while loading.
+    rng::seed_thread(args.seed, rng::SCENE_STREAM);
+    let (scene, source) = match &args.scene_file {
+        Some(path) => (load_scene_file(path), fs::read(path).unwrap_or_default()),
+        None => (
+            build_scene(args.scene.as_str()),
+            args.scene.clone().into_bytes(),
+        ),
+    };
     let output = args.output;
-    let camera = build_camera(args.camera_config, scene.as_ref());
+    let camera = build_camera(
+        args.camera_config,
+        scene.as_ref(),
+        args.integrator,
+        args.aov,
+        args.denoise,
+        args.seed,
+    );
+    let tonemap = ToneMap::new(args.tonemap, args.exposure);
+
+    let checkpointing = args.checkpoint.is_some()
+        || args.checkpoint_passes.is_some()
+        || args.checkpoint_seconds.is_some()
+        || args.resume;
+    let checkpoints = checkpointing.then(|| {
+        let path = args
+            .checkpoint
+            .unwrap_or_else(|| PathBuf::from(&output).with_extension("ckpt"));
+        let key = camera.checkpoint_key((source, args.integrator.to_string()));
+        let resume = if args.resume {
+            resume_checkpoint(&path, key)
+        } else {
+            None
+        };
+        Checkpoints {
+            path,
+            key,
+            every_passes: args.checkpoint_passes,
+            every: args.checkpoint_seconds,
+            resume,
+        }
+    });
 
-    (scene, camera, output)
+    (scene, camera, output, tonemap, checkpoints)
+}
+
+/// A positive, finite number of seconds.
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    let seconds: f32 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid seconds: '{}'", s))?;
+    if !(seconds.is_finite() && seconds > 0.0) {
+        return Err(format!(
+            "Expected a finite number of seconds above 0, got {}",
+            s
+        ));
+    }
+    Duration::try_from_secs_f32(seconds).map_err(|err| err.to_string())
+}
+
+/// The progress saved at `path`, or none if there is no checkpoint yet.
+fn resume_checkpoint(path: &Path, key: u64) -> Option<RenderState> {
+    match RenderState::load(path, key) {
+        Ok(state) => {
+            println!(
+                "Resuming {} after {} passes",
+                path.display(),
+                state.passes()
+            );
+            Some(state)
+        }
+        Err(CheckpointError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+            eprintln!("No checkpoint at {}, starting afresh", path.display());
+            None
+        }
+        Err(err) => {
+            eprintln!("Cannot resume: {}", err);
+            process::exit(1);
+        }
+    }
 }
 
 fn build_scene(scene: &str) -> Box<dyn Scene> {
@@ -106,8 +329,52 @@ fn build_scene(scene: &str) -> Box<dyn Scene> {
     }
 }
 
-fn build_camera(config: CameraConfig, scene: &dyn Scene) -> Camera {
-    let builder = scene.default_camera();
+fn load_scene_file(path: &PathBuf) -> Box<dyn Scene> {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+
+    let scene: Result<Box<dyn Scene>, Box<dyn Error>> = match extension {
+        Some("gltf") | Some("glb") => GltfScene::load(path)
+            .map(|scene| {
+                for warning in scene.warnings() {
+                    eprintln!("{}: {}", path.display(), warning);
+                }
+                Box::new(scene) as Box<dyn Scene>
+            })
+            .map_err(Box::from),
+        _ => SceneFile::load(path)
+            .map(|scene| {
+                for warning in scene.warnings() {
+                    eprintln!("{}: {}", path.display(), warning);
+                }
+                Box::new(scene) as Box<dyn Scene>
+            })
+            .map_err(Box::from),
+    };
+
+    scene.unwrap_or_else(|err| {
+        eprintln!("Invalid scene file: {}", err);
+        process::exit(1);
+    })
+}
+
+fn build_camera(
+    config: CameraConfig,
+    scene: &dyn Scene,
+    integrator: IntegratorKind,
+    aovs: Vec<Aov>,
+    denoise: bool,
+    seed: u64,
+) -> Camera {
+    let builder = scene
+        .default_camera()
+        .integrator(integrator.build())
+        .aovs(aovs)
+        .seed(seed);
+    let builder = if denoise {
+        builder.denoiser(Denoiser::new())
+    } else {
+        builder
+    };
 
     let builder = apply_camera_settings!(
         builder,
@@ -118,9 +385,25 @@ fn build_camera(config: CameraConfig, scene: &dyn Scene) -> Camera {
             look_from,
             look_at,
             vfov,
-            samples_per_pixel
+            samples_per_pixel,
+            adaptive_threshold,
+            max_samples_per_pixel,
+            sampler,
+            filter,
+            tile_size,
+            tile_order,
+            crop
         ]
     );
 
-    builder.build()
+    let camera = builder.build();
+    if camera.region().is_empty() {
+        eprintln!(
+            "The crop region lies outside the {}x{} image",
+            camera.width(),
+            camera.height()
+        );
+        process::exit(1);
+    }
+    camera
 }
@@ -1,8 +1,11 @@
-use std::{env, fmt, io, path::PathBuf};
+use std::{
+    env, fmt, io,
+    path::{Path, PathBuf},
+};
 
-use image::ImageError as GenericImageError;
+use image::{ColorType, ImageError as GenericImageError, ImageFormat};
 
-use crate::math::{color_to_bytes, Color};
+use crate::math::{color_to_bytes, Color, ColorSpace};
 
 #[derive(Debug)]
 pub enum ImageError {
@@ -13,10 +16,19 @@ pub enum ImageError {
     InvalidPath(String),
 }
 
+/// An RGB image together with the colour space its values are encoded in.
 pub struct RtwImage {
     width: u32,
     height: u32,
-    byte_data: Vec<u8>,
+    data: ImageData,
+    color_space: ColorSpace,
+}
+
+/// The channels of an image, three per pixel.
+enum ImageData {
+    Bytes(Vec<u8>),
+    /// Floating point sources keep their values, above 1 included.
+    Floats(Vec<f32>),
 }
 
 impl RtwImage {
@@ -53,21 +65,15 @@ impl RtwImage {
         Err(ImageError::NotFound(filename.to_string()))
     }
 
+    /// Decodes an encoded image (PNG, JPEG, ...) held in memory, such as one embedded in a model.
+    pub fn try_from_memory(bytes: &[u8]) -> Result<Self, ImageError> {
+        let dyn_image = image::load_from_memory(bytes)?;
+        Ok(Self::from_dynamic_image(dyn_image))
+    }
+
     fn try_load_from_file(file_path: &PathBuf) -> Result<Self, ImageError> {
         match image::open(file_path) {
-            Ok(dyn_image) => {
-                let rgb_image = dyn_image.to_rgb8();
-                let width = rgb_image.width();
-                let height = rgb_image.height();
-
-                let byte_data: Vec<u8> = rgb_image.to_vec();
-
-                Ok(Self {
-                    width,
-                    height,
-                    byte_data,
-                })
-            }
+            Ok(dyn_image) => Ok(Self::from_dynamic_image(dyn_image)),
             Err(error) => Err(ImageError::LoadError(format!(
                 "Error loading image from path {:?}: {}",
                 file_path, error
@@ -75,6 +81,35 @@ impl RtwImage {
         }
     }
 
+    /// 8-bit sources are assumed to be sRGB; floating point sources (EXR, HDR) are linear.
+    fn from_dynamic_image(dyn_image: image::DynamicImage) -> Self {
+        let (width, height) = (dyn_image.width(), dyn_image.height());
+        let (data, color_space) = match dyn_image.color() {
+            ColorType::Rgb32F | ColorType::Rgba32F => (
+                ImageData::Floats(dyn_image.to_rgb32f().into_raw()),
+                ColorSpace::Linear,
+            ),
+            _ => (
+                ImageData::Bytes(dyn_image.to_rgb8().into_raw()),
+                ColorSpace::Srgb,
+            ),
+        };
+
+        Self {
+            width,
+            height,
+            data,
+            color_space,
+        }
+    }
+
+    /// Overrides the colour space the bytes are interpreted in, e.g. `Linear` for normal or
+    /// roughness maps, which hold data rather than colour.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
     pub fn width(&self) -> u32 {
         self.width
     }
@@ -83,16 +118,31 @@ impl RtwImage {
         self.height
     }
 
-    pub fn pixel_data(&self, x: u32, y: u32) -> &[u8] {
-        static MAGENTA: [u8; 3] = [255, 0, 255];
-        if self.byte_data.is_empty() {
-            return &MAGENTA;
-        }
+    /// Decodes every pixel to linear RGB, row by row from the top.
+    pub fn to_linear(&self) -> Vec<[f32; 3]> {
+        match &self.data {
+            ImageData::Bytes(bytes) => {
+                let mut table = [0.0; 256];
+                for (byte, value) in table.iter_mut().enumerate() {
+                    *value = self.color_space.to_linear(byte as f32 / 255.0);
+                }
 
-        let clamped_x = x.clamp(0, self.width.saturating_sub(1));
-        let clamped_y = y.clamp(0, self.height.saturating_sub(1));
-        let index = (clamped_y * self.width + clamped_x) * 3;
-        &self.byte_data[(index as usize)..(index as usize + 3)]
+                bytes
+                    .chunks_exact(3)
+                    .map(|rgb| {
+                        [
+                            table[rgb[0] as usize],
+                            table[rgb[1] as usize],
+                            table[rgb[2] as usize],
+                        ]
+                    })
+                    .collect()
+            }
+            ImageData::Floats(floats) => floats
+                .chunks_exact(3)
+                .map(|rgb| [rgb[0], rgb[1], rgb[2]].map(|value| self.color_space.to_linear(value)))
+                .collect(),
+        }
     }
 }
 
@@ -170,3 +220,152 @@ pub fn save_as_png_from_floats(
 
     save_as_png(width, height, &byte_data, filename)
 }
+
+/// Writes linear radiance as 32-bit float OpenEXR, without clamping or gamma.
+pub fn save_as_exr(
+    width: u32,
+    height: u32,
+    pixels: &[f32],
+    filename: &str,
+) -> Result<(), ImageError> {
+    save_as_float_image(width, height, pixels, filename, ImageFormat::OpenExr)
+}
+
+/// Writes linear radiance as Radiance RGBE (`.hdr`), without clamping or gamma.
+pub fn save_as_hdr(
+    width: u32,
+    height: u32,
+    pixels: &[f32],
+    filename: &str,
+) -> Result<(), ImageError> {
+    save_as_float_image(width, height, pixels, filename, ImageFormat::Hdr)
+}
+
+/// Picks the writer from the extension of `filename`: `.exr` and `.hdr` keep the linear
+/// radiance, `.ppm` and anything else (PNG, JPEG, ...) are gamma corrected 8-bit.
+pub fn save_image_from_floats(
+    width: u32,
+    height: u32,
+    pixels: &[f32],
+    filename: &str,
+) -> Result<(), ImageError> {
+    match extension(filename).as_deref() {
+        Some("exr") => save_as_exr(width, height, pixels, filename),
+        Some("hdr") => save_as_hdr(width, height, pixels, filename),
+        Some("ppm") => save_as_ppm_from_floats(width, height, pixels, filename),
+        _ => save_as_png_from_floats(width, height, pixels, filename),
+    }
+}
+
+/// Whether `filename` selects a floating point format that keeps linear radiance.
+pub fn is_hdr_output(filename: &str) -> bool {
+    matches!(extension(filename).as_deref(), Some("exr") | Some("hdr"))
+}
+
+/// Where to write the `layer` image of a render saved to `filename`: `out.png` becomes
+/// `out.layer.png`.
+pub fn layer_filename(filename: &str, layer: &str) -> String {
+    let path = Path::new(filename);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    let name = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(extension) => format!("{}.{}.{}", stem, layer, extension),
+        None => format!("{}.{}", stem, layer),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn extension(filename: &str) -> Option<String> {
+    Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+fn save_as_float_image(
+    width: u32,
+    height: u32,
+    pixels: &[f32],
+    filename: &str,
+    format: ImageFormat,
+) -> Result<(), ImageError> {
+    let img = image::Rgb32FImage::from_raw(width, height, pixels.to_vec())
+        .ok_or_else(|| ImageError::LoadError("Failed to create image from raw data".to_string()))?;
+
+    img.save_with_format(filename, format)
+        .map_err(ImageError::ImageCrateError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn decodes_according_to_color_space() {
+        let png = env::temp_dir().join(format!("rtw_image_test_decode_{}.png", process::id()));
+        image::RgbImage::from_raw(1, 1, vec![0, 128, 255])
+            .unwrap()
+            .save(&png)
+            .unwrap();
+        let image = RtwImage::try_new(png.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&png).unwrap();
+
+        let [r, g, b] = image.to_linear()[0];
+        assert_eq!((r, b), (0.0, 1.0));
+        assert!((g - 0.2158).abs() < 1e-3, "{}", g);
+
+        let [_, g, _] = image.with_color_space(ColorSpace::Linear).to_linear()[0];
+        assert!((g - 128.0 / 255.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn keeps_float_sources_unclamped() {
+        let exr = env::temp_dir().join(format!("rtw_image_test_float_{}.exr", process::id()));
+        save_image_from_floats(1, 1, &[4.5, 0.125, 0.3], exr.to_str().unwrap()).unwrap();
+        let image = RtwImage::try_new(exr.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&exr).unwrap();
+
+        let [r, g, b] = image.to_linear()[0];
+        assert_eq!((r, g), (4.5, 0.125));
+        assert!((b - 0.3).abs() < 1e-3, "{}", b);
+    }
+
+    #[test]
+    fn names_layers_after_the_output() {
+        assert_eq!(layer_filename("output.png", "albedo"), "output.albedo.png");
+        assert_eq!(
+            layer_filename("renders/box.exr", "object-id"),
+            "renders/box.object-id.exr"
+        );
+        assert_eq!(layer_filename("image", "depth"), "image.depth");
+    }
+
+    #[test]
+    fn float_formats_keep_values_above_one() {
+        let pixels = [4.5, 0.25, 0.0, 1.0, 2.0, 3.0];
+
+        for extension in ["exr", "hdr"] {
+            let path =
+                env::temp_dir().join(format!("rtw_image_test_{}.{}", process::id(), extension));
+            let filename = path.to_str().unwrap();
+
+            save_image_from_floats(2, 1, &pixels, filename).unwrap();
+            let read = image::open(&path).unwrap().to_rgb32f();
+            std::fs::remove_file(&path).unwrap();
+
+            // RGBE shares one exponent per pixel, so allow for its quantization.
+            for (written, read) in pixels.iter().zip(read.as_raw()) {
+                assert!(
+                    (written - read).abs() < 0.05,
+                    "{}: {} vs {}",
+                    extension,
+                    written,
+                    read
+                );
+            }
+        }
+    }
+}